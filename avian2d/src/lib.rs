@@ -6,15 +6,19 @@
 //! * Optionally: Add [`TnuaAvian2dSensorShape`] to the sensor entities. This means the entity of
 //!   the characters controlled by Tnua, but also other things like the entity generated by
 //!   `TnuaCrouchEnforcer`, that can be affected with a closure.
+//! * Optionally: Add [`TnuaAvian2dSensorCollisionLayers`] to the characters, to make their sensors
+//!   use different collision layers than their colliders.
 use avian2d::math::{AdjustPrecision, AsF32};
 use avian2d::{prelude::*, schedule::PhysicsStepSet};
 use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
 use bevy::prelude::*;
 use bevy_tnua_physics_integration_layer::data_for_backends::{
-    TnuaGhostPlatform, TnuaGhostSensor, TnuaMotor, TnuaProximitySensor, TnuaProximitySensorOutput,
-    TnuaRigidBodyTracker, TnuaToggle,
+    TnuaGhostPlatform, TnuaGhostSensor, TnuaGravityOverride, TnuaLocomotionCollider, TnuaMotor,
+    TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker, TnuaSensorFilter,
+    TnuaToggle, TnuaUpdateRate,
 };
 use bevy_tnua_physics_integration_layer::math::*;
+use bevy_tnua_physics_integration_layer::stage_tracing::TnuaStageTrace;
 use bevy_tnua_physics_integration_layer::subservient_sensors::TnuaSubservientSensor;
 
 use bevy_tnua_physics_integration_layer::*;
@@ -68,6 +72,15 @@ impl Plugin for TnuaAvian2dPlugin {
 #[derive(Component)]
 pub struct TnuaAvian2dSensorShape(pub Collider);
 
+/// Add this component to the character entity to make its sensors use these collision layers
+/// instead of the ones of its collider.
+///
+/// This affects all the sensors of the character, including its subservient sensors. For
+/// excluding specific entities, use [`TnuaSensorFilter`].
+#[derive(Component)]
+pub struct TnuaAvian2dSensorCollisionLayers(pub CollisionLayers);
+
+#[allow(clippy::type_complexity)]
 fn update_rigid_body_trackers_system(
     gravity: Res<Gravity>,
     mut query: Query<(
@@ -76,27 +89,37 @@ fn update_rigid_body_trackers_system(
         &AngularVelocity,
         &mut TnuaRigidBodyTracker,
         Option<&TnuaToggle>,
+        Option<&TnuaGravityOverride>,
     )>,
 ) {
-    for (transform, linaer_velocity, angular_velocity, mut tracker, tnua_toggle) in query.iter_mut()
-    {
-        match tnua_toggle.copied().unwrap_or_default() {
-            TnuaToggle::Disabled => continue,
-            TnuaToggle::SenseOnly => {}
-            TnuaToggle::Enabled => {}
-        }
-        let (_, rotation, translation) = transform.to_scale_rotation_translation();
-        *tracker = TnuaRigidBodyTracker {
-            translation: translation.adjust_precision(),
-            rotation: rotation.adjust_precision(),
-            velocity: linaer_velocity.0.extend(0.0),
-            angvel: Vector3::new(0.0, 0.0, angular_velocity.0),
-            gravity: gravity.0.extend(0.0),
-        };
-    }
+    query.par_iter_mut().for_each(
+        |(
+            transform,
+            linaer_velocity,
+            angular_velocity,
+            mut tracker,
+            tnua_toggle,
+            gravity_override,
+        )| {
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            *tracker = TnuaRigidBodyTracker {
+                translation: translation.adjust_precision(),
+                rotation: rotation.adjust_precision(),
+                velocity: linaer_velocity.0.extend(0.0),
+                angvel: Vector3::new(0.0, 0.0, angular_velocity.0),
+                gravity: gravity_override
+                    .map_or(gravity.0.extend(0.0), |gravity_override| gravity_override.0),
+            };
+        },
+    );
 }
 
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn update_proximity_sensors_system(
     spatial_query_pipeline: Res<SpatialQueryPipeline>,
     collisions: Res<Collisions>,
@@ -108,8 +131,13 @@ fn update_proximity_sensors_system(
         Option<&mut TnuaGhostSensor>,
         Option<&TnuaSubservientSensor>,
         Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
     )>,
     collision_layers_entity: Query<&CollisionLayers>,
+    locomotion_collider_query: Query<&TnuaLocomotionCollider>,
+    collider_parent_query: Query<&ColliderParent>,
+    sensor_filter_query: Query<&TnuaSensorFilter>,
+    sensor_collision_layers_query: Query<&TnuaAvian2dSensorCollisionLayers>,
     other_object_query: Query<(
         Option<(&GlobalTransform, &LinearVelocity, &AngularVelocity)>,
         Option<&CollisionLayers>,
@@ -117,6 +145,7 @@ fn update_proximity_sensors_system(
         Has<Sensor>,
     )>,
 ) {
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::Sensors);
     query.par_iter_mut().for_each(
         |(
             owner_entity,
@@ -126,12 +155,17 @@ fn update_proximity_sensors_system(
             mut ghost_sensor,
             subservient,
             tnua_toggle,
+            update_rate,
         )| {
+            let _span = stage_trace.character(owner_entity);
             match tnua_toggle.copied().unwrap_or_default() {
                 TnuaToggle::Disabled => return,
                 TnuaToggle::SenseOnly => {}
                 TnuaToggle::Enabled => {}
             }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
             let cast_origin = transform.transform_point(sensor.cast_origin.f32());
             let cast_direction = sensor.cast_direction;
             let cast_direction_2d = Dir2::new(cast_direction.truncate())
@@ -152,7 +186,16 @@ fn update_proximity_sensors_system(
                 owner_entity
             };
 
-            let collision_layers = collision_layers_entity.get(owner_entity).ok();
+            let owner_collider = locomotion_collider_query
+                .get(owner_entity)
+                .map_or(owner_entity, |locomotion_collider| locomotion_collider.0);
+
+            let collision_layers = sensor_collision_layers_query
+                .get(owner_entity)
+                .map(|sensor_collision_layers| &sensor_collision_layers.0)
+                .or_else(|_| collision_layers_entity.get(owner_collider))
+                .ok();
+            let sensor_filter = sensor_filter_query.get(owner_entity).ok();
 
             let mut final_sensor_output = None;
             if let Some(ghost_sensor) = ghost_sensor.as_mut() {
@@ -166,9 +209,27 @@ fn update_proximity_sensors_system(
                     normal,
                 } = cast_result;
 
+                let collider_parent = collider_parent_query
+                    .get(entity)
+                    .ok()
+                    .map(|collider_parent| collider_parent.get());
+
+                // The other colliders of the character (e.g. hitboxes)
+                if collider_parent == Some(owner_entity) {
+                    return true;
+                }
+
+                if let Some(sensor_filter) = sensor_filter {
+                    if !sensor_filter.allows(entity)
+                        || collider_parent.is_some_and(|parent| !sensor_filter.allows(parent))
+                    {
+                        return true;
+                    }
+                }
+
                 // This fixes https://github.com/idanarye/bevy-tnua/issues/14
-                if let Some(contacts) = collisions.get(owner_entity, entity) {
-                    let same_order = owner_entity == contacts.entity1;
+                if let Some(contacts) = collisions.get(owner_collider, entity) {
+                    let same_order = owner_collider == contacts.entity1;
                     for manifold in contacts.manifolds.iter() {
                         if !manifold.contacts.is_empty() {
                             let manifold_normal = if same_order {
@@ -248,7 +309,8 @@ fn update_proximity_sensors_system(
                 }
             };
 
-            let query_filter = SpatialQueryFilter::from_excluded_entities([owner_entity]);
+            let query_filter =
+                SpatialQueryFilter::from_excluded_entities([owner_entity, owner_collider]);
             if let Some(TnuaAvian2dSensorShape(shape)) = shape {
                 spatial_query_pipeline.shape_hits_callback(
                     shape,
@@ -296,7 +358,9 @@ fn update_proximity_sensors_system(
 
 #[allow(clippy::type_complexity)]
 fn apply_motors_system(
+    gravity: Res<Gravity>,
     mut query: Query<(
+        Entity,
         &TnuaMotor,
         &mut LinearVelocity,
         &mut AngularVelocity,
@@ -305,41 +369,61 @@ fn apply_motors_system(
         &mut ExternalForce,
         &mut ExternalTorque,
         Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+        Option<&TnuaGravityOverride>,
+        Option<&GravityScale>,
     )>,
 ) {
-    for (
-        motor,
-        mut linare_velocity,
-        mut angular_velocity,
-        mass,
-        inertia,
-        mut external_force,
-        mut external_torque,
-        tnua_toggle,
-    ) in query.iter_mut()
-    {
-        match tnua_toggle.copied().unwrap_or_default() {
-            TnuaToggle::Disabled | TnuaToggle::SenseOnly => {
-                *external_force = Default::default();
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::Motors);
+    query.par_iter_mut().for_each(
+        |(
+            entity,
+            motor,
+            mut linare_velocity,
+            mut angular_velocity,
+            mass,
+            inertia,
+            mut external_force,
+            mut external_torque,
+            tnua_toggle,
+            update_rate,
+            gravity_override,
+            gravity_scale,
+        )| {
+            let _span = stage_trace.character(entity);
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled | TnuaToggle::SenseOnly => {
+                    *external_force = Default::default();
+                    return;
+                }
+                TnuaToggle::Enabled => {}
+            }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
                 return;
             }
-            TnuaToggle::Enabled => {}
-        }
-        if motor.lin.boost.is_finite() {
-            linare_velocity.0 += motor.lin.boost.truncate();
-        }
-        if motor.lin.acceleration.is_finite() {
-            external_force.set_force(motor.lin.acceleration.truncate() * mass.0);
-        }
-        if motor.ang.boost.is_finite() {
-            angular_velocity.0 += motor.ang.boost.z;
-        }
-        if motor.ang.acceleration.is_finite() {
-            external_torque.set_torque(
-                // NOTE: I did not actually verify that this is the correct formula. Nothing uses
-                // angular acceleration yet - only angular impulses.
-                inertia.0 * motor.ang.acceleration.z,
-            );
-        }
-    }
+            // The physics engine already applies its own gravity to the rigid body, so only the
+            // difference needs to be added.
+            let gravity_offset = gravity_override.map_or(Vector2::ZERO, |gravity_override| {
+                gravity_override.0.truncate()
+                    - gravity.0 * gravity_scale.map_or(1.0, |gravity_scale| gravity_scale.0)
+            });
+            if motor.lin.boost.is_finite() {
+                linare_velocity.0 += motor.lin.boost.truncate();
+            }
+            if motor.lin.acceleration.is_finite() {
+                external_force
+                    .set_force((motor.lin.acceleration.truncate() + gravity_offset) * mass.0);
+            }
+            if motor.ang.boost.is_finite() {
+                angular_velocity.0 += motor.ang.boost.z;
+            }
+            if motor.ang.acceleration.is_finite() {
+                external_torque.set_torque(
+                    // NOTE: I did not actually verify that this is the correct formula. Nothing
+                    // uses angular acceleration yet - only angular impulses.
+                    inertia.0 * motor.ang.acceleration.z,
+                );
+            }
+        },
+    );
 }
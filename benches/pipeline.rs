@@ -0,0 +1,167 @@
+//! Benchmarks for the stages of the Tnua pipeline with crowds of characters.
+//!
+//! Each benchmark settles a crowd of walking (and occasionally jumping) characters on a flat
+//! ground, and then measures a frame in which only some of the stages run, without the physics
+//! engine - so that the cost of each stage can be seen separately from the cost of the physics
+//! step and from each other. The `none` benchmarks measure the frame without any of them, and the
+//! `full` benchmarks run all the stages together with the physics step.
+//!
+//! Run with `cargo bench --bench pipeline`.
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_tnua::math::{Float, Vector3};
+use bevy_tnua::prelude::*;
+use bevy_tnua::testing::TnuaTestApp;
+use bevy_tnua::TnuaUserControlsSystemSet;
+use bevy_tnua_avian3d::TnuaAvian3dPlugin;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const CROWD_SIZES: [usize; 3] = [1_000, 3_000, 10_000];
+
+#[derive(Resource, Clone, Copy)]
+struct BenchedStages {
+    sensors: bool,
+    logic: bool,
+    motors: bool,
+    physics: bool,
+}
+
+impl BenchedStages {
+    const ALL: Self = Self {
+        sensors: true,
+        logic: true,
+        motors: true,
+        physics: true,
+    };
+}
+
+#[derive(Component)]
+struct CrowdMember {
+    index: usize,
+}
+
+#[derive(Resource, Default)]
+struct FrameCounter(usize);
+
+fn crowd_controls_system(
+    mut frame_counter: ResMut<FrameCounter>,
+    mut query: Query<(&CrowdMember, &mut TnuaController)>,
+) {
+    frame_counter.0 += 1;
+    let frame = frame_counter.0;
+    query.par_iter_mut().for_each(|(member, mut controller)| {
+        // Walk in circles, each character in a different phase.
+        let angle = (frame as Float) * 0.02 + member.index as Float;
+        controller.basis(TnuaBuiltinWalk {
+            desired_velocity: 4.0 * Vector3::new(angle.cos(), 0.0, angle.sin()),
+            float_height: 1.5,
+            ..Default::default()
+        });
+        // Every character jumps for one second out of every four.
+        #[allow(clippy::manual_is_multiple_of)]
+        if (frame / 60 + member.index) % 4 == 0 {
+            controller.action(TnuaBuiltinJump {
+                height: 2.0,
+                ..Default::default()
+            });
+        }
+    });
+}
+
+fn crowd_app(crowd_size: usize) -> TnuaTestApp {
+    let mut test_app = TnuaTestApp::new((
+        AssetPlugin::default(),
+        bevy::scene::ScenePlugin,
+        PhysicsPlugins::default(),
+        TnuaAvian3dPlugin::default(),
+    ));
+    let app = test_app.app_mut();
+    app.init_asset::<Mesh>();
+    app.insert_resource(BenchedStages::ALL);
+    app.init_resource::<FrameCounter>();
+    app.configure_sets(
+        Update,
+        TnuaPipelineStages::Sensors.run_if(|stages: Res<BenchedStages>| stages.sensors),
+    );
+    app.configure_sets(
+        Update,
+        (
+            TnuaPipelineStages::SubservientSensors,
+            TnuaUserControlsSystemSet,
+            TnuaPipelineStages::Logic,
+        )
+            .run_if(|stages: Res<BenchedStages>| stages.logic),
+    );
+    app.configure_sets(
+        Update,
+        TnuaPipelineStages::Motors.run_if(|stages: Res<BenchedStages>| stages.motors),
+    );
+    // Pausing `Time<Physics>` would also stop Tnua's systems, so the physics sets are disabled
+    // instead.
+    app.configure_sets(
+        PostUpdate,
+        (
+            PhysicsSet::Prepare,
+            PhysicsSet::StepSimulation,
+            PhysicsSet::Sync,
+        )
+            .run_if(|stages: Res<BenchedStages>| stages.physics),
+    );
+    app.add_systems(
+        Update,
+        crowd_controls_system.in_set(TnuaUserControlsSystemSet),
+    );
+
+    let world = test_app.world_mut();
+    world.spawn((RigidBody::Static, Collider::half_space(Vec3::Y)));
+    let row_length = (crowd_size as f32).sqrt().ceil() as usize;
+    for index in 0..crowd_size {
+        let (row, column) = (index / row_length, index % row_length);
+        world.spawn((
+            CrowdMember { index },
+            TransformBundle::from_transform(Transform::from_xyz(
+                3.0 * column as f32,
+                2.0,
+                3.0 * row as f32,
+            )),
+            RigidBody::Dynamic,
+            Collider::capsule(0.5, 1.0),
+            LockedAxes::ROTATION_LOCKED,
+            TnuaControllerBundle::default(),
+        ));
+    }
+
+    // Let the characters settle at their float height.
+    test_app.step(120);
+    test_app
+}
+
+fn bench_stages(criterion: &mut Criterion, group_name: &str, stages: BenchedStages) {
+    let mut group = criterion.benchmark_group(group_name);
+    group.sample_size(20);
+    for crowd_size in CROWD_SIZES {
+        let mut test_app = crowd_app(crowd_size);
+        test_app.world_mut().insert_resource(stages);
+        group.bench_function(BenchmarkId::from_parameter(crowd_size), |bencher| {
+            bencher.iter(|| test_app.step(1));
+        });
+    }
+    group.finish();
+}
+
+fn pipeline_benchmarks(criterion: &mut Criterion) {
+    let only = |sensors, logic, motors| BenchedStages {
+        sensors,
+        logic,
+        motors,
+        physics: false,
+    };
+    bench_stages(criterion, "none", only(false, false, false));
+    bench_stages(criterion, "sensors", only(true, false, false));
+    bench_stages(criterion, "logic", only(false, true, false));
+    bench_stages(criterion, "motors", only(false, false, true));
+    bench_stages(criterion, "full", BenchedStages::ALL);
+}
+
+criterion_group!(benches, pipeline_benchmarks);
+criterion_main!(benches);
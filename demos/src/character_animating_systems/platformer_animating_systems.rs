@@ -64,6 +64,7 @@ pub fn animate_platformer_character(
                         TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => {
                             AnimationState::Jumping
                         }
+                        TnuaBuiltinJumpState::ExtendingJump { .. } => AnimationState::Jumping,
                         TnuaBuiltinJumpState::MaintainingJump => AnimationState::Jumping,
                         TnuaBuiltinJumpState::StoppedMaintainingJump => AnimationState::Jumping,
                         TnuaBuiltinJumpState::FallSection => AnimationState::Falling,
@@ -137,9 +138,16 @@ pub fn animate_platformer_character(
             // start a new animation. The actual implementation for each possible animation state
             // is straightforward - we start the animation, set its speed if the state has a
             // variable speed, and set it to repeat if it's something that needs to repeat.
+            //
+            // `Interrupt` is emitted for animations forced with `TnuaAnimatingState::interrupt`.
+            // The demos don't use it, but if they did - starting the animation would be the same.
             TnuaAnimatingStateDirective::Alter {
                 old_state: _,
                 state,
+            }
+            | TnuaAnimatingStateDirective::Interrupt {
+                old_state: _,
+                state,
             } => {
                 player.stop_all();
                 match state {
@@ -1,6 +1,8 @@
 #[cfg(feature = "egui")]
 use std::ops::RangeInclusive;
 
+#[allow(unused_imports)]
+use bevy_tnua::builtins::TnuaWalkRotationMode;
 use bevy_tnua::builtins::{TnuaBuiltinCrouch, TnuaBuiltinDash, TnuaBuiltinKnockback};
 #[allow(unused_imports)]
 use bevy_tnua::math::{float_consts, Float};
@@ -103,6 +105,17 @@ impl UiTunable for TnuaBuiltinWalk {
     fn tune(&mut self, ui: &mut egui::Ui) {
         ui.add(egui::Slider::new(&mut self.float_height, 0.0..=10.0).text("Float At"));
         ui.add(egui::Slider::new(&mut self.cling_distance, 0.0..=10.0).text("Cling Distance"));
+        ui.add(
+            egui::Slider::new(&mut self.snap_to_ground_distance, 0.0..=10.0)
+                .text("Snap To Ground Distance"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.short_hop_max_distance, 0.0..=10.0)
+                .text("Short Hop Max Distance"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.short_hop_max_time, 0.0..=1.0).text("Short Hop Max Time"),
+        );
         ui.add(egui::Slider::new(&mut self.spring_strengh, 0.0..=4000.0).text("Spring Strengh"));
         ui.add(egui::Slider::new(&mut self.spring_dampening, 0.0..=1.9).text("Spring Dampening"));
         slider_or_infinity(ui, "Acceleration", &mut self.acceleration, 0.0..=200.0);
@@ -133,17 +146,50 @@ impl UiTunable for TnuaBuiltinWalk {
             0.0..=2000.0,
         );
 
+        egui::ComboBox::from_label("Rotation Mode")
+            .selected_text(format!("{:?}", self.rotation_mode))
+            .show_ui(ui, |ui| {
+                for rotation_mode in [
+                    TnuaWalkRotationMode::FaceMovementDirection,
+                    TnuaWalkRotationMode::FaceDesiredForward,
+                    TnuaWalkRotationMode::Strafe,
+                    TnuaWalkRotationMode::Flip,
+                ] {
+                    ui.selectable_value(
+                        &mut self.rotation_mode,
+                        rotation_mode,
+                        format!("{:?}", rotation_mode),
+                    );
+                }
+            });
+
         slider_or_infinity(
             ui,
             "Turning Angular Velocity",
             &mut self.turning_angvel,
             0.0..=70.0,
         );
+        slider_or_infinity(
+            ui,
+            "Turning Angular Acceleration",
+            &mut self.turning_angacl,
+            0.0..=2000.0,
+        );
+        slider_or_infinity(
+            ui,
+            "Turn In Place Threshold (in radians)",
+            &mut self.turn_in_place_threshold,
+            0.0..=float_consts::PI,
+        );
 
         ui.add(
             egui::Slider::new(&mut self.max_slope, 0.0..=float_consts::FRAC_PI_2)
                 .text("Max Slope (in radians)"),
         );
+        ui.add(
+            egui::Slider::new(&mut self.landing_slope_conversion, 0.0..=1.0)
+                .text("Landing Slope Conversion"),
+        );
     }
 }
 
@@ -165,6 +211,10 @@ impl UiTunable for TnuaBuiltinJump {
             egui::Slider::new(&mut self.upslope_extra_gravity, 0.0..=100.0)
                 .text("Upslope Jump Extra Gravity"),
         );
+        ui.checkbox(
+            &mut self.normalize_slope_takeoff,
+            "Normalize Slope Jump Takeoff",
+        );
         ui.add(
             egui::Slider::new(&mut self.takeoff_extra_gravity, 0.0..=100.0)
                 .text("Jump Takeoff Extra Gravity"),
@@ -183,6 +233,18 @@ impl UiTunable for TnuaBuiltinJump {
             egui::Slider::new(&mut self.shorten_extra_gravity, 0.0..=100.0)
                 .text("Jump Shorten Extra Gravity"),
         );
+        ui.add(
+            egui::Slider::new(&mut self.hold_extension_duration, 0.0..=1.0)
+                .text("Jump Hold Extension Duration"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.hold_extension_acceleration, 0.0..=100.0)
+                .text("Jump Hold Extension Acceleration"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.hold_extension_falloff, 0.0..=4.0)
+                .text("Jump Hold Extension Falloff"),
+        );
 
         ui.add(
             egui::Slider::new(&mut self.peak_prevention_at_upward_velocity, 0.0..=20.0)
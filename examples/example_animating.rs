@@ -252,6 +252,7 @@ fn handle_animating(
                 TnuaBuiltinJumpState::NoJump => return,
                 TnuaBuiltinJumpState::StartingJump { .. } => AnimationState::Jumping,
                 TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => AnimationState::Jumping,
+                TnuaBuiltinJumpState::ExtendingJump { .. } => AnimationState::Jumping,
                 TnuaBuiltinJumpState::MaintainingJump => AnimationState::Jumping,
                 TnuaBuiltinJumpState::StoppedMaintainingJump => AnimationState::Jumping,
                 TnuaBuiltinJumpState::FallSection => AnimationState::Falling,
@@ -306,9 +307,14 @@ fn handle_animating(
         TnuaAnimatingStateDirective::Alter {
             old_state: _,
             state,
+        }
+        | TnuaAnimatingStateDirective::Interrupt {
+            old_state: _,
+            state,
         } => {
             // `Alter` means that we have switched to a different variant and need to play a
-            // different animation.
+            // different animation. `Interrupt` means the same, but for animations forced with
+            // `TnuaAnimatingState::interrupt` - which this example does not use.
 
             // First - stop the currently running animation. We don't check which one is running
             // here because we just assume it belongs to the old state, but more sophisticated code
@@ -0,0 +1,388 @@
+//! A trait for integrating physics engines with Tnua, and a plugin that drives the integration.
+//!
+//! Implementing [`TnuaPhysicsBackend`] and adding [`TnuaPhysicsBackendPlugin`] takes care of the
+//! parts of the integration that are the same for every physics engine - respecting
+//! [`TnuaToggle`] and [`TnuaUpdateRate`], resolving the owners of
+//! [subservient sensors](TnuaSubservientSensor), [`TnuaLocomotionCollider`], [`TnuaSensorFilter`],
+//! [`TnuaGravityOverride`] and looking through [ghost platforms](TnuaGhostPlatform) - so that the
+//! backend only needs to talk to the physics engine.
+use std::marker::PhantomData;
+
+use bevy::ecs::query::{QueryData, QueryItem, ReadOnlyQueryData};
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::{ReadOnlySystemParam, StaticSystemParam, SystemParamItem};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::data_for_backends::{
+    TnuaGhostPlatform, TnuaGhostSensor, TnuaGravityOverride, TnuaLocomotionCollider, TnuaMotor,
+    TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker, TnuaSensorFilter,
+    TnuaToggle, TnuaUpdateRate,
+};
+use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
+use crate::stage_tracing::TnuaStageTrace;
+use crate::subservient_sensors::TnuaSubservientSensor;
+use crate::TnuaPipelineStages;
+
+/// The interaction of Tnua with a physics engine.
+///
+/// The type that implements this trait is only used as a marker - the data the backend needs
+/// comes from its [`Params`](Self::Params) and [`MotorParams`](Self::MotorParams) and from the
+/// components in its query types.
+///
+/// The plugin of the backend should add [`TnuaPhysicsBackendPlugin`] with its implementation, and
+/// also make [`TnuaSystemSet`](crate::TnuaSystemSet) run before the physics step and not run while
+/// the physics engine is paused.
+pub trait TnuaPhysicsBackend: 'static + Send + Sync {
+    /// Data shared by all the characters - e.g. the resources of the physics engine and queries
+    /// for reading other entities.
+    type Params: ReadOnlySystemParam;
+
+    /// Data shared by all the characters when applying the motors.
+    ///
+    /// This is separate from [`Params`](Self::Params) because the motors usually write to the
+    /// components that `Params` reads from other entities.
+    type MotorParams: ReadOnlySystemParam;
+
+    /// The components of the character entity for reading its rigid body.
+    type RigidBody: ReadOnlyQueryData;
+
+    /// The components of the sensor entity that configure its cast - e.g. a shape to cast instead
+    /// of a ray.
+    type SensorShape: ReadOnlyQueryData;
+
+    /// The components of the character entity the motor is applied to.
+    type Motor: QueryData;
+
+    /// Read the rigid body of the character.
+    ///
+    /// The [`gravity`](TnuaRigidBodyTracker::gravity) should be the gravity of the physics
+    /// engine - [`TnuaGravityOverride`] is applied by the caller.
+    fn rigid_body_tracker(
+        params: &SystemParamItem<Self::Params>,
+        rigid_body: QueryItem<Self::RigidBody>,
+    ) -> TnuaRigidBodyTracker;
+
+    /// Cast a sensor and return the closest hit.
+    ///
+    /// The backend is responsible for skipping the owner's own rigid body, for using the
+    /// collision groups of [`owner_collider_entity`](TnuaSensorCast::owner_collider_entity), and
+    /// for respecting [`allows`](TnuaSensorCast::allows) and
+    /// [`hits_ghost_platform`](TnuaSensorCast::hits_ghost_platform).
+    fn cast(
+        params: &SystemParamItem<Self::Params>,
+        sensor_shape: &QueryItem<Self::SensorShape>,
+        cast: &TnuaSensorCast,
+    ) -> Option<TnuaSensorHit>;
+
+    /// The linear velocity of a point on the rigid body of an entity, and the angular velocity of
+    /// that rigid body.
+    ///
+    /// Return `None` if the entity does not have a rigid body that can move.
+    fn point_velocity(
+        params: &SystemParamItem<Self::Params>,
+        entity: Entity,
+        point: Vector3,
+    ) -> Option<(Vector3, Vector3)>;
+
+    /// Apply the motor to the rigid body of the character.
+    ///
+    /// `gravity_override` is the [`TnuaGravityOverride`] of the character, which means the motor
+    /// should also make up for the difference between it and the gravity of the physics engine.
+    fn apply_motor(
+        params: &SystemParamItem<Self::MotorParams>,
+        motor: &TnuaMotor,
+        gravity_override: Option<Vector3>,
+        body: QueryItem<Self::Motor>,
+    );
+
+    /// Stop applying the motor, for characters whose [`TnuaToggle`] does not let them move.
+    ///
+    /// Only needed if the backend applies the motor in a way that persists between frames (e.g.
+    /// with a force component).
+    fn release_motor(_body: QueryItem<Self::Motor>) {}
+}
+
+/// A single cast of a [`TnuaProximitySensor`], for [`TnuaPhysicsBackend::cast`].
+pub struct TnuaSensorCast<'a> {
+    /// The character the sensor belongs to. For subservient sensors, this is their owner.
+    pub owner_entity: Entity,
+
+    /// The entity of the collider of the owner - which is different from the owner entity itself
+    /// when it has a [`TnuaLocomotionCollider`].
+    pub owner_collider_entity: Entity,
+
+    /// The transform of the sensor entity.
+    pub transform: &'a GlobalTransform,
+
+    /// The origin of the cast, in world coordinates.
+    pub origin: Vector3,
+
+    /// The direction of the cast.
+    pub direction: Dir3,
+
+    /// The maximum distance of the cast.
+    pub range: Float,
+
+    /// See [`TnuaProximitySensor::intersection_match_prevention_cutoff`].
+    pub intersection_match_prevention_cutoff: Float,
+
+    sensor_filter: Option<&'a TnuaSensorFilter>,
+    is_unvisited_ghost_platform: &'a dyn Fn(Entity) -> bool,
+}
+
+impl TnuaSensorCast<'_> {
+    /// Whether the [`TnuaSensorFilter`] of the owner lets the sensor hit the entity.
+    ///
+    /// Backends should check both the entity of the collider and the entity of its rigid body.
+    pub fn allows(&self, entity: Entity) -> bool {
+        self.sensor_filter
+            .map_or(true, |sensor_filter| sensor_filter.allows(entity))
+    }
+
+    /// Whether the sensor should hit the entity even though it does not collide with the owner -
+    /// because it is a [`TnuaGhostPlatform`] that the sensor did not already pass through in this
+    /// frame.
+    pub fn hits_ghost_platform(&self, entity: Entity) -> bool {
+        (self.is_unvisited_ghost_platform)(entity)
+    }
+}
+
+/// The result of [`TnuaPhysicsBackend::cast`].
+pub struct TnuaSensorHit {
+    /// The entity that was hit.
+    pub entity: Entity,
+
+    /// The distance from the [`origin`](TnuaSensorCast::origin) of the cast to the hit.
+    pub proximity: Float,
+
+    /// The point of the hit, in world coordinates.
+    pub intersection_point: Vector3,
+
+    /// The normal of the surface at the hit.
+    pub normal: Dir3,
+}
+
+/// Add this plugin to drive a [`TnuaPhysicsBackend`].
+pub struct TnuaPhysicsBackendPlugin<B: TnuaPhysicsBackend> {
+    schedule: InternedScheduleLabel,
+    _phantom: PhantomData<fn() -> B>,
+}
+
+impl<B: TnuaPhysicsBackend> TnuaPhysicsBackendPlugin<B> {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: TnuaPhysicsBackend> Default for TnuaPhysicsBackendPlugin<B> {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl<B: TnuaPhysicsBackend> Plugin for TnuaPhysicsBackendPlugin<B>
+where
+    for<'w, 's> SystemParamItem<'w, 's, B::Params>: Sync,
+    for<'w, 's> SystemParamItem<'w, 's, B::MotorParams>: Sync,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            (
+                update_rigid_body_trackers_system::<B>,
+                update_proximity_sensors_system::<B>,
+            )
+                .in_set(TnuaPipelineStages::Sensors),
+        );
+        app.add_systems(
+            self.schedule,
+            apply_motors_system::<B>.in_set(TnuaPipelineStages::Motors),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_rigid_body_trackers_system<B: TnuaPhysicsBackend>(
+    params: StaticSystemParam<B::Params>,
+    mut query: Query<(
+        &mut TnuaRigidBodyTracker,
+        B::RigidBody,
+        Option<&TnuaToggle>,
+        Option<&TnuaGravityOverride>,
+    )>,
+) where
+    for<'w, 's> SystemParamItem<'w, 's, B::Params>: Sync,
+{
+    let params = params.into_inner();
+    query
+        .par_iter_mut()
+        .for_each(|(mut tracker, rigid_body, tnua_toggle, gravity_override)| {
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            *tracker = B::rigid_body_tracker(&params, rigid_body);
+            if let Some(gravity_override) = gravity_override {
+                tracker.gravity = gravity_override.0;
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn update_proximity_sensors_system<B: TnuaPhysicsBackend>(
+    params: StaticSystemParam<B::Params>,
+    mut query: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut TnuaProximitySensor,
+        B::SensorShape,
+        Option<&mut TnuaGhostSensor>,
+        Option<&TnuaSubservientSensor>,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+    ghost_platforms_query: Query<(), With<TnuaGhostPlatform>>,
+    locomotion_collider_query: Query<&TnuaLocomotionCollider>,
+    sensor_filter_query: Query<&TnuaSensorFilter>,
+) where
+    for<'w, 's> SystemParamItem<'w, 's, B::Params>: Sync,
+{
+    let params = params.into_inner();
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::Sensors);
+    query.par_iter_mut().for_each(
+        |(
+            sensor_entity,
+            transform,
+            mut sensor,
+            sensor_shape,
+            mut ghost_sensor,
+            subservient,
+            tnua_toggle,
+            update_rate,
+        )| {
+            let _span = stage_trace.character(sensor_entity);
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+
+            let owner_entity = if let Some(subservient) = subservient {
+                subservient.owner_entity
+            } else {
+                sensor_entity
+            };
+            let owner_collider_entity = locomotion_collider_query
+                .get(owner_entity)
+                .map_or(owner_entity, |locomotion_collider| locomotion_collider.0);
+            let sensor_filter = sensor_filter_query.get(owner_entity).ok();
+            let cast_origin = transform
+                .transform_point(sensor.cast_origin.f32())
+                .adjust_precision();
+
+            let has_ghost_sensor = ghost_sensor.is_some();
+            if let Some(ghost_sensor) = ghost_sensor.as_mut() {
+                ghost_sensor.0.clear();
+            }
+            let mut already_visited_ghost_entities = HashSet::<Entity>::default();
+            let mut cast_range_skip = 0.0;
+
+            sensor.output = 'sensor_output: loop {
+                let is_unvisited_ghost_platform = |entity: Entity| {
+                    has_ghost_sensor
+                        && ghost_platforms_query.contains(entity)
+                        && !already_visited_ghost_entities.contains(&entity)
+                };
+                let cast = TnuaSensorCast {
+                    owner_entity,
+                    owner_collider_entity,
+                    transform,
+                    origin: cast_origin
+                        + cast_range_skip * sensor.cast_direction.adjust_precision(),
+                    direction: sensor.cast_direction,
+                    range: sensor.cast_range - cast_range_skip,
+                    intersection_match_prevention_cutoff: sensor
+                        .intersection_match_prevention_cutoff,
+                    sensor_filter,
+                    is_unvisited_ghost_platform: &is_unvisited_ghost_platform,
+                };
+                let Some(TnuaSensorHit {
+                    entity,
+                    proximity,
+                    intersection_point,
+                    normal,
+                }) = B::cast(&params, &sensor_shape, &cast)
+                else {
+                    break 'sensor_output None;
+                };
+                let proximity = proximity + cast_range_skip;
+                let (entity_linvel, entity_angvel) =
+                    B::point_velocity(&params, entity, intersection_point)
+                        .unwrap_or((Vector3::ZERO, Vector3::ZERO));
+                let sensor_output = TnuaProximitySensorOutput {
+                    entity,
+                    proximity,
+                    normal,
+                    entity_linvel,
+                    entity_angvel,
+                };
+                if ghost_platforms_query.contains(entity) {
+                    cast_range_skip = proximity;
+                    already_visited_ghost_entities.insert(entity);
+                    if let Some(ghost_sensor) = ghost_sensor.as_mut() {
+                        ghost_sensor.0.push(sensor_output);
+                    }
+                } else {
+                    break 'sensor_output Some(sensor_output);
+                }
+            };
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_motors_system<B: TnuaPhysicsBackend>(
+    params: StaticSystemParam<B::MotorParams>,
+    mut query: Query<(
+        Entity,
+        &TnuaMotor,
+        B::Motor,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+        Option<&TnuaGravityOverride>,
+    )>,
+) where
+    for<'w, 's> SystemParamItem<'w, 's, B::MotorParams>: Sync,
+{
+    let params = params.into_inner();
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::Motors);
+    query.par_iter_mut().for_each(
+        |(entity, motor, body, tnua_toggle, update_rate, gravity_override)| {
+            let _span = stage_trace.character(entity);
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled | TnuaToggle::SenseOnly => {
+                    B::release_motor(body);
+                    return;
+                }
+                TnuaToggle::Enabled => {}
+            }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            B::apply_motor(
+                &params,
+                motor,
+                gravity_override.map(|gravity_override| gravity_override.0),
+                body,
+            );
+        },
+    );
+}
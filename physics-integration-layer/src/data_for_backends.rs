@@ -1,11 +1,16 @@
 use std::ops::{Add, AddAssign};
+use std::sync::Arc;
 
 use crate::math::{Float, Quaternion, Vector3};
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 
 /// Allows disabling Tnua for a specific entity.
 ///
-/// This can be used to let some other system  temporarily take control over a character.
+/// This can be used to let some other system  temporarily take control over a character - e.g. for
+/// cutscenes, dialogues or ragdolling. Unlike removing the Tnua components, this does not lose the
+/// state of the controller (like the coyote time of the walk basis), so the character will resume
+/// from where it stopped when it is enabled again.
 ///
 /// This component is not mandatory - if omitted, Tnua will just assume it is enabled for that
 /// entity.
@@ -28,6 +33,85 @@ pub enum TnuaToggle {
     Enabled,
 }
 
+/// Allows running Tnua for a specific entity at a reduced rate.
+///
+/// This can be used for characters that are far away from the camera, where a lower update rate
+/// would not be noticed but is much cheaper.
+///
+/// On frames where the entity is not updated, the backend should neither update its sensors nor
+/// apply forces from its motor, and the controller system will not run for it. When the entity is
+/// updated, the controller uses the time passed since the previous update as the frame duration -
+/// so that accelerations and timers are compensated for the skipped frames. Accelerations applied
+/// by the motor remain in effect between the updates.
+///
+/// This component is not mandatory - if omitted, Tnua will just update that entity every frame.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct TnuaUpdateRate {
+    pub mode: TnuaUpdateRateMode,
+    frames_since_update: u32,
+    time_since_update: Float,
+    update_this_frame: bool,
+}
+
+/// How often an entity with [`TnuaUpdateRate`] gets updated.
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TnuaUpdateRateMode {
+    /// Update the entity every frame.
+    #[default]
+    EveryFrame,
+    /// Update the entity once every this amount of frames.
+    EveryNthFrame(u32),
+    /// Do not update the entity at all, until the mode is changed.
+    ///
+    /// Unlike [`TnuaToggle::Disabled`], the forces last applied by the motor are kept - so a
+    /// character that stands still will keep standing.
+    Frozen,
+}
+
+impl TnuaUpdateRate {
+    pub fn new(mode: TnuaUpdateRateMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Advance to the next frame.
+    ///
+    /// This is called by Tnua once per frame, before [the
+    /// sensors](crate::TnuaPipelineStages::Sensors) are updated. Physics backends should not call
+    /// it.
+    pub fn tick(&mut self, frame_duration: Float) {
+        if self.update_this_frame {
+            self.frames_since_update = 0;
+            self.time_since_update = 0.0;
+        }
+        self.frames_since_update += 1;
+        self.time_since_update += frame_duration;
+        self.update_this_frame = match self.mode {
+            TnuaUpdateRateMode::EveryFrame => true,
+            TnuaUpdateRateMode::EveryNthFrame(n) => n <= self.frames_since_update,
+            TnuaUpdateRateMode::Frozen => {
+                // Don't let the frozen time accumulate into the first frame after unfreezing.
+                self.frames_since_update = 0;
+                self.time_since_update = 0.0;
+                false
+            }
+        };
+    }
+
+    /// Whether the entity should be updated in the current frame.
+    pub fn should_update(&self) -> bool {
+        self.update_this_frame
+    }
+
+    /// The time, in seconds, since the previous update of the entity (including the current
+    /// frame).
+    pub fn time_since_update(&self) -> Float {
+        self.time_since_update
+    }
+}
+
 /// Newtonian state of the rigid body.
 ///
 /// Tnua takes the position and rotation of the rigid body from its `GlobalTransform`, but things
@@ -194,7 +278,7 @@ impl AddAssign for TnuaVelChange {
 /// Instructions on how to move forces to the rigid body.
 ///
 /// The physics backend is responsible for reading this component during
-/// [`TnuaPipelineStages::Sensors`](crate::TnuaPipelineStages::Sensors) and apply the forces to the
+/// [`TnuaPipelineStages::Motors`](crate::TnuaPipelineStages::Motors) and apply the forces to the
 /// rigid body.
 ///
 /// This documentation uses the term "forces", but in fact these numbers ignore mass and are
@@ -228,6 +312,113 @@ impl TnuaGhostSensor {
     }
 }
 
+/// The collider a character uses for its locomotion, for characters made of more than one
+/// collider (e.g. a body collider with hitboxes and hurtboxes as children of the rigid body).
+///
+/// Add this to the character entity - the one with the rigid body and the
+/// [`TnuaProximitySensor`]. The physics backend uses this collider, instead of the one on the
+/// character entity, for the collision groups (or layers) of the sensor and for detecting when the
+/// character squeezed into an obstacle (see
+/// [`intersection_match_prevention_cutoff`](TnuaProximitySensor::intersection_match_prevention_cutoff)).
+/// Subservient sensors of the character use it too.
+///
+/// With or without this component, the sensors ignore all the colliders attached to the
+/// character's rigid body, and the motors act on - and the mass is read from - the rigid body.
+///
+/// This component is not mandatory - if omitted, the collider on the character entity is used.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TnuaLocomotionCollider(pub Entity);
+
+/// Replaces the gravity of the physics engine for a single character - for moon boots, heavy
+/// armor, underwater zones and so on.
+///
+/// The physics backend reports this as the [`gravity`](TnuaRigidBodyTracker::gravity) of the
+/// character, so everything in Tnua that depends on gravity - the float spring, the jump height
+/// calculations and the extra gravity of the jump - uses it. The backend also applies the
+/// difference between this and the gravity the physics engine applies to the rigid body together
+/// with the motor, so the character actually falls with this gravity.
+///
+/// The difference is only applied while Tnua controls the character - not when its
+/// [`TnuaToggle`] is set to [`Disabled`](TnuaToggle::Disabled) or
+/// [`SenseOnly`](TnuaToggle::SenseOnly).
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TnuaGravityOverride(pub Vector3);
+
+/// Excludes colliders from the proximity sensors of a character - e.g. its pets, its projectiles
+/// or objects it carries - so that it never stands on them.
+///
+/// Add this to the character entity. The physics backend applies it to all the sensors of the
+/// character, including its subservient sensors. A collider is ignored if either its entity or the
+/// entity of the rigid body it is attached to is excluded.
+///
+/// To filter by collision groups (or layers), use the component the integration crate provides
+/// for it.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tnua_physics_integration_layer::data_for_backends::TnuaSensorFilter;
+/// # let (carried_crate, pet) = (Entity::from_raw(1), Entity::from_raw(2));
+/// let filter = TnuaSensorFilter::excluding([carried_crate, pet]);
+/// assert!(!filter.allows(carried_crate));
+///
+/// // Predicates cannot query the world, so they usually check a set the game updates:
+/// let projectiles = std::sync::Arc::new(std::sync::RwLock::new(Vec::<Entity>::new()));
+/// let filter = TnuaSensorFilter::default().with_predicate(move |entity| {
+///     !projectiles.read().unwrap().contains(&entity)
+/// });
+/// ```
+#[derive(Component, Clone, Default)]
+pub struct TnuaSensorFilter {
+    /// Entities the sensors should not detect.
+    pub excluded_entities: HashSet<Entity>,
+
+    /// When set, the sensors only detect entities this returns `true` for.
+    ///
+    /// This is called from the sensor systems, which may run in parallel for different
+    /// characters.
+    pub predicate: Option<Arc<TnuaSensorFilterPredicate>>,
+}
+
+/// The type of [`TnuaSensorFilter::predicate`].
+pub type TnuaSensorFilterPredicate = dyn 'static + Send + Sync + Fn(Entity) -> bool;
+
+impl std::fmt::Debug for TnuaSensorFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TnuaSensorFilter")
+            .field("excluded_entities", &self.excluded_entities)
+            .field("predicate", &self.predicate.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl TnuaSensorFilter {
+    /// A filter that excludes the given entities.
+    pub fn excluding(entities: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            excluded_entities: entities.into_iter().collect(),
+            predicate: None,
+        }
+    }
+
+    /// Set the [predicate](Self::predicate) of the filter.
+    pub fn with_predicate(
+        mut self,
+        predicate: impl 'static + Send + Sync + Fn(Entity) -> bool,
+    ) -> Self {
+        self.predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Check if the sensors may detect the entity.
+    pub fn allows(&self, entity: Entity) -> bool {
+        !self.excluded_entities.contains(&entity)
+            && self
+                .predicate
+                .as_ref()
+                .map_or(true, |predicate| predicate(entity))
+    }
+}
+
 /// A marker for jump/fall-through platforms.
 ///
 /// Ghost platforms must also have their solver groups (**not** collision groups) set to exclude
@@ -12,7 +12,9 @@
 //! * Add systems, to the [`TnuaPipelineStages::Sensors`] stage, that update:
 //!   * [`TnuaRigidBodyTracker`](data_for_backends::TnuaRigidBodyTracker) with the objects current
 //!     kinematic status (position, rotation, velocity, angular velocity) as well as the gravity
-//!     currently applied to it.
+//!     currently applied to it. If the entity has
+//!     [`TnuaGravityOverride`](data_for_backends::TnuaGravityOverride), its value should be
+//!     reported as the gravity instead.
 //!   * [`TnuaProximitySensor`](data_for_backends::TnuaProximitySensor) with the _first_ tangible
 //!     collider within range, and [`TnuaGhostSensor`](data_for_backends::TnuaGhostSensor) with
 //!     _all_ the ghost colliders found before that tangible collider.
@@ -22,7 +24,16 @@
 //!       [`TnuaGhostPlatform`](data_for_backends::TnuaGhostPlatform) component. It may or may not
 //!       physically interact with the character's collider - as long as it has the component it is
 //!       considered a ghost collider.
-//!     * The sensor should ignore the owner entity's collider.
+//!     * The sensor should ignore the owner entity's collider, and any other collider attached to
+//!       its rigid body.
+//!     * If the owner entity has
+//!       [`TnuaLocomotionCollider`](data_for_backends::TnuaLocomotionCollider), the collider it
+//!       points to should be used instead of the owner entity's collider wherever the owner's
+//!       collider is needed (collision groups, contacts).
+//!     * If the owner entity has [`TnuaSensorFilter`](data_for_backends::TnuaSensorFilter), the
+//!       sensor should skip every collider the filter does not
+//!       [allow](data_for_backends::TnuaSensorFilter::allows), and every collider attached to a
+//!       rigid body the filter does not allow.
 //!     * If the sensor has the
 //!       [`TnuaSubservientSensor`](subservient_sensors::TnuaSubservientSensor) component, the
 //!       "owner entity" is defined as the `owner_entity` field from that component and not the
@@ -37,7 +48,10 @@
 //!   sees fit.
 //!
 //! * Add a system, to the [`TnuaPipelineStages::Motors`] stage, that applies all the impulses and
-//!   accelerations from [`TnuaMotor`](data_for_backends::TnuaMotor) components.
+//!   accelerations from [`TnuaMotor`](data_for_backends::TnuaMotor) components. For entities
+//!   with [`TnuaGravityOverride`](data_for_backends::TnuaGravityOverride), the difference between
+//!   the override and the gravity the physics engine applies to the rigid body should be added to
+//!   the acceleration.
 //!
 //!   Here, too, if it makes sense to split this work into multiple systems the integration crate
 //!   may do so at its own discretion.
@@ -60,10 +74,143 @@
 //! body and a collider, so they should not go in that bundle, but if the crate needs things users
 //! rarely think about - for example, bevy_rapier's `ReadMassProperties` - then these components
 //! should go in that bundle.
+//!
+//! Instead of writing these systems, an integration crate can implement
+//! [`TnuaPhysicsBackend`](backend::TnuaPhysicsBackend) and add
+//! [`TnuaPhysicsBackendPlugin`](backend::TnuaPhysicsBackendPlugin), which adds systems that
+//! handle everything that does not depend on the physics engine -
+//! [`TnuaToggle`](data_for_backends::TnuaToggle),
+//! [`TnuaUpdateRate`](data_for_backends::TnuaUpdateRate), the subservient sensors, the ghost
+//! platforms etc. The Rapier integration crates are implemented this way. A skeleton integration
+//! for a custom or in-house physics engine:
+//!
+//! ```
+//! use bevy::ecs::query::QueryItem;
+//! use bevy::ecs::system::SystemParamItem;
+//! use bevy::prelude::*;
+//! use bevy_tnua_physics_integration_layer::backend::*;
+//! use bevy_tnua_physics_integration_layer::data_for_backends::*;
+//! use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, Float, Vector3};
+//! use bevy_tnua_physics_integration_layer::TnuaSystemSet;
+//!
+//! // The in-house engine's rigid body.
+//! #[derive(Component)]
+//! struct MyEngineBody {
+//!     velocity: Vector3,
+//!     angvel: Vector3,
+//! }
+//!
+//! # #[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+//! # struct MyEngineStepSet;
+//! # #[derive(Resource)]
+//! # struct MyEngineGravity(Vector3);
+//! # #[derive(Resource)]
+//! # struct MyEngineWorld;
+//! # impl MyEngineWorld {
+//! #     fn cast_ray(
+//! #         &self,
+//! #         _origin: Vector3,
+//! #         _direction: Dir3,
+//! #         _range: Float,
+//! #         _filter: impl Fn(Entity) -> bool,
+//! #     ) -> Option<TnuaSensorHit> {
+//! #         None
+//! #     }
+//! # }
+//! struct MyEngineBackend;
+//!
+//! impl TnuaPhysicsBackend for MyEngineBackend {
+//!     type Params = (
+//!         Res<'static, MyEngineWorld>,
+//!         Res<'static, MyEngineGravity>,
+//!         Query<'static, 'static, &'static MyEngineBody>,
+//!     );
+//!     // The motors cannot use `Params`, because it reads the `MyEngineBody` the motors write.
+//!     type MotorParams = (Res<'static, MyEngineGravity>, Res<'static, Time>);
+//!     type RigidBody = (&'static GlobalTransform, &'static MyEngineBody);
+//!     type SensorShape = ();
+//!     type Motor = &'static mut MyEngineBody;
+//!
+//!     fn rigid_body_tracker(
+//!         (_, gravity, _): &SystemParamItem<Self::Params>,
+//!         (transform, body): QueryItem<Self::RigidBody>,
+//!     ) -> TnuaRigidBodyTracker {
+//!         let (_, rotation, translation) = transform.to_scale_rotation_translation();
+//!         TnuaRigidBodyTracker {
+//!             translation: translation.adjust_precision(),
+//!             rotation: rotation.adjust_precision(),
+//!             velocity: body.velocity,
+//!             angvel: body.angvel,
+//!             gravity: gravity.0,
+//!         }
+//!     }
+//!
+//!     fn cast(
+//!         (world, _, _): &SystemParamItem<Self::Params>,
+//!         _: &QueryItem<Self::SensorShape>,
+//!         cast: &TnuaSensorCast,
+//!     ) -> Option<TnuaSensorHit> {
+//!         world.cast_ray(cast.origin, cast.direction, cast.range, |entity| {
+//!             entity != cast.owner_entity && cast.allows(entity)
+//!         })
+//!     }
+//!
+//!     fn point_velocity(
+//!         (_, _, bodies): &SystemParamItem<Self::Params>,
+//!         entity: Entity,
+//!         _point: Vector3,
+//!     ) -> Option<(Vector3, Vector3)> {
+//!         let body = bodies.get(entity).ok()?;
+//!         Some((body.velocity, body.angvel))
+//!     }
+//!
+//!     fn apply_motor(
+//!         (gravity, time): &SystemParamItem<Self::MotorParams>,
+//!         motor: &TnuaMotor,
+//!         gravity_override: Option<Vector3>,
+//!         mut body: QueryItem<Self::Motor>,
+//!     ) {
+//!         let dt = time.delta_seconds().adjust_precision();
+//!         let gravity_offset = gravity_override.map_or(Vector3::ZERO, |gravity_override| {
+//!             gravity_override - gravity.0
+//!         });
+//!         body.velocity += motor.lin.boost + dt * (motor.lin.acceleration + gravity_offset);
+//!         body.angvel += motor.ang.boost + dt * motor.ang.acceleration;
+//!     }
+//! }
+//!
+//! struct TnuaMyEnginePlugin;
+//!
+//! impl Plugin for TnuaMyEnginePlugin {
+//!     fn build(&self, app: &mut App) {
+//!         // Running Tnua before the engine's step is what makes the motor's output take effect
+//!         // in the same frame.
+//!         app.configure_sets(Update, TnuaSystemSet.before(MyEngineStepSet));
+//!         app.add_plugins(TnuaPhysicsBackendPlugin::<MyEngineBackend>::default());
+//!     }
+//! }
+//! # let mut app = App::new();
+//! # app.add_plugins((MinimalPlugins, TnuaMyEnginePlugin));
+//! # app.insert_resource(MyEngineWorld);
+//! # app.insert_resource(MyEngineGravity(-9.81 * Vector3::Y));
+//! # app.world_mut().spawn((
+//! #     TransformBundle::default(),
+//! #     MyEngineBody { velocity: Vector3::ZERO, angvel: Vector3::ZERO },
+//! #     TnuaRigidBodyTracker::default(),
+//! #     TnuaProximitySensor::default(),
+//! #     TnuaMotor::default(),
+//! # ));
+//! # app.update();
+//! ```
+//!
+//! Backends that need more control than the trait gives can still add their own systems that
+//! work with the components directly, like the Avian integration crates do.
 use bevy::prelude::*;
 
+pub mod backend;
 pub mod data_for_backends;
 pub mod math;
+pub mod stage_tracing;
 pub mod subservient_sensors;
 
 /// Umbrella system set for [`TnuaPipelineStages`].
@@ -74,7 +221,7 @@ pub mod subservient_sensors;
 pub struct TnuaSystemSet;
 
 /// The various stages of the Tnua pipeline.
-#[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum TnuaPipelineStages {
     /// Data is read from the physics backend.
     Sensors,
@@ -0,0 +1,104 @@
+//! Tracing spans for attributing the time spent in the [`TnuaPipelineStages`] to specific stages
+//! and characters.
+//!
+//! The spans are only emitted when the `trace` feature is enabled. Without it,
+//! [`TnuaStageTrace`] and [`TnuaCharacterSpan`] are empty and compile to nothing, so systems can
+//! use them unconditionally.
+//!
+//! The spans are regular [`tracing`](bevy::utils::tracing) spans, so they show up in any tracing
+//! subscriber - including Tracy, when Bevy's `trace_tracy` feature is enabled.
+//!
+//! Systems that work on characters should create a [`TnuaStageTrace`] when they start, and enter
+//! a [`character`](TnuaStageTrace::character) span while working on each character:
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_tnua_physics_integration_layer::data_for_backends::TnuaMotor;
+//! # use bevy_tnua_physics_integration_layer::stage_tracing::TnuaStageTrace;
+//! # use bevy_tnua_physics_integration_layer::TnuaPipelineStages;
+//! fn apply_motors_system(query: Query<(Entity, &TnuaMotor)>) {
+//!     let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::Motors);
+//!     query.par_iter().for_each(|(entity, motor)| {
+//!         let _span = stage_trace.character(entity);
+//!         // Apply the motor
+//!     });
+//! }
+//! ```
+#[cfg(feature = "trace")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::prelude::*;
+
+use crate::TnuaPipelineStages;
+
+/// A span covering the work of a single system in one of the [`TnuaPipelineStages`].
+///
+/// The span is entered when this is created, and exited when it is dropped.
+///
+/// When dropped, records (in its `characters` field) how many times
+/// [`character`](Self::character) was called - i.e. how many characters the system worked on.
+pub struct TnuaStageTrace {
+    #[cfg(feature = "trace")]
+    span: bevy::utils::tracing::span::EnteredSpan,
+    #[cfg(feature = "trace")]
+    characters: AtomicUsize,
+}
+
+impl TnuaStageTrace {
+    pub fn new(stage: TnuaPipelineStages) -> Self {
+        #[cfg(feature = "trace")]
+        {
+            Self {
+                span: bevy::utils::tracing::info_span!(
+                    "tnua_stage",
+                    stage = ?stage,
+                    characters = bevy::utils::tracing::field::Empty,
+                )
+                .entered(),
+                characters: AtomicUsize::new(0),
+            }
+        }
+        #[cfg(not(feature = "trace"))]
+        {
+            let _ = stage;
+            Self {}
+        }
+    }
+
+    /// Enter a span for the work on a single character, as a child of the stage span.
+    ///
+    /// The span is exited when the returned guard is dropped.
+    pub fn character(&self, entity: Entity) -> TnuaCharacterSpan {
+        #[cfg(feature = "trace")]
+        {
+            self.characters.fetch_add(1, Ordering::Relaxed);
+            TnuaCharacterSpan {
+                _entered: bevy::utils::tracing::info_span!(
+                    parent: self.span.id(),
+                    "tnua_character",
+                    ?entity,
+                )
+                .entered(),
+            }
+        }
+        #[cfg(not(feature = "trace"))]
+        {
+            let _ = entity;
+            TnuaCharacterSpan {}
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Drop for TnuaStageTrace {
+    fn drop(&mut self) {
+        self.span
+            .record("characters", self.characters.load(Ordering::Relaxed));
+    }
+}
+
+/// A guard for the span of the work on a single character. See [`TnuaStageTrace::character`].
+pub struct TnuaCharacterSpan {
+    #[cfg(feature = "trace")]
+    _entered: bevy::utils::tracing::span::EnteredSpan,
+}
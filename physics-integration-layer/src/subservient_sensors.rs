@@ -1,6 +1,14 @@
 use bevy::prelude::*;
 
+/// Marks a [`TnuaProximitySensor`](crate::data_for_backends::TnuaProximitySensor) on an entity
+/// other than the character's, that casts on behalf of the character.
+///
+/// The sensor entity should be a child of the character entity, so that its cast origin will move
+/// with the character. Physics backends exclude the [owner](Self::owner_entity)'s collider from
+/// the cast, and update the sensor's output during the same sensors stage as the character's main
+/// sensor.
 #[derive(Component)]
 pub struct TnuaSubservientSensor {
+    /// The character entity the sensor casts for.
     pub owner_entity: Entity,
 }
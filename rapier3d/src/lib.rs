@@ -7,21 +7,22 @@
 //! * Optionally: Add [`TnuaRapier3dSensorShape`] to the sensor entities. This means the entity of
 //!   the characters controlled by Tnua, but also other things like the entity generated by
 //!   `TnuaCrouchEnforcer`, that can be affected with a closure.
+//! * Optionally: Add [`TnuaRapier3dSensorCollisionGroups`] to the characters, to make their
+//!   sensors use different collision groups than their colliders.
+//! * Alternatively: Add [`TnuaRapier3dSensorRays`] to the sensor entities, to cast multiple rays
+//!   instead of a shape. This is cheaper than a shape cast, which can matter when there are many
+//!   characters.
 use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use bevy::utils::HashSet;
 use bevy_rapier3d::prelude::*;
 use bevy_rapier3d::rapier;
 use bevy_rapier3d::rapier::prelude::InteractionGroups;
 
-use bevy_tnua_physics_integration_layer::data_for_backends::TnuaGhostPlatform;
-use bevy_tnua_physics_integration_layer::data_for_backends::TnuaGhostSensor;
-use bevy_tnua_physics_integration_layer::data_for_backends::TnuaToggle;
-use bevy_tnua_physics_integration_layer::data_for_backends::{
-    TnuaMotor, TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker,
+use bevy_tnua_physics_integration_layer::backend::{
+    TnuaPhysicsBackend, TnuaPhysicsBackendPlugin, TnuaSensorCast, TnuaSensorHit,
 };
-use bevy_tnua_physics_integration_layer::subservient_sensors::TnuaSubservientSensor;
-use bevy_tnua_physics_integration_layer::TnuaPipelineStages;
+use bevy_tnua_physics_integration_layer::data_for_backends::{TnuaMotor, TnuaRigidBodyTracker};
 use bevy_tnua_physics_integration_layer::TnuaSystemSet;
 
 /// Add this plugin to use bevy_rapier3d as a physics backend.
@@ -53,18 +54,9 @@ impl Plugin for TnuaRapier3dPlugin {
                 |rapier_config: Res<RapierConfiguration>| rapier_config.physics_pipeline_active,
             ),
         );
-        app.add_systems(
+        app.add_plugins(TnuaPhysicsBackendPlugin::<TnuaRapier3dBackend>::new(
             self.schedule,
-            (
-                update_rigid_body_trackers_system,
-                update_proximity_sensors_system,
-            )
-                .in_set(TnuaPipelineStages::Sensors),
-        );
-        app.add_systems(
-            self.schedule,
-            apply_motors_system.in_set(TnuaPipelineStages::Motors),
-        );
+        ));
     }
 }
 
@@ -77,33 +69,74 @@ pub struct TnuaRapier3dIOBundle {
 }
 
 /// Add this component to make [`TnuaProximitySensor`] cast a shape instead of a ray.
+///
+/// [`TnuaProximitySensor`]: bevy_tnua_physics_integration_layer::data_for_backends::TnuaProximitySensor
 #[derive(Component)]
 pub struct TnuaRapier3dSensorShape(pub Collider);
 
-fn update_rigid_body_trackers_system(
-    rapier_config: Res<RapierConfiguration>,
-    mut query: Query<(
-        &GlobalTransform,
-        &Velocity,
-        &mut TnuaRigidBodyTracker,
-        Option<&TnuaToggle>,
-    )>,
-) {
-    for (transform, velocity, mut tracker, tnua_toggle) in query.iter_mut() {
-        match tnua_toggle.copied().unwrap_or_default() {
-            TnuaToggle::Disabled => continue,
-            TnuaToggle::SenseOnly => {}
-            TnuaToggle::Enabled => {}
-        }
-        let (_, rotation, translation) = transform.to_scale_rotation_translation();
-        *tracker = TnuaRigidBodyTracker {
-            translation,
-            rotation,
-            velocity: velocity.linvel,
-            angvel: velocity.angvel,
-            gravity: rapier_config.gravity,
-        };
+/// Add this component to the character entity to make its sensors use these collision groups
+/// instead of the ones of its collider.
+///
+/// This affects all the sensors of the character, including its subservient sensors. For
+/// excluding specific entities, use [`TnuaSensorFilter`].
+///
+/// [`TnuaSensorFilter`]: bevy_tnua_physics_integration_layer::data_for_backends::TnuaSensorFilter
+#[derive(Component)]
+pub struct TnuaRapier3dSensorCollisionGroups(pub CollisionGroups);
+
+/// Add this component to make [`TnuaProximitySensor`] cast multiple rays instead of a single ray.
+///
+/// Each ray is cast from the [`cast_origin`](TnuaProximitySensor::cast_origin) moved by one of the
+/// offsets (which are in the entity's coord system, and rotate with it only around the cast
+/// direction). The results are aggregated into a single output - the closest hit, with the average
+/// normal of all the hits.
+///
+/// This is a cheaper alternative to [`TnuaRapier3dSensorShape`] for large amounts of characters.
+/// If the entity has both components, [`TnuaRapier3dSensorShape`] is used.
+///
+/// [`TnuaProximitySensor`]: bevy_tnua_physics_integration_layer::data_for_backends::TnuaProximitySensor
+#[derive(Component, Clone, Debug)]
+pub struct TnuaRapier3dSensorRays(pub Vec<Vec3>);
+
+impl TnuaRapier3dSensorRays {
+    /// Cast rays from the center and from the four corners of a rectangular footprint,
+    /// perpendicular to the Y axis.
+    pub fn rectangle(half_width: f32, half_depth: f32) -> Self {
+        Self(vec![
+            Vec3::ZERO,
+            Vec3::new(-half_width, 0.0, -half_depth),
+            Vec3::new(-half_width, 0.0, half_depth),
+            Vec3::new(half_width, 0.0, -half_depth),
+            Vec3::new(half_width, 0.0, half_depth),
+        ])
     }
+
+    /// Cast rays from the center and from `num_rays` points evenly spread on a circular footprint,
+    /// perpendicular to the Y axis.
+    ///
+    /// Use the radius of the character's capsule (or cylinder) for this.
+    pub fn circle(radius: f32, num_rays: usize) -> Self {
+        Self(
+            std::iter::once(Vec3::ZERO)
+                .chain((0..num_rays).map(|i| {
+                    let angle = std::f32::consts::TAU * i as f32 / num_rays as f32;
+                    radius * Vec3::new(angle.cos(), 0.0, angle.sin())
+                }))
+                .collect(),
+        )
+    }
+}
+
+/// The [`TnuaPhysicsBackend`] of bevy_rapier3d, added by [`TnuaRapier3dPlugin`].
+pub struct TnuaRapier3dBackend;
+
+/// The data [`TnuaRapier3dBackend`] reads from bevy_rapier3d.
+#[derive(SystemParam)]
+pub struct TnuaRapier3dBackendParams<'w, 's> {
+    rapier_context: Res<'w, RapierContext>,
+    rapier_config: Res<'w, RapierConfiguration>,
+    sensor_collision_groups_query: Query<'w, 's, &'static TnuaRapier3dSensorCollisionGroups>,
+    other_object_query: Query<'w, 's, (&'static GlobalTransform, &'static Velocity)>,
 }
 
 fn get_collider(
@@ -112,240 +145,230 @@ fn get_collider(
 ) -> Option<&rapier::geometry::Collider> {
     let collider_handle = rapier_context.entity2collider().get(&entity)?;
     rapier_context.colliders.get(*collider_handle)
-    //if let Some(owner_collider) = rapier_context.entity2collider().get(&owner_entity).and_then(|handle| rapier_context.colliders.get(*handle)) {
 }
 
-#[allow(clippy::type_complexity)]
-fn update_proximity_sensors_system(
-    rapier_context: Res<RapierContext>,
-    mut query: Query<(
-        Entity,
-        &GlobalTransform,
-        &mut TnuaProximitySensor,
-        Option<&TnuaRapier3dSensorShape>,
-        Option<&mut TnuaGhostSensor>,
-        Option<&TnuaSubservientSensor>,
-        Option<&TnuaToggle>,
-    )>,
-    ghost_platforms_query: Query<(), With<TnuaGhostPlatform>>,
-    other_object_query: Query<(&GlobalTransform, &Velocity)>,
-) {
-    query.par_iter_mut().for_each(
-        |(
-            owner_entity,
-            transform,
-            mut sensor,
-            shape,
-            mut ghost_sensor,
-            subservient,
-            tnua_toggle,
-        )| {
-            match tnua_toggle.copied().unwrap_or_default() {
-                TnuaToggle::Disabled => return,
-                TnuaToggle::SenseOnly => {}
-                TnuaToggle::Enabled => {}
-            }
-            let cast_origin = transform.transform_point(sensor.cast_origin);
-            let cast_direction = sensor.cast_direction;
+impl TnuaPhysicsBackend for TnuaRapier3dBackend {
+    type Params = TnuaRapier3dBackendParams<'static, 'static>;
+    type MotorParams = Res<'static, RapierConfiguration>;
+    type RigidBody = (&'static GlobalTransform, &'static Velocity);
+    type SensorShape = (
+        Option<&'static TnuaRapier3dSensorShape>,
+        Option<&'static TnuaRapier3dSensorRays>,
+    );
+    type Motor = (
+        &'static mut Velocity,
+        &'static ReadMassProperties,
+        &'static mut ExternalForce,
+        Option<&'static GravityScale>,
+    );
 
-            struct CastResult {
-                entity: Entity,
-                proximity: f32,
-                intersection_point: Vec3,
-                normal: Dir3,
-            }
+    fn rigid_body_tracker(
+        params: &TnuaRapier3dBackendParams,
+        (transform, velocity): (&GlobalTransform, &Velocity),
+    ) -> TnuaRigidBodyTracker {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        TnuaRigidBodyTracker {
+            translation,
+            rotation,
+            velocity: velocity.linvel,
+            angvel: velocity.angvel,
+            gravity: params.rapier_config.gravity,
+        }
+    }
 
-            let owner_entity = if let Some(subservient) = subservient {
-                subservient.owner_entity
-            } else {
-                owner_entity
-            };
+    fn cast(
+        params: &TnuaRapier3dBackendParams,
+        (shape, rays): &(
+            Option<&TnuaRapier3dSensorShape>,
+            Option<&TnuaRapier3dSensorRays>,
+        ),
+        cast: &TnuaSensorCast,
+    ) -> Option<TnuaSensorHit> {
+        let rapier_context = &params.rapier_context;
+        let mut query_filter = QueryFilter::new().exclude_rigid_body(cast.owner_entity);
+        let owner_solver_groups: InteractionGroups;
 
-            let mut query_filter = QueryFilter::new().exclude_rigid_body(owner_entity);
-            let owner_solver_groups: InteractionGroups;
+        if let Some(owner_collider) = get_collider(rapier_context, cast.owner_collider_entity) {
+            let collision_groups = owner_collider.collision_groups();
+            query_filter.groups = Some(CollisionGroups {
+                memberships: Group::from_bits_truncate(collision_groups.memberships.bits()),
+                filters: Group::from_bits_truncate(collision_groups.filter.bits()),
+            });
+            owner_solver_groups = owner_collider.solver_groups();
+        } else {
+            owner_solver_groups = InteractionGroups::all();
+        }
+        if let Ok(sensor_collision_groups) =
+            params.sensor_collision_groups_query.get(cast.owner_entity)
+        {
+            query_filter.groups = Some(sensor_collision_groups.0);
+        }
 
-            if let Some(owner_collider) = get_collider(&rapier_context, owner_entity) {
-                let collision_groups = owner_collider.collision_groups();
-                query_filter.groups = Some(CollisionGroups {
-                    memberships: Group::from_bits_truncate(collision_groups.memberships.bits()),
-                    filters: Group::from_bits_truncate(collision_groups.filter.bits()),
-                });
-                owner_solver_groups = owner_collider.solver_groups();
-            } else {
-                owner_solver_groups = InteractionGroups::all();
+        let predicate = |other_entity: Entity| {
+            if !cast.allows(other_entity)
+                || rapier_context
+                    .collider_parent(other_entity)
+                    .is_some_and(|parent| !cast.allows(parent))
+            {
+                return false;
+            }
+            if let Some(other_collider) = get_collider(rapier_context, other_entity) {
+                if !other_collider.solver_groups().test(owner_solver_groups)
+                    && !cast.hits_ghost_platform(other_entity)
+                {
+                    return false;
+                }
+                if other_collider.is_sensor() {
+                    return false;
+                }
             }
 
-            let mut already_visited_ghost_entities = HashSet::<Entity>::default();
-
-            let has_ghost_sensor = ghost_sensor.is_some();
-
-            let do_cast = |cast_range_skip: f32,
-                           already_visited_ghost_entities: &HashSet<Entity>|
-             -> Option<CastResult> {
-                let predicate = |other_entity: Entity| {
-                    if let Some(other_collider) = get_collider(&rapier_context, other_entity) {
-                        if !other_collider.solver_groups().test(owner_solver_groups) {
-                            if has_ghost_sensor && ghost_platforms_query.contains(other_entity) {
-                                if already_visited_ghost_entities.contains(&other_entity) {
-                                    return false;
-                                }
-                            } else {
-                                return false;
-                            }
-                        }
-                        if other_collider.is_sensor() {
+            // This fixes https://github.com/idanarye/bevy-tnua/issues/14
+            if let Some(contact) =
+                rapier_context.contact_pair(cast.owner_collider_entity, other_entity)
+            {
+                let same_order = cast.owner_collider_entity == contact.collider1();
+                for manifold in contact.manifolds() {
+                    if 0 < manifold.num_points() {
+                        let manifold_normal = if same_order {
+                            manifold.local_n2()
+                        } else {
+                            manifold.local_n1()
+                        };
+                        if cast.intersection_match_prevention_cutoff
+                            < manifold_normal.dot(*cast.direction)
+                        {
                             return false;
                         }
                     }
-
-                    // This fixes https://github.com/idanarye/bevy-tnua/issues/14
-                    if let Some(contact) = rapier_context.contact_pair(owner_entity, other_entity) {
-                        let same_order = owner_entity == contact.collider1();
-                        for manifold in contact.manifolds() {
-                            if 0 < manifold.num_points() {
-                                let manifold_normal = if same_order {
-                                    manifold.local_n2()
-                                } else {
-                                    manifold.local_n1()
-                                };
-                                if sensor.intersection_match_prevention_cutoff
-                                    < manifold_normal.dot(*cast_direction)
-                                {
-                                    return false;
-                                }
-                            }
-                        }
-                    }
-                    true
-                };
-                let query_filter = query_filter.predicate(&predicate);
-                let cast_origin = cast_origin + cast_range_skip * *cast_direction;
-                let cast_range = sensor.cast_range - cast_range_skip;
-                if let Some(TnuaRapier3dSensorShape(shape)) = shape {
-                    let (_, owner_rotation, _) = transform.to_scale_rotation_translation();
-                    let owner_rotation = Quat::from_scaled_axis(
-                        owner_rotation.to_scaled_axis().dot(*cast_direction) * *cast_direction,
-                    );
-                    rapier_context
-                        .cast_shape(
-                            cast_origin,
-                            owner_rotation,
-                            *cast_direction,
-                            shape,
-                            ShapeCastOptions {
-                                max_time_of_impact: cast_range,
-                                target_distance: 0.0,
-                                stop_at_penetration: false,
-                                compute_impact_geometry_on_penetration: false,
-                            },
-                            query_filter,
-                        )
-                        .and_then(|(entity, hit)| {
-                            let details = hit.details?;
-                            Some(CastResult {
-                                entity,
-                                proximity: hit.time_of_impact,
-                                intersection_point: details.witness1,
-                                normal: Dir3::new(details.normal1)
-                                    .unwrap_or_else(|_| -cast_direction),
-                            })
-                        })
-                } else {
-                    rapier_context
-                        .cast_ray_and_get_normal(
-                            cast_origin,
-                            *cast_direction,
-                            cast_range,
-                            false,
-                            query_filter,
-                        )
-                        .map(|(entity, hit)| CastResult {
-                            entity,
-                            proximity: hit.time_of_impact,
-                            intersection_point: hit.point,
-                            normal: Dir3::new(hit.normal).unwrap_or_else(|_| -cast_direction),
-                        })
                 }
-            };
-
-            let mut cast_range_skip = 0.0;
-            if let Some(ghost_sensor) = ghost_sensor.as_mut() {
-                ghost_sensor.0.clear();
             }
-            sensor.output = 'sensor_output: loop {
-                if let Some(CastResult {
-                    entity,
-                    proximity,
-                    intersection_point,
-                    normal,
-                }) = do_cast(cast_range_skip, &already_visited_ghost_entities)
-                {
-                    let entity_linvel;
-                    let entity_angvel;
-                    if let Ok((entity_transform, entity_velocity)) = other_object_query.get(entity)
-                    {
-                        entity_angvel = entity_velocity.angvel;
-                        entity_linvel = entity_velocity.linvel
-                            + if 0.0 < entity_angvel.length_squared() {
-                                let relative_point =
-                                    intersection_point - entity_transform.translation();
-                                // NOTE: no need to project relative_point on the rotation plane, it will not
-                                // affect the cross product.
-                                entity_angvel.cross(relative_point)
-                            } else {
-                                Vec3::ZERO
-                            };
-                    } else {
-                        entity_angvel = Vec3::ZERO;
-                        entity_linvel = Vec3::ZERO;
-                    }
-                    let sensor_output = TnuaProximitySensorOutput {
+            true
+        };
+        let query_filter = query_filter.predicate(&predicate);
+        let cast_direction = cast.direction;
+        // Shapes and rays only rotate with the owner around the cast direction.
+        let owner_rotation = {
+            let (_, owner_rotation, _) = cast.transform.to_scale_rotation_translation();
+            Quat::from_scaled_axis(
+                owner_rotation.to_scaled_axis().dot(*cast_direction) * *cast_direction,
+            )
+        };
+        if let Some(TnuaRapier3dSensorShape(shape)) = shape {
+            rapier_context
+                .cast_shape(
+                    cast.origin,
+                    owner_rotation,
+                    *cast_direction,
+                    shape,
+                    ShapeCastOptions {
+                        max_time_of_impact: cast.range,
+                        target_distance: 0.0,
+                        stop_at_penetration: false,
+                        compute_impact_geometry_on_penetration: false,
+                    },
+                    query_filter,
+                )
+                .and_then(|(entity, hit)| {
+                    let details = hit.details?;
+                    Some(TnuaSensorHit {
                         entity,
-                        proximity,
-                        normal,
-                        entity_linvel,
-                        entity_angvel,
-                    };
-                    if ghost_platforms_query.contains(entity) {
-                        cast_range_skip = proximity;
-                        already_visited_ghost_entities.insert(entity);
-                        if let Some(ghost_sensor) = ghost_sensor.as_mut() {
-                            ghost_sensor.0.push(sensor_output);
-                        }
-                    } else {
-                        break 'sensor_output Some(sensor_output);
-                    }
-                } else {
-                    break 'sensor_output None;
+                        proximity: hit.time_of_impact,
+                        intersection_point: details.witness1,
+                        normal: Dir3::new(details.normal1).unwrap_or_else(|_| -cast_direction),
+                    })
+                })
+        } else if let Some(TnuaRapier3dSensorRays(offsets)) = rays {
+            let mut closest: Option<TnuaSensorHit> = None;
+            let mut normals_sum = Vec3::ZERO;
+            for offset in offsets.iter() {
+                let Some((entity, hit)) = rapier_context.cast_ray_and_get_normal(
+                    cast.origin + owner_rotation * *offset,
+                    *cast_direction,
+                    cast.range,
+                    false,
+                    query_filter,
+                ) else {
+                    continue;
+                };
+                normals_sum += hit.normal;
+                if closest
+                    .as_ref()
+                    .is_some_and(|closest| closest.proximity <= hit.time_of_impact)
+                {
+                    continue;
                 }
-            };
-        },
-    );
-}
-
-fn apply_motors_system(
-    mut query: Query<(
-        &TnuaMotor,
-        &mut Velocity,
-        &ReadMassProperties,
-        &mut ExternalForce,
-        Option<&TnuaToggle>,
-    )>,
-) {
-    for (motor, mut velocity, mass_properties, mut external_force, tnua_toggle) in query.iter_mut()
-    {
-        match tnua_toggle.copied().unwrap_or_default() {
-            TnuaToggle::Disabled | TnuaToggle::SenseOnly => {
-                *external_force = Default::default();
-                return;
+                closest = Some(TnuaSensorHit {
+                    entity,
+                    proximity: hit.time_of_impact,
+                    intersection_point: hit.point,
+                    normal: Dir3::new(hit.normal).unwrap_or_else(|_| -cast_direction),
+                });
             }
-            TnuaToggle::Enabled => {}
+            closest.map(|closest| TnuaSensorHit {
+                normal: Dir3::new(normals_sum).unwrap_or(closest.normal),
+                ..closest
+            })
+        } else {
+            rapier_context
+                .cast_ray_and_get_normal(
+                    cast.origin,
+                    *cast_direction,
+                    cast.range,
+                    false,
+                    query_filter,
+                )
+                .map(|(entity, hit)| TnuaSensorHit {
+                    entity,
+                    proximity: hit.time_of_impact,
+                    intersection_point: hit.point,
+                    normal: Dir3::new(hit.normal).unwrap_or_else(|_| -cast_direction),
+                })
         }
+    }
+
+    fn point_velocity(
+        params: &TnuaRapier3dBackendParams,
+        entity: Entity,
+        point: Vec3,
+    ) -> Option<(Vec3, Vec3)> {
+        let (entity_transform, entity_velocity) = params.other_object_query.get(entity).ok()?;
+        let angvel = entity_velocity.angvel;
+        let linvel = entity_velocity.linvel
+            + if 0.0 < angvel.length_squared() {
+                let relative_point = point - entity_transform.translation();
+                // NOTE: no need to project relative_point on the rotation plane, it will not
+                // affect the cross product.
+                angvel.cross(relative_point)
+            } else {
+                Vec3::ZERO
+            };
+        Some((linvel, angvel))
+    }
+
+    fn apply_motor(
+        rapier_config: &Res<RapierConfiguration>,
+        motor: &TnuaMotor,
+        gravity_override: Option<Vec3>,
+        (mut velocity, mass_properties, mut external_force, gravity_scale): (
+            Mut<Velocity>,
+            &ReadMassProperties,
+            Mut<ExternalForce>,
+            Option<&GravityScale>,
+        ),
+    ) {
+        // The physics engine already applies its own gravity to the rigid body, so only the
+        // difference needs to be added.
+        let gravity_offset = gravity_override.map_or(Vect::ZERO, |gravity_override| {
+            gravity_override
+                - rapier_config.gravity * gravity_scale.map_or(1.0, |gravity_scale| gravity_scale.0)
+        });
         if motor.lin.boost.is_finite() {
             velocity.linvel += motor.lin.boost;
         }
         if motor.lin.acceleration.is_finite() {
-            external_force.force = motor.lin.acceleration * mass_properties.get().mass;
+            external_force.force =
+                (motor.lin.acceleration + gravity_offset) * mass_properties.get().mass;
         }
         if motor.ang.boost.is_finite() {
             velocity.angvel += motor.ang.boost;
@@ -355,4 +378,15 @@ fn apply_motors_system(
                 motor.ang.acceleration * mass_properties.get().principal_inertia;
         }
     }
+
+    fn release_motor(
+        (_, _, mut external_force, _): (
+            Mut<Velocity>,
+            &ReadMassProperties,
+            Mut<ExternalForce>,
+            Option<&GravityScale>,
+        ),
+    ) {
+        *external_force = Default::default();
+    }
 }
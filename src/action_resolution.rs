@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+/// Decides which action starts when several actions are fed for the first time in the same frame,
+/// and which actions are not allowed to cancel each other.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) and
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will use it when deciding on
+/// the actions. Without it, when several actions are pressed together the one that was fed last
+/// wins. Either way, the decision of the last frame can be inspected with
+/// [`TnuaController::action_resolution`](crate::prelude::TnuaController::action_resolution), and
+/// the actions that lost are reported as
+/// [rejected](crate::TnuaActionRejectionReason::OutprioritizedBy).
+///
+/// ```
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::TnuaActionResolutionPolicy;
+/// # use bevy_tnua::builtins::{TnuaBuiltinCrouch, TnuaBuiltinDash};
+/// let policy = TnuaActionResolutionPolicy {
+///     // When pressed together, dash beats jump and jump beats crouch.
+///     priority: vec![
+///         TnuaBuiltinDash::NAME,
+///         TnuaBuiltinJump::NAME,
+///         TnuaBuiltinCrouch::NAME,
+///     ],
+///     // A dash cannot be cancelled into a jump, and a jump cannot be cancelled into a dash.
+///     exclusive_groups: vec![vec![TnuaBuiltinDash::NAME, TnuaBuiltinJump::NAME]],
+/// };
+/// ```
+#[derive(Component, Clone, Debug, Default)]
+pub struct TnuaActionResolutionPolicy {
+    /// Action names, from the highest priority to the lowest.
+    ///
+    /// When multiple actions are fed for the first time in the same frame, the one with the
+    /// highest priority is the one that tries to start. Actions that are not listed have the
+    /// lowest priority, and between actions of the same priority the one that was fed last wins.
+    pub priority: Vec<&'static str>,
+
+    /// Groups of action names that are mutually exclusive - an action cannot start while another
+    /// action from the same group is running.
+    pub exclusive_groups: Vec<Vec<&'static str>>,
+}
+
+impl TnuaActionResolutionPolicy {
+    /// The rank of the action in [`priority`](Self::priority). Lower is more important.
+    pub fn rank(&self, action: &'static str) -> usize {
+        self.priority
+            .iter()
+            .position(|name| *name == action)
+            .unwrap_or(self.priority.len())
+    }
+
+    /// Check if the two actions share an [exclusive group](Self::exclusive_groups).
+    pub fn are_exclusive(&self, action: &'static str, other: &'static str) -> bool {
+        action != other
+            && self
+                .exclusive_groups
+                .iter()
+                .any(|group| group.contains(&action) && group.contains(&other))
+    }
+}
+
+/// How the controller resolved the actions that were fed for the first time in the same frame.
+///
+/// See [`TnuaController::action_resolution`](crate::prelude::TnuaController::action_resolution).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TnuaActionResolution {
+    /// The actions that were fed for the first time in the same frame, in the order they were
+    /// fed.
+    pub candidates: Vec<&'static str>,
+
+    /// The action that was chosen to try and start.
+    ///
+    /// Note that it can still be rejected or delayed by its own
+    /// [`initiation_decision`](crate::TnuaAction::initiation_decision).
+    pub winner: &'static str,
+}
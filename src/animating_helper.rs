@@ -2,6 +2,8 @@ use std::mem::discriminant;
 
 use bevy::prelude::*;
 
+use crate::math::Float;
+
 /// Utility for deciding which animation to play.
 ///
 /// Add `TnuaAnimatingState<State>` as a component, where `State` is a data type - usually an
@@ -60,6 +62,12 @@ use bevy::prelude::*;
 ///                 // animations.
 ///                 old_state: _,
 ///                 state,
+///             }
+///             // This example does not use interrupts, but they are started just like any
+///             // other animation.
+///             | TnuaAnimatingStateDirective::Interrupt {
+///                 old_state: _,
+///                 state,
 ///             } => {
 ///                 animation_player.stop_all();
 ///                 match state {
@@ -81,17 +89,109 @@ use bevy::prelude::*;
 ///     }
 /// }
 /// ```
+///
+/// Animations that must play regardless of the current state (e.g. knockback or death) can be
+/// forced with [`interrupt`](TnuaAnimatingState::interrupt). The next update will emit
+/// [`TnuaAnimatingStateDirective::Interrupt`], and from then on the interrupting animation will
+/// be maintained - ignoring the states fed to the update methods - until
+/// [`resume`](TnuaAnimatingState::resume) is called. Once resumed, the next update will emit an
+/// [`Alter`](TnuaAnimatingStateDirective::Alter) from the interrupting state to the new state.
+///
+/// When the basis is replaced with
+/// [`TnuaController::transition_to_basis`](crate::prelude::TnuaController::transition_to_basis),
+/// the [`Alter`](TnuaAnimatingStateDirective::Alter) to the animation of the new basis is emitted
+/// on the first frame of the transition, and
+/// [`TnuaController::basis_transition`](crate::prelude::TnuaController::basis_transition) reports
+/// its progress on the following frames - which can be used as the weight for crossfading from
+/// the animation of the old basis.
+///
+/// ## Blending
+///
+/// For crossfading between animations, set a [fade duration](Self::with_fade_duration) and use
+/// the `update_blended*` methods instead of the `update_by*` ones. They emit the same directives,
+/// and also track the [blend weights](Self::blend_weights) of the new animation (fading in) and of
+/// the old animations (fading out), so that the animating system only needs to apply them to the
+/// animation player.
+///
+/// States can also be hierarchical - e.g. "grounded" with "idle" and "run" under it, and
+/// "airborne" with "rise" and "fall" under it - by implementing [`TnuaAnimatingHierarchy`] and
+/// using [`update_hierarchical`](Self::update_hierarchical). Changing the animation within the
+/// same parent uses the (usually shorter) [sibling fade
+/// duration](Self::with_sibling_fade_duration):
+///
+/// ```
+/// # use bevy_tnua::{TnuaAnimatingHierarchy, TnuaAnimatingState};
+/// # use bevy_tnua::math::Float;
+/// #[derive(Clone, PartialEq)]
+/// enum AnimationState {
+///     Idle,
+///     Run(Float),
+///     Rise,
+///     Fall,
+/// }
+///
+/// impl TnuaAnimatingHierarchy for AnimationState {
+///     type Parent = bool; // is airborne
+///
+///     fn parent(&self) -> Self::Parent {
+///         matches!(self, AnimationState::Rise | AnimationState::Fall)
+///     }
+///
+///     fn is_same_animation(&self, other: &Self) -> bool {
+///         std::mem::discriminant(self) == std::mem::discriminant(other)
+///     }
+/// }
+///
+/// let mut animating_state = TnuaAnimatingState::default()
+///     .with_fade_duration(0.2)
+///     .with_sibling_fade_duration(0.1);
+/// animating_state.update_hierarchical(AnimationState::Idle, 1.0 / 60.0);
+/// animating_state.update_hierarchical(AnimationState::Run(5.0), 0.05);
+/// // Half way through the sibling fade from idle to running:
+/// let weights = animating_state.blend_weights().map(|(_, weight)| weight).collect::<Vec<_>>();
+/// assert!((weights[0] - 0.5).abs() < 0.001); // running
+/// assert!((weights[1] - 0.5).abs() < 0.001); // idle
+/// ```
 #[derive(Component)]
 pub struct TnuaAnimatingState<State> {
     state: Option<State>,
+    pending_interrupt: Option<State>,
+    interrupted: bool,
+    fade_duration: Float,
+    sibling_fade_duration: Float,
+    active_fade_duration: Float,
+    current_weight: Float,
+    fading_out: Vec<(State, Float)>,
 }
 
 impl<State> Default for TnuaAnimatingState<State> {
     fn default() -> Self {
-        Self { state: None }
+        Self {
+            state: None,
+            pending_interrupt: None,
+            interrupted: false,
+            fade_duration: 0.0,
+            sibling_fade_duration: 0.0,
+            active_fade_duration: 0.0,
+            current_weight: 1.0,
+            fading_out: Vec::new(),
+        }
     }
 }
 
+/// A state of [`TnuaAnimatingState`] that is part of a hierarchy. See
+/// [`TnuaAnimatingState::update_hierarchical`].
+pub trait TnuaAnimatingHierarchy {
+    /// The parent state - e.g. "grounded" for "idle" and "run".
+    type Parent: PartialEq;
+
+    /// The parent of this state.
+    fn parent(&self) -> Self::Parent;
+
+    /// Whether the two states are the same animation (possibly with different parameters).
+    fn is_same_animation(&self, other: &Self) -> bool;
+}
+
 pub enum TnuaAnimatingStateDirective<'a, State> {
     /// The animation to play remains the same - possibly with different parameters.
     Maintain { state: &'a State },
@@ -102,6 +202,14 @@ pub enum TnuaAnimatingStateDirective<'a, State> {
         old_state: Option<State>,
         state: &'a State,
     },
+    /// An animation was forced with [`interrupt`](TnuaAnimatingState::interrupt), and needs to be
+    /// played regardless of the previous animation.
+    ///
+    /// Also returned if an interrupting animation interrupts another interrupting animation.
+    Interrupt {
+        old_state: Option<State>,
+        state: &'a State,
+    },
 }
 
 impl<State> TnuaAnimatingState<State> {
@@ -114,6 +222,23 @@ impl<State> TnuaAnimatingState<State> {
         new_state: State,
         comparison: impl FnOnce(&State, &State) -> bool,
     ) -> TnuaAnimatingStateDirective<State> {
+        if let Some(interrupting_state) = self.pending_interrupt.take() {
+            self.interrupted = true;
+            self.reset_blend();
+            let old_state = self.state.replace(interrupting_state);
+            return TnuaAnimatingStateDirective::Interrupt {
+                old_state,
+                state: self.state.as_ref().expect("state was just placed there"),
+            };
+        }
+        if self.interrupted {
+            return TnuaAnimatingStateDirective::Maintain {
+                state: self
+                    .state
+                    .as_ref()
+                    .expect("interrupting state was placed there"),
+            };
+        }
         let is_same = self
             .state
             .as_ref()
@@ -124,6 +249,7 @@ impl<State> TnuaAnimatingState<State> {
                 state: self.state.as_ref().expect("state was just placed there"),
             }
         } else {
+            self.reset_blend();
             TnuaAnimatingStateDirective::Alter {
                 old_state,
                 state: self.state.as_ref().expect("state was just placed there"),
@@ -155,10 +281,215 @@ impl<State> TnuaAnimatingState<State> {
         self.update_by(new_state, |a, b| discriminant(a) == discriminant(b))
     }
 
+    /// Force an animation to play, regardless of the current state.
+    ///
+    /// The next update will return [`TnuaAnimatingStateDirective::Interrupt`] with this state, and
+    /// the following updates will maintain it until [`resume`](Self::resume) is called. If this is
+    /// called again before the next update, only the last interrupting state is used.
+    pub fn interrupt(&mut self, interrupting_state: State) {
+        self.pending_interrupt = Some(interrupting_state);
+    }
+
+    /// Stop maintaining the interrupting animation, so that the next update will pick the
+    /// animation based on the state fed to it.
+    ///
+    /// Typically called when the interrupting animation is finished. Does not cancel interrupts
+    /// that were requested after the last update.
+    pub fn resume(&mut self) {
+        self.interrupted = false;
+    }
+
+    /// Check if an interrupting animation is playing (or is about to play on the next update).
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted || self.pending_interrupt.is_some()
+    }
+
     /// Get the current state.
     ///
     /// Can provide no information about the previous state.
     pub fn get(&self) -> Option<&State> {
         self.state.as_ref()
     }
+
+    fn reset_blend(&mut self) {
+        self.current_weight = 1.0;
+        self.fading_out.clear();
+    }
+
+    /// Set the duration, in seconds, of the crossfade between animations when using the
+    /// `update_blended*` methods.
+    ///
+    /// This also sets the [sibling fade duration](Self::with_sibling_fade_duration).
+    pub fn with_fade_duration(mut self, fade_duration: Float) -> Self {
+        self.fade_duration = fade_duration;
+        self.sibling_fade_duration = fade_duration;
+        self
+    }
+
+    /// Set the duration, in seconds, of the crossfade between animations that share a
+    /// [parent](TnuaAnimatingHierarchy::parent) when using
+    /// [`update_hierarchical`](Self::update_hierarchical).
+    pub fn with_sibling_fade_duration(mut self, sibling_fade_duration: Float) -> Self {
+        self.sibling_fade_duration = sibling_fade_duration;
+        self
+    }
+
+    /// The animations to play, each with its blend weight.
+    ///
+    /// The first item is the current state, fading in. The rest are the previous states that are
+    /// still fading out, from the oldest to the newest. The weights always add up to `1.0`.
+    ///
+    /// The weights are only updated by the `update_blended*` methods - the `update_by*` methods
+    /// give the current state the full weight.
+    pub fn blend_weights(&self) -> impl '_ + Iterator<Item = (&State, Float)> {
+        self.state
+            .iter()
+            .map(|state| (state, self.current_weight))
+            .chain(
+                self.fading_out
+                    .iter()
+                    .map(|(state, weight)| (state, *weight)),
+            )
+    }
+}
+
+impl<State: Clone> TnuaAnimatingState<State> {
+    /// Consider a new animation to play, and advance the crossfade by `frame_duration`.
+    ///
+    /// Like [`update_by`](Self::update_by), but also updates the
+    /// [`blend_weights`](Self::blend_weights) using the [fade
+    /// duration](Self::with_fade_duration).
+    pub fn update_blended(
+        &mut self,
+        new_state: State,
+        comparison: impl FnOnce(&State, &State) -> bool,
+        frame_duration: Float,
+    ) -> TnuaAnimatingStateDirective<State> {
+        let fade_duration = self.fade_duration;
+        self.update_with_fade(new_state, comparison, |_, _| fade_duration, frame_duration)
+    }
+
+    /// Like [`update_by_value`](Self::update_by_value), but also updates the
+    /// [`blend_weights`](Self::blend_weights).
+    pub fn update_blended_by_value(
+        &mut self,
+        new_state: State,
+        frame_duration: Float,
+    ) -> TnuaAnimatingStateDirective<State>
+    where
+        State: PartialEq,
+    {
+        self.update_blended(new_state, |a, b| a == b, frame_duration)
+    }
+
+    /// Like [`update_by_discriminant`](Self::update_by_discriminant), but also updates the
+    /// [`blend_weights`](Self::blend_weights).
+    pub fn update_blended_by_discriminant(
+        &mut self,
+        new_state: State,
+        frame_duration: Float,
+    ) -> TnuaAnimatingStateDirective<State> {
+        self.update_blended(
+            new_state,
+            |a, b| discriminant(a) == discriminant(b),
+            frame_duration,
+        )
+    }
+
+    /// Consider a new animation to play in a [hierarchy of states](TnuaAnimatingHierarchy), and
+    /// advance the crossfade by `frame_duration`.
+    ///
+    /// The states are compared with
+    /// [`is_same_animation`](TnuaAnimatingHierarchy::is_same_animation). When the animation
+    /// changes, the crossfade uses the [sibling fade duration](Self::with_sibling_fade_duration) if
+    /// the old and new states have the same [parent](TnuaAnimatingHierarchy::parent), and the
+    /// [fade duration](Self::with_fade_duration) otherwise.
+    pub fn update_hierarchical(
+        &mut self,
+        new_state: State,
+        frame_duration: Float,
+    ) -> TnuaAnimatingStateDirective<State>
+    where
+        State: TnuaAnimatingHierarchy,
+    {
+        let (fade_duration, sibling_fade_duration) =
+            (self.fade_duration, self.sibling_fade_duration);
+        self.update_with_fade(
+            new_state,
+            |a, b| a.is_same_animation(b),
+            |old_state, state| {
+                if old_state.parent() == state.parent() {
+                    sibling_fade_duration
+                } else {
+                    fade_duration
+                }
+            },
+            frame_duration,
+        )
+    }
+
+    fn update_with_fade(
+        &mut self,
+        new_state: State,
+        comparison: impl FnOnce(&State, &State) -> bool,
+        fade_duration: impl FnOnce(&State, &State) -> Float,
+        frame_duration: Float,
+    ) -> TnuaAnimatingStateDirective<State> {
+        let old_state = self.state.clone();
+        let old_weight = self.current_weight;
+        let mut fading_out = std::mem::take(&mut self.fading_out);
+        let interrupted = match self.update_by(new_state, comparison) {
+            TnuaAnimatingStateDirective::Maintain { .. } => None,
+            TnuaAnimatingStateDirective::Alter { .. } => Some(false),
+            TnuaAnimatingStateDirective::Interrupt { .. } => Some(true),
+        };
+
+        if interrupted.is_some() {
+            let fade = match (&old_state, &self.state) {
+                (Some(old_state), Some(state)) => {
+                    Some((old_state, fade_duration(old_state, state)))
+                }
+                _ => None,
+            };
+            if let Some((old_state, fade_duration)) = fade.filter(|(_, duration)| 0.0 < *duration) {
+                fading_out.push((old_state.clone(), old_weight));
+                self.current_weight = 0.0;
+                self.active_fade_duration = fade_duration;
+            } else {
+                fading_out.clear();
+                self.current_weight = 1.0;
+            }
+        }
+
+        if self.current_weight < 1.0 {
+            self.current_weight = if 0.0 < self.active_fade_duration {
+                (self.current_weight + frame_duration / self.active_fade_duration).min(1.0)
+            } else {
+                1.0
+            };
+        }
+        // Scale the fading out animations down so that all the weights add up to 1.0.
+        let fading_total: Float = fading_out.iter().map(|(_, weight)| weight).sum();
+        if self.current_weight < 1.0 && 0.0 < fading_total {
+            let scale = (1.0 - self.current_weight) / fading_total;
+            for (_, weight) in fading_out.iter_mut() {
+                *weight *= scale;
+            }
+            fading_out.retain(|(_, weight)| 0.0 < *weight);
+        } else {
+            fading_out.clear();
+            self.current_weight = 1.0;
+        }
+        self.fading_out = fading_out;
+
+        let state = self
+            .state
+            .as_ref()
+            .expect("update_by always places a state");
+        match interrupted {
+            None => TnuaAnimatingStateDirective::Maintain { state },
+            Some(false) => TnuaAnimatingStateDirective::Alter { old_state, state },
+            Some(true) => TnuaAnimatingStateDirective::Interrupt { old_state, state },
+        }
+    }
 }
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::math::{AsF32, Float, Vector3};
+use crate::{TnuaMotor, TnuaRigidBodyTracker, TnuaToggle, TnuaUpdateRate};
+
+/// Keeps a character inside an axis-aligned box - either by wrapping it around to the opposite
+/// edge (like in arcade games where exiting the screen on one side enters it from the other) or
+/// by stopping it at the edges.
+///
+/// Add this component to the character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) and
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will enforce it after the
+/// controller decides on the motor, so that the sensors of the next frame are already cast from
+/// the new position.
+///
+/// To only limit some of the axes, set the bounds of the other axes to infinity (this is what
+/// [`Default`] does).
+///
+/// The character is moved by changing its [`Transform`], which means the character entity must
+/// not have a parent (or its parent must not be transformed). Wrapping keeps the velocity of the
+/// character. Wrapping along the up axis is not recommended, because actions that track the
+/// height of the character (like [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump)) would see
+/// it as a sudden climb or drop.
+#[derive(Component, Clone, Debug)]
+pub struct TnuaArenaBounds {
+    /// The minimal coordinates, in world space, of the center of the character.
+    pub min: Vector3,
+
+    /// The maximal coordinates, in world space, of the center of the character.
+    pub max: Vector3,
+
+    pub mode: TnuaArenaBoundsMode,
+}
+
+impl Default for TnuaArenaBounds {
+    fn default() -> Self {
+        Self {
+            min: Vector3::NEG_INFINITY,
+            max: Vector3::INFINITY,
+            mode: TnuaArenaBoundsMode::Wrap,
+        }
+    }
+}
+
+/// What [`TnuaArenaBounds`] does when the character crosses its edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TnuaArenaBoundsMode {
+    /// Teleport the character to the opposite edge.
+    Wrap,
+    /// Keep the character at the edge, and stop its movement out of the bounds.
+    Clamp,
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_arena_bounds_system(
+    mut query: Query<(
+        &TnuaArenaBounds,
+        &mut TnuaRigidBodyTracker,
+        &mut TnuaMotor,
+        &mut Transform,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+) {
+    query.par_iter_mut().for_each(
+        |(bounds, mut tracker, mut motor, mut transform, tnua_toggle, update_rate)| {
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled | TnuaToggle::SenseOnly => return,
+                TnuaToggle::Enabled => {}
+            }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            let mut translation = tracker.translation;
+            for axis in 0..3 {
+                let (min, max) = (bounds.min[axis], bounds.max[axis]);
+                let position = translation[axis];
+                if min <= position && position <= max {
+                    continue;
+                }
+                match bounds.mode {
+                    TnuaArenaBoundsMode::Wrap => {
+                        let size = max - min;
+                        if size.is_finite() && 0.0 < size {
+                            translation[axis] = min + (position - min).rem_euclid(size);
+                        }
+                    }
+                    TnuaArenaBoundsMode::Clamp => {
+                        translation[axis] = position.clamp(min, max);
+                        let outward: Float = if position < min { -1.0 } else { 1.0 };
+                        let mut axis_direction = Vector3::ZERO;
+                        axis_direction[axis] = outward;
+                        let outward_velocity = (tracker.velocity + motor.lin.boost)
+                            .dot(axis_direction)
+                            .max(0.0);
+                        motor.lin.boost -= outward_velocity * axis_direction;
+                        if 0.0 < motor.lin.acceleration.dot(axis_direction) {
+                            motor.lin.acceleration[axis] = 0.0;
+                        }
+                    }
+                }
+            }
+            if translation != tracker.translation {
+                let offset = translation - tracker.translation;
+                transform.translation += offset.f32();
+                tracker.translation = translation;
+            }
+        },
+    );
+}
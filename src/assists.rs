@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use crate::builtins::{
+    TnuaBuiltinDash, TnuaBuiltinJump, TnuaBuiltinJumpState, TnuaBuiltinKnockback, TnuaBuiltinWalk,
+};
+use crate::controller::TnuaController;
+use crate::math::{Float, Vector3};
+use crate::TnuaBasis;
+
+/// Assists that make the character easier to control, for games that offer accessibility modes.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) to enable the assists for that character,
+/// and remove it to disable them. The assists are applied by
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) on top of the
+/// [builtins](crate::builtins) fed by the game code, so the controls system does not need to know
+/// about them.
+///
+/// The default value does not assist with anything - each assist needs to be enabled explicitly.
+#[derive(Component, Clone)]
+pub struct TnuaAssists {
+    /// Extra time, in seconds, added to [`TnuaBuiltinWalk::coyote_time`] - so that the player can
+    /// still jump for a longer while after walking off a ledge.
+    pub extra_coyote_time: Float,
+
+    /// Extra time, in seconds, added to the input buffer time of [`TnuaBuiltinJump`] and
+    /// [`TnuaBuiltinDash`] - so that pressing the button too early would still register.
+    pub extra_input_buffer_time: Float,
+
+    /// Make jumps reach their full height even if the player releases the button early, so that
+    /// the player does not need to hold the button for precise durations.
+    pub full_height_jumps: bool,
+
+    /// Multiply the [`shove`](TnuaBuiltinKnockback::shove) of knockbacks by this number.
+    ///
+    /// Values lower than `1.0` make the character stick to its ground and resist getting knocked
+    /// back. `0.0` disables knockbacks entirely.
+    pub knockback_multiplier: Float,
+
+    /// Automatically jump with this action when the character walks off a ledge.
+    ///
+    /// The jump is triggered when the character starts falling while walking and not performing
+    /// any action (that is - when coyote time starts), so it may also trigger when stepping down
+    /// from a high step.
+    pub auto_jump_at_ledges: Option<TnuaBuiltinJump>,
+
+    auto_jumping: bool,
+}
+
+impl Default for TnuaAssists {
+    fn default() -> Self {
+        Self {
+            extra_coyote_time: 0.0,
+            extra_input_buffer_time: 0.0,
+            full_height_jumps: false,
+            knockback_multiplier: 1.0,
+            auto_jump_at_ledges: None,
+            auto_jumping: false,
+        }
+    }
+}
+
+pub(crate) fn apply_assists_system(mut query: Query<(&mut TnuaController, &mut TnuaAssists)>) {
+    query
+        .par_iter_mut()
+        .for_each(|(mut controller, mut assists)| {
+            let controller = controller.as_mut();
+
+            if let Some(auto_jump) = assists.auto_jump_at_ledges.as_ref() {
+                let walking_into_coyote_time = controller
+                    .concrete_basis::<TnuaBuiltinWalk>()
+                    .is_some_and(|(walk, state)| {
+                        walk.desired_velocity != Vector3::ZERO
+                            && walk.displacement(state).is_none()
+                            && !walk.is_airborne(state)
+                    });
+                let keep_jumping = if assists.auto_jumping {
+                    match controller.concrete_action::<TnuaBuiltinJump>() {
+                        Some((_, TnuaBuiltinJumpState::FallSection)) => false,
+                        Some(_) => true,
+                        // The jump did not start yet.
+                        None => walking_into_coyote_time,
+                    }
+                } else {
+                    walking_into_coyote_time && controller.action_name().is_none()
+                };
+                if keep_jumping {
+                    controller.action(auto_jump.clone());
+                }
+                assists.auto_jumping = keep_jumping;
+            }
+
+            if let Some(walk) = controller.take_fresh_basis_input::<TnuaBuiltinWalk>() {
+                walk.coyote_time += assists.extra_coyote_time;
+            }
+            controller.for_each_fresh_action_input(|jump: &mut TnuaBuiltinJump| {
+                jump.input_buffer_time += assists.extra_input_buffer_time;
+                if assists.full_height_jumps {
+                    jump.shorten_extra_gravity = 0.0;
+                }
+            });
+            controller.for_each_fresh_action_input(|dash: &mut TnuaBuiltinDash| {
+                dash.input_buffer_time += assists.extra_input_buffer_time;
+            });
+            controller.for_each_fresh_action_input(|knockback: &mut TnuaBuiltinKnockback| {
+                knockback.shove *= assists.knockback_multiplier;
+            });
+        });
+}
@@ -4,9 +4,13 @@ use bevy_tnua_physics_integration_layer::math::{Float, Vector3};
 
 use std::{any::Any, time::Duration};
 
-use crate::{TnuaMotor, TnuaProximitySensor, TnuaRigidBodyTracker};
+use crate::{
+    TnuaMotor, TnuaProximitySensor, TnuaRigidBodyTracker, TnuaRootMotionVelocity,
+    TnuaSurfaceProperties,
+};
 
 /// Various data passed to [`TnuaBasis::apply`].
+#[non_exhaustive]
 pub struct TnuaBasisContext<'a> {
     /// The duration of the current frame.
     pub frame_duration: Float,
@@ -17,6 +21,19 @@ pub struct TnuaBasisContext<'a> {
     /// A sensor that tracks the distance of the character's center from the ground.
     pub proximity_sensor: &'a TnuaProximitySensor,
 
+    /// The properties of the surface the proximity sensor detected, if it has
+    /// [`TnuaSurfaceProperties`].
+    pub surface_properties: Option<&'a TnuaSurfaceProperties>,
+
+    /// The velocity accumulated by [`TnuaExternalForce`](crate::TnuaExternalForce), which bases
+    /// should add to the velocity they try to maintain instead of cancelling it.
+    pub external_velocity: Vector3,
+
+    /// The root motion of the current frame, if the character has
+    /// [`TnuaRootMotion`](crate::TnuaRootMotion). Bases that support root motion should follow it
+    /// instead of their own desired velocity.
+    pub root_motion: Option<TnuaRootMotionVelocity>,
+
     /// The direction considered as "up".
     pub up_direction: Dir3,
 }
@@ -99,6 +116,30 @@ pub trait TnuaBasis: 'static + Send + Sync {
     ///
     /// If the character is fully grounded, this method must not change that.
     fn violate_coyote_time(&self, state: &mut Self::State);
+
+    /// The time, in seconds, since the character was last grounded, or `None` if the basis does
+    /// not track it.
+    ///
+    /// This is a query method for game code, and should be `0.0` while the character is grounded.
+    fn airborne_duration(&self, _state: &Self::State) -> Option<Float> {
+        None
+    }
+
+    /// How far above the height it wants to float at the character is (negative if it is below
+    /// it), or `None` if the character does not stand on anything or the basis does not track it.
+    ///
+    /// This is a query method for game code.
+    fn float_offset(&self, _state: &Self::State) -> Option<Float> {
+        None
+    }
+
+    /// The velocity, perpendicular to the up direction, that the character is supposed to run at,
+    /// or `None` if the basis does not have such notion.
+    ///
+    /// This is a query method for game code.
+    fn running_velocity(&self, _state: &Self::State) -> Option<Vector3> {
+        None
+    }
 }
 
 /// Helper trait for accessing a basis and its trait with dynamic dispatch.
@@ -132,11 +173,26 @@ pub trait DynamicBasis: Send + Sync + Any + 'static {
 
     #[doc(hidden)]
     fn violate_coyote_time(&mut self);
+
+    #[doc(hidden)]
+    fn reset_state(&mut self);
+
+    /// Dynamically invokes [`TnuaBasis::airborne_duration`].
+    fn airborne_duration(&self) -> Option<Float>;
+
+    /// Dynamically invokes [`TnuaBasis::float_offset`].
+    fn float_offset(&self) -> Option<Float>;
+
+    /// Dynamically invokes [`TnuaBasis::running_velocity`].
+    fn running_velocity(&self) -> Option<Vector3>;
 }
 
 pub(crate) struct BoxableBasis<B: TnuaBasis> {
     pub(crate) input: B,
     pub(crate) state: B::State,
+    /// Set when the input is fed, so that [`TnuaAssists`](crate::TnuaAssists) will only adjust
+    /// each fed input once.
+    pub(crate) input_is_fresh: bool,
 }
 
 impl<B: TnuaBasis> BoxableBasis<B> {
@@ -144,6 +200,7 @@ impl<B: TnuaBasis> BoxableBasis<B> {
         Self {
             input: basis,
             state: Default::default(),
+            input_is_fresh: true,
         }
     }
 }
@@ -188,9 +245,26 @@ impl<B: TnuaBasis> DynamicBasis for BoxableBasis<B> {
     fn violate_coyote_time(&mut self) {
         self.input.violate_coyote_time(&mut self.state)
     }
+
+    fn reset_state(&mut self) {
+        self.state = Default::default();
+    }
+
+    fn airborne_duration(&self) -> Option<Float> {
+        self.input.airborne_duration(&self.state)
+    }
+
+    fn float_offset(&self) -> Option<Float> {
+        self.input.float_offset(&self.state)
+    }
+
+    fn running_velocity(&self) -> Option<Vector3> {
+        self.input.running_velocity(&self.state)
+    }
 }
 
 /// Various data passed to [`TnuaAction::apply`].
+#[non_exhaustive]
 pub struct TnuaActionContext<'a> {
     /// The duration of the current frame.
     pub frame_duration: Float,
@@ -201,6 +275,19 @@ pub struct TnuaActionContext<'a> {
     /// A sensor that tracks the distance of the character's center from the ground.
     pub proximity_sensor: &'a TnuaProximitySensor,
 
+    /// The properties of the surface the proximity sensor detected, if it has
+    /// [`TnuaSurfaceProperties`].
+    pub surface_properties: Option<&'a TnuaSurfaceProperties>,
+
+    /// The velocity accumulated by [`TnuaExternalForce`](crate::TnuaExternalForce), which bases
+    /// should add to the velocity they try to maintain instead of cancelling it.
+    pub external_velocity: Vector3,
+
+    /// The root motion of the current frame, if the character has
+    /// [`TnuaRootMotion`](crate::TnuaRootMotion). Bases that support root motion should follow it
+    /// instead of their own desired velocity.
+    pub root_motion: Option<TnuaRootMotionVelocity>,
+
     /// The direction considered as "up".
     pub up_direction: Dir3,
 
@@ -226,17 +313,22 @@ impl<'a> TnuaActionContext<'a> {
             frame_duration: self.frame_duration,
             tracker: self.tracker,
             proximity_sensor: self.proximity_sensor,
+            surface_properties: self.surface_properties,
+            external_velocity: self.external_velocity,
+            root_motion: self.root_motion,
             up_direction: self.up_direction,
         }
     }
 
     pub fn frame_duration_as_duration(&self) -> Duration {
-        Duration::from_secs_f64(self.frame_duration.into())
+        #[allow(clippy::unnecessary_cast)]
+        Duration::from_secs_f64(self.frame_duration as f64)
     }
 }
 
 /// Input for [`TnuaAction::apply`] that informs it about the long-term feeding of the input.
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum TnuaActionLifecycleStatus {
     /// There was no action in the previous frame
     Initiated,
@@ -313,6 +405,7 @@ impl TnuaActionLifecycleStatus {
 /// Note that an action may continue (probably with different state) after no longer being fed, or
 /// stopped while still being fed. It's up to the action, and it should be responsible with it.
 #[derive(PartialEq, Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum TnuaActionLifecycleDirective {
     /// The action should continue in the next frame.
     StillActive,
@@ -341,6 +434,7 @@ pub enum TnuaActionLifecycleDirective {
 
 /// A decision by [`TnuaAction::initiation_decision`] that determines if the action can start.
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum TnuaActionInitiationDirective {
     /// The action will not start as long as the input is still fed. In order to start it, the
     /// input must be released for at least one frame and then start being fed again.
@@ -428,6 +522,16 @@ pub trait TnuaAction: 'static + Send + Sync {
         ctx: TnuaActionContext,
         being_fed_for: &Stopwatch,
     ) -> TnuaActionInitiationDirective;
+
+    /// The name of the stage the action is currently at, or `None` if the action does not have
+    /// stages.
+    ///
+    /// This is a query method for game code that needs to know what the action is doing (e.g. for
+    /// animation) without downcasting the state. The names should match the variants of the
+    /// state.
+    fn stage_name(&self, _state: &Self::State) -> Option<&'static str> {
+        None
+    }
 }
 
 pub trait DynamicAction: Send + Sync + Any + 'static {
@@ -446,11 +550,15 @@ pub trait DynamicAction: Send + Sync + Any + 'static {
         being_fed_for: &Stopwatch,
     ) -> TnuaActionInitiationDirective;
     fn violates_coyote_time(&self) -> bool;
+    fn stage_name(&self) -> Option<&'static str>;
 }
 
 pub(crate) struct BoxableAction<A: TnuaAction> {
     pub(crate) input: A,
     pub(crate) state: A::State,
+    /// Set when the input is fed, so that [`TnuaAssists`](crate::TnuaAssists) will only adjust
+    /// each fed input once.
+    pub(crate) input_is_fresh: bool,
 }
 
 impl<A: TnuaAction> BoxableAction<A> {
@@ -458,6 +566,7 @@ impl<A: TnuaAction> BoxableAction<A> {
         Self {
             input: basis,
             state: Default::default(),
+            input_is_fresh: true,
         }
     }
 }
@@ -496,4 +605,8 @@ impl<A: TnuaAction> DynamicAction for BoxableAction<A> {
     fn violates_coyote_time(&self) -> bool {
         A::VIOLATES_COYOTE_TIME
     }
+
+    fn stage_name(&self) -> Option<&'static str> {
+        self.input.stage_name(&self.state)
+    }
 }
@@ -167,6 +167,14 @@ impl TnuaAction for TnuaBuiltinCrouch {
             }
         }
     }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinCrouchState::Sinking => "Sinking",
+            TnuaBuiltinCrouchState::Maintaining => "Maintaining",
+            TnuaBuiltinCrouchState::Rising => "Rising",
+        })
+    }
 }
 
 impl TnuaBuiltinCrouch {
@@ -1,6 +1,7 @@
 use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
 use bevy::prelude::*;
 
+use crate::control_helpers::buffered_initiation_decision;
 use crate::util::rotation_arc_around_axis;
 use crate::{
     prelude::*, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
@@ -74,13 +75,10 @@ impl TnuaAction for TnuaBuiltinDash {
     ) -> crate::TnuaActionInitiationDirective {
         if !self.displacement.is_finite() || self.displacement == Vector3::ZERO {
             TnuaActionInitiationDirective::Reject
-        } else if self.allow_in_air || !ctx.basis.is_airborne() {
-            // Either not airborne, or air jumps are allowed
-            TnuaActionInitiationDirective::Allow
-        } else if (being_fed_for.elapsed().as_secs_f64() as Float) < self.input_buffer_time {
-            TnuaActionInitiationDirective::Delay
         } else {
-            TnuaActionInitiationDirective::Reject
+            // Either not airborne, or air jumps are allowed
+            let can_start = self.allow_in_air || !ctx.basis.is_airborne();
+            buffered_initiation_decision(can_start, being_fed_for, self.input_buffer_time)
         }
     }
 
@@ -176,6 +174,14 @@ impl TnuaAction for TnuaBuiltinDash {
         error!("Tnua could not decide on dash state");
         TnuaActionLifecycleDirective::Finished
     }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinDashState::PreDash => "PreDash",
+            TnuaBuiltinDashState::During { .. } => "During",
+            TnuaBuiltinDashState::Braking { .. } => "Braking",
+        })
+    }
 }
 
 #[derive(Default)]
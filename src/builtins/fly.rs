@@ -0,0 +1,221 @@
+use crate::math::{AdjustPrecision, Float, Quaternion, Vector3};
+use bevy::prelude::*;
+
+use crate::motion_math;
+use crate::util::rotation_arc_around_axis;
+use crate::{TnuaBasis, TnuaBasisContext, TnuaMotor, TnuaVelChange};
+
+/// A [basis](TnuaBasis) for flying and hovering - jetpacks, drones, fairy companions and so on.
+///
+/// The basis cancels the gravity and moves the character in all three dimensions according to
+/// [`desired_velocity`](Self::desired_velocity). When moving, the character tilts toward its
+/// acceleration (up to [`max_tilt`](Self::max_tilt)) like a drone does.
+///
+/// The speed changes are done with (limited) accelerations, so switching between this basis and
+/// [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) at runtime keeps the character's
+/// momentum - e.g. a character that jumps and then activates its jetpack continues with the
+/// velocity of the jump.
+///
+/// When [`altitude_hold`](Self::altitude_hold) is set, the ground proximity sensor is used to keep
+/// the character at that height above the terrain while there is no vertical component to the
+/// desired velocity.
+#[derive(Clone)]
+pub struct TnuaBuiltinFly {
+    /// The direction (in the world space) and speed to fly in, including the vertical component.
+    pub desired_velocity: Vector3,
+
+    /// The direction the character should face. Retains the current facing when `None`.
+    pub desired_forward: Option<Dir3>,
+
+    /// The maximum acceleration for reaching the desired velocity.
+    pub acceleration: Float,
+
+    /// A boost, as a fraction of the difference between the desired velocity and the current
+    /// velocity, applied every frame in addition to the [`acceleration`](Self::acceleration).
+    ///
+    /// Low values make the flight feel floaty, and higher values make the character settle quickly
+    /// when it stops.
+    pub hover_dampening: Float,
+
+    /// The maximum angle, in radians, the character tilts toward its acceleration.
+    ///
+    /// The tilt is proportional to the acceleration, reaching this angle at full
+    /// [`acceleration`](Self::acceleration). Set to `0.0` to keep the character upright.
+    pub max_tilt: Float,
+
+    /// Keep the character at this height above the terrain, measured by the ground proximity
+    /// sensor.
+    ///
+    /// The terrain is sensed up to twice this height. The altitude is only held while the
+    /// [`desired_velocity`](Self::desired_velocity) has no vertical component, and while there is
+    /// terrain in range - otherwise the character just keeps its vertical position.
+    pub altitude_hold: Option<Float>,
+
+    /// The strength of the spring that keeps the character at
+    /// [`altitude_hold`](Self::altitude_hold).
+    pub altitude_hold_strength: Float,
+
+    /// A boost for dampening the vertical motion of the spring that keeps the character at
+    /// [`altitude_hold`](Self::altitude_hold).
+    pub altitude_hold_dampening: Float,
+
+    /// The maximum angular velocity used for reaching the desired tilt.
+    pub tilt_offset_angvel: Float,
+
+    /// The maximum angular acceleration used for reaching `tilt_offset_angvel`.
+    pub tilt_offset_angacl: Float,
+
+    /// The maximum angular velocity used for turning the character when the direction changes.
+    pub turning_angvel: Float,
+}
+
+impl Default for TnuaBuiltinFly {
+    fn default() -> Self {
+        Self {
+            desired_velocity: Vector3::ZERO,
+            desired_forward: None,
+            acceleration: 30.0,
+            hover_dampening: 0.1,
+            max_tilt: 0.3,
+            altitude_hold: None,
+            altitude_hold_strength: 100.0,
+            altitude_hold_dampening: 0.3,
+            tilt_offset_angvel: 5.0,
+            tilt_offset_angacl: 500.0,
+            turning_angvel: 10.0,
+        }
+    }
+}
+
+impl TnuaBasis for TnuaBuiltinFly {
+    const NAME: &'static str = "TnuaBuiltinFly";
+    type State = TnuaBuiltinFlyState;
+
+    fn apply(&self, state: &mut Self::State, ctx: TnuaBasisContext, motor: &mut TnuaMotor) {
+        let up = ctx.up_direction.adjust_precision();
+
+        let velocity = ctx.tracker.velocity;
+        state.effective_velocity = velocity;
+        state.altitude = ctx
+            .proximity_sensor
+            .output
+            .as_ref()
+            .map(|sensor_output| sensor_output.proximity);
+
+        let desired_velocity = self.desired_velocity + ctx.external_velocity;
+        let altitude_offset = match (self.altitude_hold, state.altitude) {
+            (Some(altitude_hold), Some(altitude)) if desired_velocity.dot(up) == 0.0 => {
+                Some(altitude_hold - altitude)
+            }
+            _ => None,
+        };
+        // When holding the altitude, the vertical velocity is left to the spring.
+        let desired_velocity = if altitude_offset.is_some() {
+            desired_velocity.reject_from(up) + velocity.dot(up) * up
+        } else {
+            desired_velocity
+        };
+
+        let mut boost = (desired_velocity - velocity) * self.hover_dampening;
+        let mut acceleration = motion_math::velocity_matching_acceleration(
+            velocity + boost,
+            desired_velocity,
+            self.acceleration,
+            ctx.frame_duration,
+        );
+        let tilt_acceleration = acceleration.reject_from(up);
+        if let Some(altitude_offset) = altitude_offset {
+            acceleration += up
+                * motion_math::float_spring_acceleration(
+                    altitude_offset,
+                    self.altitude_hold_strength,
+                    0.0,
+                );
+            boost += up
+                * motion_math::float_spring_dampening_boost(
+                    velocity.dot(up),
+                    self.altitude_hold_dampening,
+                );
+        }
+        motor.lin = TnuaVelChange {
+            acceleration: acceleration - ctx.tracker.gravity,
+            boost,
+        };
+
+        // Tilt toward the acceleration
+
+        let desired_up = if 0.0 < self.acceleration {
+            let tilt_angle =
+                self.max_tilt * (tilt_acceleration.length() / self.acceleration).min(1.0);
+            up * tilt_angle.cos() + tilt_acceleration.normalize_or_zero() * tilt_angle.sin()
+        } else {
+            up
+        };
+        let tilted_up = ctx.tracker.rotation.mul_vec3(Vector3::Y);
+        let rotation_required_to_fix_tilt = Quaternion::from_rotation_arc(tilted_up, desired_up);
+        let desired_angvel = (rotation_required_to_fix_tilt.xyz() / ctx.frame_duration)
+            .clamp_length_max(self.tilt_offset_angvel);
+        let torque_to_fix_tilt = (desired_angvel - ctx.tracker.angvel)
+            .clamp_length_max(ctx.frame_duration * self.tilt_offset_angacl);
+
+        // Turning
+
+        let rotation_along_up_axis = self.desired_forward.map_or(0.0, |desired_forward| {
+            let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+            rotation_arc_around_axis(
+                ctx.up_direction,
+                current_forward,
+                desired_forward.adjust_precision(),
+            )
+            .unwrap_or(0.0)
+        });
+        let desired_turn_angvel = (rotation_along_up_axis / ctx.frame_duration)
+            .clamp(-self.turning_angvel, self.turning_angvel);
+        let torque_to_turn =
+            desired_turn_angvel - ctx.tracker.angvel.dot(up) - torque_to_fix_tilt.dot(up);
+        motor.ang = TnuaVelChange::boost(torque_to_fix_tilt + torque_to_turn * up);
+    }
+
+    fn proximity_sensor_cast_range(&self, _state: &Self::State) -> Float {
+        self.altitude_hold
+            .map_or(0.0, |altitude_hold| 2.0 * altitude_hold)
+    }
+
+    fn displacement(&self, _state: &Self::State) -> Option<Vector3> {
+        None
+    }
+
+    fn effective_velocity(&self, state: &Self::State) -> Vector3 {
+        state.effective_velocity
+    }
+
+    fn vertical_velocity(&self, _state: &Self::State) -> Float {
+        0.0
+    }
+
+    fn neutralize(&mut self) {
+        self.desired_velocity = Vector3::ZERO;
+        self.desired_forward = None;
+    }
+
+    fn is_airborne(&self, _state: &Self::State) -> bool {
+        true
+    }
+
+    fn violate_coyote_time(&self, _state: &mut Self::State) {}
+}
+
+#[derive(Default, Debug)]
+pub struct TnuaBuiltinFlyState {
+    effective_velocity: Vector3,
+    altitude: Option<Float>,
+}
+
+impl TnuaBuiltinFlyState {
+    /// The height of the character above the terrain, if the ground proximity sensor found any.
+    ///
+    /// The terrain is only sensed when [`altitude_hold`](TnuaBuiltinFly::altitude_hold) is set.
+    pub fn altitude(&self) -> Option<Float> {
+        self.altitude
+    }
+}
@@ -1,10 +1,12 @@
 use crate::math::{AdjustPrecision, Float, Vector3};
 use bevy::prelude::*;
 
+use crate::control_helpers::buffered_initiation_decision;
+use crate::motion_math;
 use crate::util::SegmentedJumpInitialVelocityCalculator;
 use crate::{
-    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
-    TnuaActionLifecycleStatus,
+    TnuaAction, TnuaActionContext, TnuaActionLifecycleDirective, TnuaActionLifecycleStatus,
+    TnuaVelChange,
 };
 
 /// The basic jump [action](TnuaAction).
@@ -17,6 +19,14 @@ use crate::{
 /// being fed, it'll apply extra gravity to shorten the jump. If the game desires fixed height
 /// jumps instead (where the player cannot make lower jumps by tapping the jump button)
 /// [`shorten_extra_gravity`](Self::shorten_extra_gravity) should be set to `0.0`.
+///
+/// For jumps that go higher the longer the button is held (like in Super Mario), set
+/// [`hold_extension_duration`](Self::hold_extension_duration). The jump will then reach
+/// [`height`](Self::height) even with a short press, and holding the button will keep pushing the
+/// character up for the duration of the extension window.
+///
+/// For analog jump buttons, set [`min_height`](Self::min_height) and feed the pressure as the
+/// [`strength`](Self::strength).
 #[derive(Clone)]
 pub struct TnuaBuiltinJump {
     /// The height the character will jump to.
@@ -31,6 +41,20 @@ pub struct TnuaBuiltinJump {
     /// and the basis' [`displacement`](crate::TnuaBasis::displacement).
     pub height: Float,
 
+    /// The height the character will jump to when [`strength`](Self::strength) is `0.0`.
+    ///
+    /// Irrelevant if [`strength`](Self::strength) is `1.0` (the default).
+    pub min_height: Float,
+
+    /// How strong the jump is, from `0.0` (a jump to [`min_height`](Self::min_height)) to `1.0` (a
+    /// jump to [`height`](Self::height)). Values in between are interpolated linearly between the
+    /// two heights - see [`effective_height`](Self::effective_height).
+    ///
+    /// This is for analog inputs (like gamepad triggers) and for AI that wants hops of specific
+    /// heights. Only the strength the action is fed with when the jump starts matters - the
+    /// takeoff velocity is calculated once, for the effective height at that moment.
+    pub strength: Float,
+
     /// Allow this action to start even if the character is not touching ground nor in coyote time.
     pub allow_in_air: bool,
 
@@ -42,6 +66,17 @@ pub struct TnuaBuiltinJump {
     /// **NOTE**: This force will be added to the normal gravity.
     pub upslope_extra_gravity: Float,
 
+    /// Cap the takeoff upward velocity to the velocity needed for reaching
+    /// [`height`](Self::height), even when the character is running up a slope.
+    ///
+    /// By default the jump boost is added on top of the upward velocity the character already has
+    /// from the slope, and [`upslope_extra_gravity`](Self::upslope_extra_gravity) brakes it
+    /// afterwards. With fast characters on steep slopes this combination can still send the
+    /// character far above its jump height. When this is set, the slope-derived upward velocity is
+    /// counted as part of the jump velocity instead, so the total takeoff vertical speed never
+    /// exceeds what the configured height requires.
+    pub normalize_slope_takeoff: bool,
+
     /// Extra gravity for fast takeoff.
     ///
     /// Without this, jumps feel painfully slow. Adding this will apply extra gravity until the
@@ -61,6 +96,12 @@ pub struct TnuaBuiltinJump {
     /// **NOTE**: This force will be added to the normal gravity.
     pub fall_extra_gravity: Float,
 
+    /// The maximum speed of falling after reaching the top of the jump.
+    ///
+    /// The downward velocity is clamped to it, after the gravity and the
+    /// [`fall_extra_gravity`](Self::fall_extra_gravity).
+    pub max_fall_speed: Float,
+
     /// Extra gravity for shortening a jump when the player releases the jump button.
     ///
     /// **NOTE**: This force will be added to the normal gravity.
@@ -99,22 +140,90 @@ pub struct TnuaBuiltinJump {
     /// possible (typically when a character is still in the air and about the land) and the jump
     /// action would still get registered and be executed once the jump is possible.
     pub input_buffer_time: Float,
+
+    /// A point, in world coordinates, the jump should land on - e.g. the top of a ledge the
+    /// character jumps toward.
+    ///
+    /// When set, and the character jumps toward it (within
+    /// [`landing_assist_cone`](Self::landing_assist_cone)), the horizontal takeoff velocity is
+    /// nudged (by no more than
+    /// [`landing_assist_max_velocity_change`](Self::landing_assist_max_velocity_change)) so that a
+    /// full height jump will land the center of the character on that point. This is a subtle
+    /// aim assist - the air control of the basis can still steer the character away from it
+    /// (unless [`landing_correction_acceleration`](Self::landing_correction_acceleration) is set).
+    ///
+    /// Like [`height`](Self::height), this point refers to the center of the character, so to land
+    /// on a point on the ground the float height should be added to it.
+    pub landing_target: Option<Vector3>,
+
+    /// The maximum angle, in radians, between the horizontal direction the character moves in and
+    /// the direction of [`landing_target`](Self::landing_target), for the assist to kick in.
+    pub landing_assist_cone: Float,
+
+    /// The maximum change to the horizontal takeoff velocity for landing on
+    /// [`landing_target`](Self::landing_target).
+    pub landing_assist_max_velocity_change: Float,
+
+    /// The maximum horizontal acceleration for steering the character, while it is in the air,
+    /// so that it lands on [`landing_target`](Self::landing_target).
+    ///
+    /// Unlike the takeoff nudge, this correction keeps working during the entire flight, which
+    /// makes jumps of AI characters (e.g. over navmesh links) reliable. The time to land is
+    /// re-estimated every frame from the current vertical velocity, and the correction is only
+    /// applied while the target is still reachable with this acceleration. While it is applied,
+    /// it replaces the air control of the basis.
+    ///
+    /// Set to `0.0` (the default) to disable the correction.
+    pub landing_correction_acceleration: Float,
+
+    /// A duration, in seconds, during which holding the jump button keeps pushing the character
+    /// up after the initial jump boost.
+    ///
+    /// The window starts once the character reaches the takeoff velocity needed for
+    /// [`height`](Self::height), and ends early if the action stops being fed or the character
+    /// starts falling. Set to `0.0` (the default) to disable the extension.
+    pub hold_extension_duration: Float,
+
+    /// The upward acceleration applied during the
+    /// [`hold_extension_duration`](Self::hold_extension_duration) window.
+    ///
+    /// **NOTE**: This acceleration works against the normal gravity, and replaces
+    /// [`takeoff_extra_gravity`](Self::takeoff_extra_gravity) while the extension is in effect.
+    pub hold_extension_acceleration: Float,
+
+    /// Controls the shape of the extension acceleration over its window.
+    ///
+    /// The acceleration is multiplied by `(1 - t) ^ hold_extension_falloff`, where `t` goes from
+    /// `0.0` at the start of the window to `1.0` at its end. `0.0` means constant acceleration,
+    /// `1.0` means it fades linearly, and higher values make it fade faster.
+    pub hold_extension_falloff: Float,
 }
 
 impl Default for TnuaBuiltinJump {
     fn default() -> Self {
         Self {
             height: 0.0,
+            min_height: 0.0,
+            strength: 1.0,
             allow_in_air: false,
             upslope_extra_gravity: 30.0,
+            normalize_slope_takeoff: false,
             takeoff_extra_gravity: 30.0,
             takeoff_above_velocity: 2.0,
             fall_extra_gravity: 20.0,
+            max_fall_speed: Float::INFINITY,
             shorten_extra_gravity: 60.0,
             peak_prevention_at_upward_velocity: 1.0,
             peak_prevention_extra_gravity: 20.0,
             reschedule_cooldown: None,
             input_buffer_time: 0.2,
+            landing_target: None,
+            landing_assist_cone: 0.5,
+            landing_assist_max_velocity_change: 2.0,
+            landing_correction_acceleration: 0.0,
+            hold_extension_duration: 0.0,
+            hold_extension_acceleration: 30.0,
+            hold_extension_falloff: 1.0,
         }
     }
 }
@@ -129,14 +238,9 @@ impl TnuaAction for TnuaBuiltinJump {
         ctx: TnuaActionContext,
         being_fed_for: &bevy::time::Stopwatch,
     ) -> crate::basis_action_traits::TnuaActionInitiationDirective {
-        if self.allow_in_air || !ctx.basis.is_airborne() {
-            // Either not airborne, or air jumps are allowed
-            TnuaActionInitiationDirective::Allow
-        } else if (being_fed_for.elapsed().as_secs_f64() as Float) < self.input_buffer_time {
-            TnuaActionInitiationDirective::Delay
-        } else {
-            TnuaActionInitiationDirective::Reject
-        }
+        // Either not airborne, or air jumps are allowed
+        let can_start = self.allow_in_air || !ctx.basis.is_airborne();
+        buffered_initiation_decision(can_start, being_fed_for, self.input_buffer_time)
     }
 
     fn apply(
@@ -149,17 +253,8 @@ impl TnuaAction for TnuaBuiltinJump {
         let up = ctx.up_direction.adjust_precision();
 
         if lifecycle_status.just_started() {
-            let mut calculator = SegmentedJumpInitialVelocityCalculator::new(self.height);
             let gravity = ctx.tracker.gravity.dot(-up);
-            let kinetic_energy = calculator
-                .add_segment(
-                    gravity + self.peak_prevention_extra_gravity,
-                    self.peak_prevention_at_upward_velocity,
-                )
-                .add_segment(gravity, self.takeoff_above_velocity)
-                .add_final_segment(gravity + self.takeoff_extra_gravity)
-                .kinetic_energy()
-                .expect("`add_final_segment` should have covered remaining height");
+            let kinetic_energy = motion_math::jump_kinetic_energy(self, gravity);
             *state = TnuaBuiltinJumpState::StartingJump {
                 desired_energy: kinetic_energy,
             };
@@ -167,9 +262,24 @@ impl TnuaAction for TnuaBuiltinJump {
 
         let effective_velocity = ctx.basis.effective_velocity();
 
+        if matches!(
+            state,
+            TnuaBuiltinJumpState::ExtendingJump { .. }
+                | TnuaBuiltinJumpState::MaintainingJump
+                | TnuaBuiltinJumpState::StoppedMaintainingJump
+                | TnuaBuiltinJumpState::FallSection
+        ) {
+            if let Some(correction) = self.landing_correction(&ctx, effective_velocity) {
+                // Replace the air control of the basis, so that it won't steer away from the
+                // target.
+                motor.lin.acceleration = motor.lin.acceleration.project_onto(up) + correction;
+                motor.lin.boost = motor.lin.boost.project_onto(up);
+            }
+        }
+
         // TODO: Once `std::mem::variant_count` gets stabilized, use that instead. The idea is to
         // allow jumping through multiple states but failing if we get into loop.
-        for _ in 0..7 {
+        for _ in 0..8 {
             return match state {
                 TnuaBuiltinJumpState::NoJump => panic!(),
                 TnuaBuiltinJumpState::StartingJump { desired_energy } => {
@@ -189,9 +299,19 @@ impl TnuaAction for TnuaBuiltinJump {
                             desired_kinetic_energy,
                         );
 
-                    let relative_velocity =
-                        effective_velocity.dot(up) - ctx.basis.vertical_velocity().max(0.0);
+                    let relative_velocity = if self.normalize_slope_takeoff {
+                        effective_velocity.dot(up)
+                    } else {
+                        effective_velocity.dot(up) - ctx.basis.vertical_velocity().max(0.0)
+                    };
 
+                    if let Some(landing_assist_boost) =
+                        self.landing_assist_boost(&ctx, extra_height, effective_velocity)
+                    {
+                        // Replace the horizontal acceleration of the basis, so that it won't add
+                        // to the nudge.
+                        motor.lin = TnuaVelChange::boost(landing_assist_boost);
+                    }
                     motor.lin.cancel_on_axis(up);
                     motor.lin.boost += (desired_upward_velocity - relative_velocity) * up;
                     if 0.0 <= extra_height {
@@ -225,7 +345,11 @@ impl TnuaAction for TnuaBuiltinJump {
                             desired_kinetic_energy,
                         );
                     if relative_velocity <= desired_upward_velocity {
-                        *state = TnuaBuiltinJumpState::MaintainingJump;
+                        *state = if 0.0 < self.hold_extension_duration {
+                            TnuaBuiltinJumpState::ExtendingJump { elapsed: 0.0 }
+                        } else {
+                            TnuaBuiltinJumpState::MaintainingJump
+                        };
                         continue;
                     } else {
                         let mut extra_gravity = self.upslope_extra_gravity;
@@ -237,6 +361,27 @@ impl TnuaAction for TnuaBuiltinJump {
                         self.directive_simple_or_reschedule(lifecycle_status)
                     }
                 }
+                TnuaBuiltinJumpState::ExtendingJump { elapsed } => {
+                    if !lifecycle_status.is_active() {
+                        *state = TnuaBuiltinJumpState::StoppedMaintainingJump;
+                        continue;
+                    }
+                    if up.dot(effective_velocity) <= 0.0 {
+                        *state = TnuaBuiltinJumpState::FallSection;
+                        continue;
+                    }
+                    if self.hold_extension_duration <= *elapsed {
+                        *state = TnuaBuiltinJumpState::MaintainingJump;
+                        continue;
+                    }
+                    let progress = *elapsed / self.hold_extension_duration;
+                    *elapsed += ctx.frame_duration;
+                    motor.lin.cancel_on_axis(up);
+                    motor.lin.acceleration += self.hold_extension_acceleration
+                        * (1.0 - progress).powf(self.hold_extension_falloff)
+                        * up;
+                    TnuaActionLifecycleDirective::StillActive
+                }
                 TnuaBuiltinJumpState::MaintainingJump => {
                     let relevant_upward_velocity = effective_velocity.dot(up);
                     if relevant_upward_velocity <= 0.0 {
@@ -304,6 +449,13 @@ impl TnuaAction for TnuaBuiltinJump {
                     } else {
                         motor.lin.cancel_on_axis(up);
                         motor.lin.acceleration -= self.fall_extra_gravity * up;
+                        motor.lin.boost += up
+                            * motion_math::fall_speed_limit_boost(
+                                ctx.tracker.velocity.dot(up),
+                                (ctx.tracker.gravity + motor.lin.acceleration).dot(up),
+                                self.max_fall_speed,
+                                ctx.frame_duration,
+                            );
                         TnuaActionLifecycleDirective::StillActive
                     }
                 }
@@ -312,9 +464,97 @@ impl TnuaAction for TnuaBuiltinJump {
         error!("Tnua could not decide on jump state");
         TnuaActionLifecycleDirective::Finished
     }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinJumpState::NoJump => "NoJump",
+            TnuaBuiltinJumpState::StartingJump { .. } => "StartingJump",
+            TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => "SlowDownTooFastSlopeJump",
+            TnuaBuiltinJumpState::ExtendingJump { .. } => "ExtendingJump",
+            TnuaBuiltinJumpState::MaintainingJump => "MaintainingJump",
+            TnuaBuiltinJumpState::StoppedMaintainingJump => "StoppedMaintainingJump",
+            TnuaBuiltinJumpState::FallSection => "FallSection",
+        })
+    }
 }
 
 impl TnuaBuiltinJump {
+    /// The height the character will jump to, according to the [`strength`](Self::strength).
+    ///
+    /// ```
+    /// # use bevy_tnua::builtins::TnuaBuiltinJump;
+    /// let jump = TnuaBuiltinJump {
+    ///     height: 4.0,
+    ///     min_height: 1.0,
+    ///     strength: 0.5,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(jump.effective_height(), 2.5);
+    /// ```
+    pub fn effective_height(&self) -> Float {
+        let strength = self.strength.clamp(0.0, 1.0);
+        self.min_height + strength * (self.height - self.min_height)
+    }
+
+    /// The horizontal boost for landing on the [`landing_target`](Self::landing_target), if the
+    /// assist applies.
+    fn landing_assist_boost(
+        &self,
+        ctx: &TnuaActionContext,
+        extra_height: Float,
+        effective_velocity: Vector3,
+    ) -> Option<Vector3> {
+        let target = self.landing_target?;
+        let up = ctx.up_direction.adjust_precision();
+        let offset = target - ctx.tracker.translation;
+        let gravity = ctx.tracker.gravity.dot(-up);
+        let time_to_land =
+            motion_math::jump_time_to_land_at(self, gravity, offset.dot(up) + extra_height)?;
+        let horizontal_offset = offset.reject_from(up);
+        let horizontal_velocity = effective_velocity.reject_from(up);
+        if horizontal_velocity == Vector3::ZERO
+            || self.landing_assist_cone < horizontal_velocity.angle_between(horizontal_offset)
+        {
+            return None;
+        }
+        let boost = horizontal_offset / time_to_land - horizontal_velocity;
+        (boost.length() <= self.landing_assist_max_velocity_change).then_some(boost)
+    }
+
+    /// The horizontal acceleration for steering a character in the air toward the
+    /// [`landing_target`](Self::landing_target), if it can still reach it.
+    fn landing_correction(
+        &self,
+        ctx: &TnuaActionContext,
+        effective_velocity: Vector3,
+    ) -> Option<Vector3> {
+        if self.landing_correction_acceleration <= 0.0 {
+            return None;
+        }
+        let target = self.landing_target?;
+        let up = ctx.up_direction.adjust_precision();
+        let offset = target - ctx.tracker.translation;
+        let upward_velocity = effective_velocity.dot(up);
+        let gravity =
+            ctx.tracker.gravity.dot(-up) + motion_math::jump_extra_gravity(self, upward_velocity);
+        let time_to_land =
+            motion_math::ballistic_descent_time(offset.dot(up), upward_velocity, gravity)?;
+        if time_to_land <= 0.0 {
+            return None;
+        }
+        let velocity_change =
+            offset.reject_from(up) / time_to_land - effective_velocity.reject_from(up);
+        // Accelerating during the flight only gets half the displacement of changing the
+        // velocity at once.
+        if 0.5 * self.landing_correction_acceleration * time_to_land < velocity_change.length() {
+            return None;
+        }
+        Some(
+            (velocity_change / ctx.frame_duration)
+                .clamp_length_max(self.landing_correction_acceleration),
+        )
+    }
+
     fn finish_or_reschedule(&self) -> TnuaActionLifecycleDirective {
         if let Some(cooldown) = self.reschedule_cooldown {
             TnuaActionLifecycleDirective::Reschedule {
@@ -355,6 +595,13 @@ pub enum TnuaBuiltinJumpState {
         desired_energy: Float,
         zero_potential_energy_at: Vector3,
     },
+    /// The jump button is held during the
+    /// [`hold_extension_duration`](TnuaBuiltinJump::hold_extension_duration) window, and the
+    /// character is pushed up.
+    ExtendingJump {
+        /// The time, in seconds, since the extension window started.
+        elapsed: Float,
+    },
     MaintainingJump,
     StoppedMaintainingJump,
     FallSection,
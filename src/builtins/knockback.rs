@@ -176,6 +176,13 @@ impl TnuaAction for TnuaBuiltinKnockback {
     ) -> TnuaActionInitiationDirective {
         TnuaActionInitiationDirective::Allow
     }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinKnockbackState::Shove => "Shove",
+            TnuaBuiltinKnockbackState::Pushback { .. } => "Pushback",
+        })
+    }
 }
 
 #[derive(Default)]
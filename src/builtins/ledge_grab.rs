@@ -0,0 +1,338 @@
+use crate::math::{AdjustPrecision, Float, Vector3};
+use bevy::prelude::*;
+
+use crate::util::rotation_arc_around_axis;
+use crate::{
+    prelude::*, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor, TnuaVelChange,
+};
+
+/// A ledge the character can grab with [`TnuaBuiltinLedgeGrab`].
+///
+/// Can be detected automatically with
+/// [`TnuaLedgeDetector`](crate::control_helpers::TnuaLedgeDetector).
+#[derive(Clone, Copy, Debug)]
+pub struct TnuaLedge {
+    /// A point on the top edge of the wall, in world coordinates.
+    pub point: Vector3,
+
+    /// The normal of the wall below the ledge, pointing away from the wall (toward the side the
+    /// character hangs on).
+    pub wall_normal: Dir3,
+
+    /// The entity of the wall, if known.
+    ///
+    /// Used for telling ledges apart for
+    /// [`cooling_down_ledge`](TnuaBuiltinLedgeGrab::cooling_down_ledge).
+    pub entity: Option<Entity>,
+}
+
+/// An [action](TnuaAction) for grabbing a ledge and hanging from it.
+///
+/// Once started, the character will move to the hanging position below the ledge and stay there,
+/// facing the wall and ignoring gravity. While hanging, the character can:
+///
+/// * Climb up to the top of the ledge, by setting [`climb`](Self::climb). Once the climb starts,
+///   it will go on until the character stands on the ledge, even if the action is no longer fed.
+/// * Drop from the ledge, by setting [`drop`](Self::drop) or by no longer feeding the action.
+/// * Jump off the ledge, away from the wall, by setting [`jump_off`](Self::jump_off).
+///
+/// After dropping or jumping off, the action will not grab again until it stops being fed for at
+/// least one frame.
+///
+/// The character can only hang for [`max_hang_duration`](Self::max_hang_duration). When it runs
+/// out, the character drops and the action is
+/// [rescheduled](crate::TnuaControllerEvent::ActionRescheduled) - so it can grab other ledges while
+/// still being fed. The ledge it dropped from should be passed as
+/// [`cooling_down_ledge`](Self::cooling_down_ledge) for
+/// [`regrab_cooldown`](Self::regrab_cooldown) seconds, which
+/// [`TnuaLedgeDetector`](crate::control_helpers::TnuaLedgeDetector) keeps track of.
+#[derive(Clone)]
+pub struct TnuaBuiltinLedgeGrab {
+    /// The ledge to grab.
+    ///
+    /// The action will not start if this is `None`. This input parameter is cached when the action
+    /// starts - which means that it can be set to `None` after that (which is convenient when the
+    /// character is climbing and the ledge is no longer detected)
+    pub ledge: Option<TnuaLedge>,
+
+    /// Start climbing up to the top of the ledge.
+    pub climb: bool,
+
+    /// Let go of the ledge and fall down.
+    pub drop: bool,
+
+    /// Let go of the ledge and jump away from the wall.
+    pub jump_off: bool,
+
+    /// How far below the ledge point the center of the character is while hanging.
+    pub hang_depth: Float,
+
+    /// How far from the wall the center of the character is while hanging.
+    pub hang_distance: Float,
+
+    /// How far above the ledge point the center of the character would be when it stands on the
+    /// ledge.
+    ///
+    /// Should typically be the float height of the walk basis.
+    pub climb_height: Float,
+
+    /// How far past the edge - into the top of the ledge - the character will move when it
+    /// finishes climbing.
+    pub climb_forward: Float,
+
+    /// The maximum distance between the character and its hanging position for the action to
+    /// start.
+    pub grab_range: Float,
+
+    /// The speed the character moves in when moving to the hanging position or when climbing.
+    pub speed: Float,
+
+    /// The maximum acceleration when moving to the hanging position or when climbing.
+    pub acceleration: Float,
+
+    /// The upward speed of the character when jumping off the ledge.
+    pub jump_off_upward_speed: Float,
+
+    /// The speed, away from the wall, of the character when jumping off the ledge.
+    pub jump_off_outward_speed: Float,
+
+    /// The maximal time, in seconds, the character can hang from the ledge before it drops.
+    ///
+    /// Climbing or jumping off close to the end of this duration is still possible.
+    pub max_hang_duration: Float,
+
+    /// The time, in seconds, after dropping because of
+    /// [`max_hang_duration`](Self::max_hang_duration) before the character can grab the same ledge
+    /// again.
+    ///
+    /// This is not enforced by the action itself - it is the duration [`TnuaLedgeDetector`] keeps
+    /// reporting the ledge as [`cooling_down_ledge`](Self::cooling_down_ledge) for.
+    ///
+    /// [`TnuaLedgeDetector`]: crate::control_helpers::TnuaLedgeDetector
+    pub regrab_cooldown: Float,
+
+    /// The entity of a ledge the action will not grab.
+    ///
+    /// Should be set to the ledge the character has recently dropped from after running out of
+    /// [`max_hang_duration`](Self::max_hang_duration), which can be taken from
+    /// [`TnuaLedgeDetector::cooling_down_ledge`]. Ledges without an
+    /// [`entity`](TnuaLedge::entity) are not affected by this.
+    ///
+    /// [`TnuaLedgeDetector::cooling_down_ledge`]:
+    ///     crate::control_helpers::TnuaLedgeDetector::cooling_down_ledge
+    pub cooling_down_ledge: Option<Entity>,
+}
+
+impl Default for TnuaBuiltinLedgeGrab {
+    fn default() -> Self {
+        Self {
+            ledge: None,
+            climb: false,
+            drop: false,
+            jump_off: false,
+            hang_depth: 1.0,
+            hang_distance: 0.6,
+            climb_height: 2.0,
+            climb_forward: 0.6,
+            grab_range: 1.0,
+            speed: 8.0,
+            acceleration: 80.0,
+            jump_off_upward_speed: 10.0,
+            jump_off_outward_speed: 6.0,
+            max_hang_duration: Float::INFINITY,
+            regrab_cooldown: 1.0,
+            cooling_down_ledge: None,
+        }
+    }
+}
+
+impl TnuaBuiltinLedgeGrab {
+    fn hang_position(&self, ledge: &TnuaLedge, up: Vector3) -> Vector3 {
+        ledge.point - self.hang_depth * up
+            + self.hang_distance * ledge.wall_normal.adjust_precision()
+    }
+
+    /// Returns `true` if the character is close enough to the target to reach it this frame.
+    fn move_towards(
+        &self,
+        target: Vector3,
+        ctx: &TnuaActionContext,
+        motor: &mut TnuaMotor,
+    ) -> bool {
+        let offset = target - ctx.tracker.translation;
+        let distance = offset.length();
+        let reached = distance <= self.speed * ctx.frame_duration;
+
+        let desired_velocity = if reached {
+            offset / ctx.frame_duration
+        } else {
+            // Slow down near the target, so that the acceleration limit won't make us overshoot it.
+            let speed = self.speed.min((2.0 * self.acceleration * distance).sqrt());
+            offset * (speed / distance)
+        };
+
+        motor.lin = TnuaVelChange::acceleration(-ctx.tracker.gravity);
+        motor.lin.boost = (desired_velocity - ctx.tracker.velocity)
+            .clamp_length_max(ctx.frame_duration * self.acceleration);
+        reached
+    }
+
+    fn face_wall(&self, ledge: &TnuaLedge, ctx: &TnuaActionContext, motor: &mut TnuaMotor) {
+        let up = ctx.up_direction.adjust_precision();
+        let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+        let rotation_along_up_axis = rotation_arc_around_axis(
+            ctx.up_direction,
+            current_forward,
+            -ledge.wall_normal.adjust_precision(),
+        )
+        .unwrap_or(0.0);
+        let desired_angvel = rotation_along_up_axis / ctx.frame_duration;
+        let existing_angvel = ctx.tracker.angvel.dot(up);
+        let torque_to_turn = desired_angvel - existing_angvel;
+        motor.ang.cancel_on_axis(up);
+        motor.ang += TnuaVelChange::boost(torque_to_turn * up);
+    }
+}
+
+impl TnuaAction for TnuaBuiltinLedgeGrab {
+    const NAME: &'static str = "TnuaBuiltinLedgeGrab";
+    type State = TnuaBuiltinLedgeGrabState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: crate::TnuaActionContext,
+        _being_fed_for: &bevy::time::Stopwatch,
+    ) -> crate::TnuaActionInitiationDirective {
+        let Some(ledge) = self.ledge.as_ref() else {
+            return TnuaActionInitiationDirective::Reject;
+        };
+        if !ledge.point.is_finite() || !ctx.basis.is_airborne() {
+            return TnuaActionInitiationDirective::Reject;
+        }
+        if ledge.entity.is_some() && ledge.entity == self.cooling_down_ledge {
+            // Another ledge may get detected while the action is still fed.
+            return TnuaActionInitiationDirective::Delay;
+        }
+        let hang_position = self.hang_position(ledge, ctx.up_direction.adjust_precision());
+        if ctx.tracker.translation.distance(hang_position) <= self.grab_range {
+            TnuaActionInitiationDirective::Allow
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        let up = ctx.up_direction.adjust_precision();
+
+        // TODO: Once `std::mem::variant_count` gets stabilized, use that instead.
+        for _ in 0..5 {
+            return match state {
+                TnuaBuiltinLedgeGrabState::PreGrab => {
+                    let Some(ledge) = self.ledge else {
+                        // Probably unneeded because of the `initiation_decision`, but still
+                        return TnuaActionLifecycleDirective::Finished;
+                    };
+                    *state = TnuaBuiltinLedgeGrabState::Hanging {
+                        ledge,
+                        hang_time: 0.0,
+                    };
+                    continue;
+                }
+                TnuaBuiltinLedgeGrabState::Hanging { ledge, hang_time } => {
+                    let ledge = *ledge;
+                    if !lifecycle_status.is_active() || self.drop {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+                    *hang_time += ctx.frame_duration;
+                    if self.jump_off {
+                        motor.lin = TnuaVelChange::boost(
+                            self.jump_off_upward_speed * up
+                                + self.jump_off_outward_speed
+                                    * ledge.wall_normal.adjust_precision()
+                                - ctx.tracker.velocity,
+                        );
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+                    if self.climb {
+                        *state = TnuaBuiltinLedgeGrabState::ClimbingUp { ledge };
+                        continue;
+                    }
+                    if self.max_hang_duration <= *hang_time {
+                        // Let go of the ledge, but keep the action for one more frame so that the
+                        // ledge detector can see which ledge it was.
+                        *state = TnuaBuiltinLedgeGrabState::TimedOut { ledge };
+                        return TnuaActionLifecycleDirective::StillActive;
+                    }
+                    self.move_towards(self.hang_position(&ledge, up), &ctx, motor);
+                    self.face_wall(&ledge, &ctx, motor);
+                    TnuaActionLifecycleDirective::StillActive
+                }
+                TnuaBuiltinLedgeGrabState::TimedOut { .. } => {
+                    TnuaActionLifecycleDirective::Reschedule { after_seconds: 0.0 }
+                }
+                TnuaBuiltinLedgeGrabState::ClimbingUp { ledge } => {
+                    let ledge = *ledge;
+                    let target = ledge.point
+                        + self.climb_height * up
+                        + self.hang_distance * ledge.wall_normal.adjust_precision();
+                    if self.move_towards(target, &ctx, motor) {
+                        *state = TnuaBuiltinLedgeGrabState::ClimbingOver { ledge };
+                    }
+                    self.face_wall(&ledge, &ctx, motor);
+                    TnuaActionLifecycleDirective::StillActive
+                }
+                TnuaBuiltinLedgeGrabState::ClimbingOver { ledge } => {
+                    let ledge = *ledge;
+                    let target = ledge.point + self.climb_height * up
+                        - self.climb_forward * ledge.wall_normal.adjust_precision();
+                    self.face_wall(&ledge, &ctx, motor);
+                    if self.move_towards(target, &ctx, motor) {
+                        TnuaActionLifecycleDirective::Finished
+                    } else {
+                        TnuaActionLifecycleDirective::StillActive
+                    }
+                }
+            };
+        }
+        error!("Tnua could not decide on ledge grab state");
+        TnuaActionLifecycleDirective::Finished
+    }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinLedgeGrabState::PreGrab => "PreGrab",
+            TnuaBuiltinLedgeGrabState::Hanging { .. } => "Hanging",
+            TnuaBuiltinLedgeGrabState::TimedOut { .. } => "TimedOut",
+            TnuaBuiltinLedgeGrabState::ClimbingUp { .. } => "ClimbingUp",
+            TnuaBuiltinLedgeGrabState::ClimbingOver { .. } => "ClimbingOver",
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub enum TnuaBuiltinLedgeGrabState {
+    /// The action has just started and did not cache the ledge yet.
+    #[default]
+    PreGrab,
+    /// Moving to the hanging position, or already hanging there.
+    Hanging {
+        ledge: TnuaLedge,
+        /// The time, in seconds, since grabbing the ledge.
+        hang_time: Float,
+    },
+    /// Ran out of [`max_hang_duration`](TnuaBuiltinLedgeGrab::max_hang_duration) and let go of
+    /// the ledge. The action will be rescheduled in the next frame.
+    TimedOut { ledge: TnuaLedge },
+    /// Moving up, alongside the wall, to above the ledge.
+    ClimbingUp { ledge: TnuaLedge },
+    /// Moving from above the edge to the top of the ledge.
+    ClimbingOver { ledge: TnuaLedge },
+}
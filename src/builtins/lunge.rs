@@ -0,0 +1,235 @@
+use crate::math::{AdjustPrecision, Float, Vector3};
+use bevy::prelude::*;
+
+use crate::control_helpers::TnuaObstruction;
+use crate::util::rotation_arc_around_axis;
+use crate::{
+    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor,
+};
+
+/// An [action](TnuaAction) for lunging toward a target - e.g. a melee attack on a locked-on enemy.
+///
+/// The character moves horizontally toward [`target`](Self::target), paced to arrive within
+/// [`duration`](Self::duration), while ignoring the horizontal movement of the basis. The lunge
+/// stops when one of these happens:
+///
+/// * The character gets within [`stop_distance`](Self::stop_distance) from the target.
+/// * A wall that faces against the lunge is reported in [`obstruction`](Self::obstruction).
+/// * The duration passes.
+///
+/// After that, the character brakes for [`recovery_duration`](Self::recovery_duration), during
+/// which the [`TnuaBuiltinLungeState`] reports the [outcome](TnuaLungeOutcome) of the lunge. Once
+/// started, the lunge goes on even if the action is no longer fed.
+#[derive(Clone)]
+pub struct TnuaBuiltinLunge {
+    /// The point, in world coordinates, to lunge toward.
+    ///
+    /// This input parameter is not cached, so to follow a target entity feed its position every
+    /// frame.
+    pub target: Vector3,
+
+    /// The time, in seconds, the lunge should take to arrive at the target.
+    pub duration: Float,
+
+    /// The lunge arrives when the horizontal distance to the target drops below this.
+    pub stop_distance: Float,
+
+    /// The character will not lunge faster than this, even if it means it won't arrive in time.
+    pub max_speed: Float,
+
+    /// The maximum acceleration for reaching the speed of the lunge.
+    pub acceleration: Float,
+
+    /// The maximum acceleration for braking after the lunge.
+    pub brake_acceleration: Float,
+
+    /// The time, in seconds, the character keeps braking after the lunge stops.
+    pub recovery_duration: Float,
+
+    /// Turn the character to face the target during the lunge.
+    pub face_target: bool,
+
+    /// The wall in front of the character, as reported by
+    /// [`TnuaObstructionDetector`](crate::control_helpers::TnuaObstructionDetector). The lunge
+    /// stops if the wall faces against the direction of the lunge.
+    ///
+    /// While the lunge is going on, the detector casts its probe toward the target instead of in
+    /// the direction of the walk basis.
+    ///
+    /// This input parameter is not cached.
+    pub obstruction: Option<TnuaObstruction>,
+
+    /// Allow this action to start even if the character is not touching ground nor in coyote time.
+    pub allow_in_air: bool,
+}
+
+impl Default for TnuaBuiltinLunge {
+    fn default() -> Self {
+        Self {
+            target: Vector3::ZERO,
+            duration: 0.3,
+            stop_distance: 1.0,
+            max_speed: 20.0,
+            acceleration: 200.0,
+            brake_acceleration: 200.0,
+            recovery_duration: 0.2,
+            face_target: true,
+            obstruction: None,
+            allow_in_air: false,
+        }
+    }
+}
+
+impl TnuaAction for TnuaBuiltinLunge {
+    const NAME: &'static str = "TnuaBuiltinLunge";
+    type State = TnuaBuiltinLungeState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        _being_fed_for: &bevy::time::Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        if !self.target.is_finite() {
+            TnuaActionInitiationDirective::Reject
+        } else if self.allow_in_air || !ctx.basis.is_airborne() {
+            TnuaActionInitiationDirective::Allow
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        let up = ctx.up_direction.adjust_precision();
+        let offset = (self.target - ctx.tracker.translation).reject_from(up);
+        let distance = offset.length();
+        let horizontal_velocity = ctx.basis.effective_velocity().reject_from(up);
+
+        // Replace the horizontal movement of the basis, but keep its vertical movement (the float
+        // spring)
+        let set_horizontal_velocity =
+            |motor: &mut TnuaMotor, desired_velocity: Vector3, acceleration: Float| {
+                motor.lin.acceleration = motor.lin.acceleration.project_onto_normalized(up);
+                motor.lin.boost = motor.lin.boost.project_onto_normalized(up)
+                    + (desired_velocity - horizontal_velocity)
+                        .clamp_length_max(ctx.frame_duration * acceleration);
+            };
+
+        // TODO: Once `std::mem::variant_count` gets stabilized, use that instead.
+        for _ in 0..2 {
+            return match state {
+                TnuaBuiltinLungeState::Lunging { elapsed } => {
+                    *elapsed += ctx.frame_duration;
+                    let outcome = if distance <= self.stop_distance {
+                        Some(TnuaLungeOutcome::Arrived)
+                    } else if self.obstruction.is_some_and(|obstruction| {
+                        obstruction.normal.adjust_precision().dot(offset) < 0.0
+                    }) {
+                        Some(TnuaLungeOutcome::Obstructed)
+                    } else if self.duration <= *elapsed {
+                        Some(TnuaLungeOutcome::TimedOut)
+                    } else {
+                        None
+                    };
+                    if let Some(outcome) = outcome {
+                        *state = TnuaBuiltinLungeState::Recovering {
+                            outcome,
+                            elapsed: 0.0,
+                        };
+                        continue;
+                    }
+
+                    let remaining_time = (self.duration - *elapsed).max(ctx.frame_duration);
+                    let speed =
+                        ((distance - self.stop_distance) / remaining_time).min(self.max_speed);
+                    set_horizontal_velocity(motor, offset * (speed / distance), self.acceleration);
+
+                    if self.face_target {
+                        let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+                        let rotation_along_up_axis =
+                            rotation_arc_around_axis(ctx.up_direction, current_forward, offset)
+                                .unwrap_or(0.0);
+                        let desired_angvel = rotation_along_up_axis / ctx.frame_duration;
+                        let existing_angvel = ctx.tracker.angvel.dot(up);
+                        motor.ang.cancel_on_axis(up);
+                        motor.ang.boost += (desired_angvel - existing_angvel) * up;
+                    }
+
+                    TnuaActionLifecycleDirective::StillActive
+                }
+                TnuaBuiltinLungeState::Recovering { elapsed, .. } => {
+                    *elapsed += ctx.frame_duration;
+                    if self.recovery_duration <= *elapsed {
+                        TnuaActionLifecycleDirective::Finished
+                    } else {
+                        set_horizontal_velocity(motor, Vector3::ZERO, self.brake_acceleration);
+                        TnuaActionLifecycleDirective::StillActive
+                    }
+                }
+            };
+        }
+        error!("Tnua could not decide on lunge state");
+        TnuaActionLifecycleDirective::Finished
+    }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinLungeState::Lunging { .. } => "Lunging",
+            TnuaBuiltinLungeState::Recovering { .. } => "Recovering",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum TnuaBuiltinLungeState {
+    /// The character is moving toward the target.
+    Lunging {
+        /// The time, in seconds, since the lunge started.
+        elapsed: Float,
+    },
+    /// The lunge has stopped, and the character is braking.
+    Recovering {
+        outcome: TnuaLungeOutcome,
+        /// The time, in seconds, since the lunge stopped.
+        elapsed: Float,
+    },
+}
+
+impl Default for TnuaBuiltinLungeState {
+    fn default() -> Self {
+        Self::Lunging { elapsed: 0.0 }
+    }
+}
+
+impl TnuaBuiltinLungeState {
+    /// How the lunge ended, or `None` if it is still going on.
+    pub fn outcome(&self) -> Option<TnuaLungeOutcome> {
+        match self {
+            TnuaBuiltinLungeState::Lunging { .. } => None,
+            TnuaBuiltinLungeState::Recovering { outcome, .. } => Some(*outcome),
+        }
+    }
+}
+
+/// How a [`TnuaBuiltinLunge`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TnuaLungeOutcome {
+    /// The character got within [`stop_distance`](TnuaBuiltinLunge::stop_distance) from the
+    /// target - i.e. a melee attack would hit.
+    Arrived,
+    /// The lunge was stopped by a wall reported in [`obstruction`](TnuaBuiltinLunge::obstruction).
+    Obstructed,
+    /// The [`duration`](TnuaBuiltinLunge::duration) passed before the character arrived.
+    TimedOut,
+}
@@ -1,11 +1,32 @@
 mod crouch;
 mod dash;
+mod fly;
 mod jump;
 mod knockback;
+mod ledge_grab;
+mod lunge;
+mod motion_warp;
+mod slide;
+mod swim;
+mod tether;
+mod top_down;
+mod vault;
 mod walk;
 
 pub use crouch::{TnuaBuiltinCrouch, TnuaBuiltinCrouchState};
 pub use dash::{TnuaBuiltinDash, TnuaBuiltinDashState};
+pub use fly::{TnuaBuiltinFly, TnuaBuiltinFlyState};
 pub use jump::{TnuaBuiltinJump, TnuaBuiltinJumpState};
 pub use knockback::{TnuaBuiltinKnockback, TnuaBuiltinKnockbackState};
-pub use walk::{TnuaBuiltinWalk, TnuaBuiltinWalkState};
+pub use ledge_grab::{TnuaBuiltinLedgeGrab, TnuaBuiltinLedgeGrabState, TnuaLedge};
+pub use lunge::{TnuaBuiltinLunge, TnuaBuiltinLungeState, TnuaLungeOutcome};
+pub use motion_warp::{TnuaBuiltinMotionWarp, TnuaBuiltinMotionWarpState};
+pub use slide::{TnuaBuiltinSlide, TnuaBuiltinSlideState};
+pub use swim::{TnuaBuiltinSwim, TnuaBuiltinSwimState, TnuaWaterSurface};
+pub use tether::{TnuaBuiltinTether, TnuaBuiltinTetherState};
+pub use top_down::{TnuaBuiltinTopDown, TnuaBuiltinTopDownState};
+pub use vault::{TnuaBuiltinVault, TnuaBuiltinVaultState};
+pub use walk::{
+    TnuaAccelerationCurve, TnuaAirControl, TnuaBuiltinWalk, TnuaBuiltinWalkState,
+    TnuaWalkRotationMode,
+};
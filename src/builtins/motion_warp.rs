@@ -0,0 +1,145 @@
+use crate::math::{AdjustPrecision, Float, Vector3};
+
+use crate::motion_math;
+use crate::{
+    prelude::*, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor, TnuaVelChange,
+};
+
+/// An [action](TnuaAction) for correcting a jump (or any other airborne motion) so that the
+/// character will land exactly on a target point.
+///
+/// The action can only start while the character is in the air, and only if the required
+/// correction is within [`max_velocity_change`](Self::max_velocity_change). Once started, it
+/// takes over the rest of the flight - cancelling the action that launched the character (e.g.
+/// [`TnuaBuiltinJump`]) - and gradually changes the velocity of the character until it reaches a
+/// ballistic trajectory that ends at the target. It will go on until the character lands, even if
+/// the action is no longer fed.
+#[derive(Clone)]
+pub struct TnuaBuiltinMotionWarp {
+    /// The point, in world coordinates, where the center of the character should be when it
+    /// lands.
+    ///
+    /// To land on a point on the ground, add the float height of the walk basis to it. This input
+    /// parameter is not cached, so it can follow a moving target.
+    pub target: Vector3,
+
+    /// The maximum difference between the velocity the character needs for landing on the target
+    /// and its current velocity, for the action to start.
+    ///
+    /// This is what limits the warping to jumps that are already close enough to the target.
+    pub max_velocity_change: Float,
+
+    /// The maximum acceleration used for correcting the velocity of the character.
+    pub correction_acceleration: Float,
+
+    /// Extra gravity to apply during the warped flight.
+    ///
+    /// Should typically be the same as the
+    /// [`fall_extra_gravity`](TnuaBuiltinJump::fall_extra_gravity) of the jump it corrects.
+    ///
+    /// **NOTE**: This force will be added to the normal gravity.
+    pub extra_gravity: Float,
+}
+
+impl Default for TnuaBuiltinMotionWarp {
+    fn default() -> Self {
+        Self {
+            target: Vector3::ZERO,
+            max_velocity_change: 5.0,
+            correction_acceleration: 60.0,
+            extra_gravity: 20.0,
+        }
+    }
+}
+
+impl TnuaBuiltinMotionWarp {
+    /// Returns the velocity for landing on the target and the time it will take to get there.
+    fn plan(&self, ctx: &TnuaActionContext) -> Option<(Vector3, Float)> {
+        let up = ctx.up_direction.adjust_precision();
+        let gravity = ctx.tracker.gravity.dot(-up) + self.extra_gravity;
+        if gravity <= 0.0 || !self.target.is_finite() {
+            return None;
+        }
+        let offset = self.target - ctx.tracker.translation;
+        let height_offset = offset.dot(up);
+        let time_to_land = motion_math::ballistic_descent_time(
+            height_offset,
+            ctx.tracker.velocity.dot(up),
+            gravity,
+        )
+        // The target is above the apex - so aim for the lowest trajectory that can reach it.
+        .unwrap_or_else(|| (2.0 * height_offset / gravity).sqrt());
+        if time_to_land <= 0.0 {
+            return None;
+        }
+        Some((
+            motion_math::ballistic_velocity_to_reach(offset, time_to_land, gravity, up),
+            time_to_land,
+        ))
+    }
+}
+
+impl TnuaAction for TnuaBuiltinMotionWarp {
+    const NAME: &'static str = "TnuaBuiltinMotionWarp";
+    type State = TnuaBuiltinMotionWarpState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: crate::TnuaActionContext,
+        _being_fed_for: &bevy::time::Stopwatch,
+    ) -> crate::TnuaActionInitiationDirective {
+        if !ctx.basis.is_airborne() {
+            return TnuaActionInitiationDirective::Reject;
+        }
+        let Some((desired_velocity, _)) = self.plan(&ctx) else {
+            return TnuaActionInitiationDirective::Reject;
+        };
+        if desired_velocity.distance(ctx.tracker.velocity) <= self.max_velocity_change {
+            TnuaActionInitiationDirective::Allow
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        let up = ctx.up_direction.adjust_precision();
+
+        let landed = ctx
+            .basis
+            .displacement()
+            .is_some_and(|displacement| displacement.dot(up) <= 0.0);
+        if landed || matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        let Some((desired_velocity, time_to_land)) = self.plan(&ctx) else {
+            return TnuaActionLifecycleDirective::Finished;
+        };
+        state.time_to_land = time_to_land;
+        if time_to_land <= ctx.frame_duration {
+            // Arriving this frame - let the basis handle the landing.
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        motor.lin = TnuaVelChange {
+            acceleration: -self.extra_gravity * up,
+            boost: (desired_velocity - ctx.tracker.velocity)
+                .clamp_length_max(ctx.frame_duration * self.correction_acceleration),
+        };
+        TnuaActionLifecycleDirective::StillActive
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct TnuaBuiltinMotionWarpState {
+    /// The time, in seconds, until the character lands on the target.
+    pub time_to_land: Float,
+}
@@ -0,0 +1,265 @@
+use crate::math::{AdjustPrecision, AsF32, Float};
+use bevy::prelude::*;
+
+use crate::control_helpers::TnuaCrouchEnforcedAction;
+use crate::{TnuaAction, TnuaMotor, TnuaVelChange};
+use crate::{
+    TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus,
+};
+
+use super::TnuaBuiltinWalk;
+
+/// An [action](TnuaAction) for sliding low on the ground, keeping the momentum the character had
+/// when the slide started. Only works when [`TnuaBuiltinWalk`] is the [basis](crate::TnuaBasis).
+///
+/// Like [`TnuaBuiltinCrouch`](crate::builtins::TnuaBuiltinCrouch), the only field that must be set
+/// is [`float_offset`](Self::float_offset), which controls how low the character will slide.
+///
+/// The slide goes on, slowing down by [`deceleration`](Self::deceleration), until either the
+/// action is no longer fed, [`max_duration`](Self::max_duration) passes, or the speed drops below
+/// [`min_speed`](Self::min_speed) - and then the character stands back up. On slopes, the slide
+/// speeds up going downhill and slows down going uphill according to
+/// [`slope_acceleration_factor`](Self::slope_acceleration_factor).
+///
+/// To slide under low gaps, use this action together with
+/// [`TnuaCrouchEnforcer`](crate::control_helpers::TnuaCrouchEnforcer). While the enforcer senses a
+/// ceiling, it will [`hold`](Self::hold) the slide - so it will not end even if the duration
+/// passes, and the character will keep moving at `min_speed` until it exits the gap and stands up.
+///
+/// Tnua does not resize the collider of the character. If the collider is too tall for the gap,
+/// resize it while the [`TnuaBuiltinSlideState`] is [`Sliding`](TnuaBuiltinSlideState::Sliding).
+#[derive(Clone)]
+pub struct TnuaBuiltinSlide {
+    /// Controls how low the character will slide, compared to its regular float offset while
+    /// standing.
+    ///
+    /// This field should typically have a negative value.
+    pub float_offset: Float,
+
+    /// The minimal horizontal speed the character needs to have for the slide to start.
+    pub min_start_speed: Float,
+
+    /// The rate, in speed units per second, at which the character slows down during the slide.
+    pub deceleration: Float,
+
+    /// The portion of the gravity, pulling along the slope the character slides on, that
+    /// accelerates the slide.
+    ///
+    /// At `1.0` the slide speeds up downhill (and slows down uphill) like a frictionless body
+    /// would. At `0.0` (the default) slopes do not affect the slide.
+    pub slope_acceleration_factor: Float,
+
+    /// The slide will not speed up beyond this (e.g. when sliding downhill).
+    pub max_speed: Float,
+
+    /// The slide will end when the speed drops below this - unless the slide is held, in which
+    /// case the character will keep moving at this speed.
+    pub min_speed: Float,
+
+    /// The maximum duration of the slide, in seconds. Ignored while the slide is held.
+    pub max_duration: Float,
+
+    /// A duration, in seconds, that it should take for the character to change its floating height
+    /// to start or stop the slide.
+    pub height_change_impulse_for_duration: Float,
+
+    /// The maximum impulse to apply when starting or stopping the slide.
+    pub height_change_impulse_limit: Float,
+
+    /// Keep sliding, ignoring [`max_duration`](Self::max_duration) and
+    /// [`min_speed`](Self::min_speed). Set by
+    /// [`TnuaCrouchEnforcer`](crate::control_helpers::TnuaCrouchEnforcer) while the character is
+    /// under an obstacle.
+    pub hold: bool,
+
+    /// If set to `true`, this action will not yield to other action who try to take control.
+    pub uncancellable: bool,
+}
+
+impl Default for TnuaBuiltinSlide {
+    fn default() -> Self {
+        Self {
+            float_offset: 0.0,
+            min_start_speed: 4.0,
+            deceleration: 5.0,
+            slope_acceleration_factor: 0.0,
+            max_speed: Float::INFINITY,
+            min_speed: 2.0,
+            max_duration: 1.0,
+            height_change_impulse_for_duration: 0.02,
+            height_change_impulse_limit: 40.0,
+            hold: false,
+            uncancellable: false,
+        }
+    }
+}
+
+impl TnuaAction for TnuaBuiltinSlide {
+    const NAME: &'static str = "TnuaBuiltinSlide";
+    type State = TnuaBuiltinSlideState;
+    const VIOLATES_COYOTE_TIME: bool = false;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        _being_fed_for: &bevy::time::Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        if ctx.proximity_sensor.output.is_none() {
+            return TnuaActionInitiationDirective::Delay;
+        }
+        let horizontal_velocity = ctx
+            .basis
+            .effective_velocity()
+            .reject_from(ctx.up_direction.adjust_precision());
+        if self.min_start_speed <= horizontal_velocity.length() {
+            TnuaActionInitiationDirective::Allow
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        let Some((walk_basis, walk_state)) = ctx.concrete_basis::<TnuaBuiltinWalk>() else {
+            error!("Cannot slide - basis is not TnuaBuiltinWalk");
+            return TnuaActionLifecycleDirective::Finished;
+        };
+        let Some(sensor_output) = &ctx.proximity_sensor.output else {
+            // Slid off the ground
+            return TnuaActionLifecycleDirective::Finished;
+        };
+        let up = ctx.up_direction.adjust_precision();
+        let spring_offset_up = walk_basis.float_height - sensor_output.proximity.adjust_precision();
+        let spring_offset_down = spring_offset_up + self.float_offset;
+
+        match lifecycle_status {
+            TnuaActionLifecycleStatus::Initiated
+            | TnuaActionLifecycleStatus::CancelledFrom
+            | TnuaActionLifecycleStatus::StillFed => {}
+            TnuaActionLifecycleStatus::NoLongerFed => {
+                *state = TnuaBuiltinSlideState::Rising;
+            }
+            TnuaActionLifecycleStatus::CancelledInto => {
+                if !self.uncancellable {
+                    *state = TnuaBuiltinSlideState::Rising;
+                }
+            }
+        }
+
+        let height_change = |spring_offset: Float| -> TnuaVelChange {
+            let spring_force =
+                walk_basis.spring_force(walk_state, &ctx.as_basis_context(), spring_offset);
+            let spring_force_boost = crate::util::calc_boost(&spring_force, ctx.frame_duration);
+            let impulse_boost = (spring_offset / self.height_change_impulse_for_duration).clamp(
+                -self.height_change_impulse_limit,
+                self.height_change_impulse_limit,
+            );
+            if spring_force_boost.length_squared() < impulse_boost.powi(2) {
+                TnuaVelChange::boost(impulse_boost * up)
+            } else {
+                spring_force
+            }
+        };
+
+        let horizontal_velocity = ctx.basis.effective_velocity().reject_from(up);
+
+        // TODO: Once `std::mem::variant_count` gets stabilized, use that instead.
+        for _ in 0..3 {
+            return match state {
+                TnuaBuiltinSlideState::PreSlide => {
+                    let Ok(direction) = Dir3::new(horizontal_velocity.f32()) else {
+                        return TnuaActionLifecycleDirective::Finished;
+                    };
+                    *state = TnuaBuiltinSlideState::Sliding {
+                        direction,
+                        elapsed: 0.0,
+                    };
+                    continue;
+                }
+                TnuaBuiltinSlideState::Sliding { direction, elapsed } => {
+                    *elapsed += ctx.frame_duration;
+                    let direction = direction.adjust_precision();
+                    let speed = horizontal_velocity.dot(direction);
+                    if !self.hold && (self.max_duration <= *elapsed || speed < self.min_speed) {
+                        *state = TnuaBuiltinSlideState::Rising;
+                        continue;
+                    }
+                    let normal = sensor_output.normal.adjust_precision();
+                    let slope_acceleration = self.slope_acceleration_factor
+                        * ctx.tracker.gravity.reject_from(normal).dot(direction);
+                    let desired_speed = (speed
+                        + ctx.frame_duration * (slope_acceleration - self.deceleration))
+                        .clamp(self.min_speed, self.max_speed.max(self.min_speed));
+                    // Replace the horizontal movement of the basis entirely - the player should
+                    // not be able to steer the slide.
+                    motor.lin =
+                        TnuaVelChange::boost(desired_speed * direction - horizontal_velocity)
+                            + height_change(spring_offset_down);
+                    TnuaActionLifecycleDirective::StillActive
+                }
+                TnuaBuiltinSlideState::Rising => {
+                    if self.hold {
+                        // Got under an obstacle while rising - go back to sliding.
+                        *state = TnuaBuiltinSlideState::PreSlide;
+                        continue;
+                    }
+                    if 0.01 < spring_offset_up {
+                        motor.lin.cancel_on_axis(up);
+                        motor.lin += height_change(spring_offset_up);
+                        if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+                            // Don't finish the rise - just do the other action
+                            TnuaActionLifecycleDirective::Reschedule { after_seconds: 0.0 }
+                        } else {
+                            TnuaActionLifecycleDirective::StillActive
+                        }
+                    } else {
+                        TnuaActionLifecycleDirective::Finished
+                    }
+                }
+            };
+        }
+        error!("Tnua could not decide on slide state");
+        TnuaActionLifecycleDirective::Finished
+    }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinSlideState::PreSlide => "PreSlide",
+            TnuaBuiltinSlideState::Sliding { .. } => "Sliding",
+            TnuaBuiltinSlideState::Rising => "Rising",
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub enum TnuaBuiltinSlideState {
+    /// The action has just started and did not pick the slide direction yet.
+    #[default]
+    PreSlide,
+    /// The character is sliding - or sinking to the slide height.
+    Sliding {
+        /// The horizontal direction of the slide.
+        direction: Dir3,
+        /// The time, in seconds, since the slide started.
+        elapsed: Float,
+    },
+    /// The character is standing up after the slide.
+    Rising,
+}
+
+impl TnuaCrouchEnforcedAction for TnuaBuiltinSlide {
+    fn range_to_cast_up(&self, _state: &Self::State) -> Float {
+        -self.float_offset
+    }
+
+    fn prevent_cancellation(&mut self) {
+        self.uncancellable = true;
+        self.hold = true;
+    }
+}
@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use crate::math::{AdjustPrecision, Float, Quaternion, Vector3};
+use bevy::prelude::*;
+
+use crate::motion_math;
+use crate::util::rotation_arc_around_axis;
+use crate::{TnuaBasis, TnuaBasisContext, TnuaMotor, TnuaVelChange};
+
+/// The height of the water's surface, as a function of the position.
+///
+/// The height is measured along the up direction - for the usual up direction of `Vector3::Y` it
+/// is the Y coordinate of the surface.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use bevy_tnua::builtins::TnuaWaterSurface;
+/// # use bevy_tnua::math::{Float, Vector3};
+/// # let elapsed_time: Float = 0.0;
+/// // Since the basis is fed every frame, the time can be captured by the function.
+/// let waves = TnuaWaterSurface::Custom(Arc::new(move |position: Vector3| {
+///     0.3 * (position.x + 2.0 * elapsed_time).sin()
+/// }));
+/// assert_eq!(waves.height_at(Vector3::ZERO), 0.0);
+/// ```
+#[derive(Clone)]
+pub enum TnuaWaterSurface {
+    /// A flat surface at the given height.
+    Flat(Float),
+    /// Call a function with the position (in world coordinates) to get the height of the surface
+    /// above (or below) it.
+    Custom(Arc<dyn Send + Sync + Fn(Vector3) -> Float>),
+}
+
+impl Default for TnuaWaterSurface {
+    fn default() -> Self {
+        Self::Flat(0.0)
+    }
+}
+
+impl TnuaWaterSurface {
+    /// The height of the surface above (or below) the given position.
+    pub fn height_at(&self, position: Vector3) -> Float {
+        match self {
+            TnuaWaterSurface::Flat(height) => *height,
+            TnuaWaterSurface::Custom(surface) => surface(position),
+        }
+    }
+}
+
+/// A [basis](TnuaBasis) for swimming in water.
+///
+/// The buoyancy keeps the character floating with its center
+/// [`float_depth`](Self::float_depth) below the [surface of the water](Self::water_surface),
+/// following the surface as it rises and falls. The character can dive by giving
+/// [`desired_velocity`](Self::desired_velocity) a downward component, and will float back up when
+/// it stops diving.
+///
+/// Detecting that the character is in the water, and switching between this basis and
+/// [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk), is up to the game code.
+#[derive(Clone)]
+pub struct TnuaBuiltinSwim {
+    /// The direction (in the world space) and speed to swim in. Unlike with the walk basis, this
+    /// can have a vertical component for diving and rising.
+    pub desired_velocity: Vector3,
+
+    /// The direction the character should face. Retains the current facing when `None`.
+    pub desired_forward: Option<Dir3>,
+
+    /// The surface of the water.
+    ///
+    /// It is sampled once per frame at the character's position. The sampled height is available
+    /// from [`TnuaBuiltinSwimState::surface_height`].
+    pub water_surface: TnuaWaterSurface,
+
+    /// How deep below the surface the center of the character floats.
+    pub float_depth: Float,
+
+    /// The strength of the buoyancy that pushes the character back to its floating depth.
+    pub buoyancy_strength: Float,
+
+    /// A boost for dampening the vertical motion of the character relative to the surface, to
+    /// make it stop bobbing.
+    pub buoyancy_dampening: Float,
+
+    /// The maximum acceleration for reaching the desired velocity.
+    pub acceleration: Float,
+
+    /// The maximum angular velocity used for keeping the character upright.
+    pub tilt_offset_angvel: Float,
+
+    /// The maximum angular acceleration used for reaching `tilt_offset_angvel`.
+    pub tilt_offset_angacl: Float,
+
+    /// The maximum angular velocity used for turning the character when the direction changes.
+    pub turning_angvel: Float,
+}
+
+impl Default for TnuaBuiltinSwim {
+    fn default() -> Self {
+        Self {
+            desired_velocity: Vector3::ZERO,
+            desired_forward: None,
+            water_surface: Default::default(),
+            float_depth: 0.5,
+            buoyancy_strength: 100.0,
+            buoyancy_dampening: 0.3,
+            acceleration: 20.0,
+            tilt_offset_angvel: 5.0,
+            tilt_offset_angacl: 500.0,
+            turning_angvel: 10.0,
+        }
+    }
+}
+
+impl TnuaBasis for TnuaBuiltinSwim {
+    const NAME: &'static str = "TnuaBuiltinSwim";
+    type State = TnuaBuiltinSwimState;
+
+    fn apply(&self, state: &mut Self::State, ctx: TnuaBasisContext, motor: &mut TnuaMotor) {
+        let up = ctx.up_direction.adjust_precision();
+
+        let surface_height = self.water_surface.height_at(ctx.tracker.translation);
+        let surface_velocity = match state.surface_height {
+            Some(previous) => (surface_height - previous) / ctx.frame_duration,
+            None => 0.0,
+        };
+        state.surface_height = Some(surface_height);
+        state.effective_velocity = ctx.tracker.velocity;
+
+        let velocity = ctx.tracker.velocity;
+        let desired_velocity = self.desired_velocity + ctx.external_velocity;
+        let desired_upward_velocity = desired_velocity.dot(up);
+
+        // Swim horizontally
+        let horizontal_acceleration = motion_math::velocity_matching_acceleration(
+            velocity.reject_from(up),
+            desired_velocity.reject_from(up),
+            self.acceleration,
+            ctx.frame_duration,
+        );
+
+        // Float (or dive)
+        let float_offset = ctx.tracker.translation.dot(up) - (surface_height - self.float_depth);
+        state.float_offset = float_offset;
+        state.displacement = float_offset * up;
+        let gravity = -ctx.tracker.gravity.dot(up);
+        let gravity_compensation = -ctx.tracker.gravity.reject_from(up);
+        let upward_velocity = velocity.dot(up);
+        state.vertical_velocity = upward_velocity;
+        let vertical = if desired_upward_velocity < 0.0
+            || (0.0 < desired_upward_velocity && float_offset < 0.0)
+        {
+            // Diving, or rising back to the floating depth
+            TnuaVelChange::acceleration(
+                up * (gravity
+                    + ((desired_upward_velocity - upward_velocity) / ctx.frame_duration)
+                        .clamp(-self.acceleration, self.acceleration)),
+            )
+        } else {
+            TnuaVelChange {
+                acceleration: up
+                    * motion_math::float_spring_acceleration(
+                        -float_offset,
+                        self.buoyancy_strength,
+                        gravity,
+                    ),
+                boost: up
+                    * motion_math::float_spring_dampening_boost(
+                        upward_velocity - surface_velocity,
+                        self.buoyancy_dampening,
+                    ),
+            }
+        };
+        motor.lin =
+            vertical + TnuaVelChange::acceleration(horizontal_acceleration + gravity_compensation);
+
+        // Keep the character upright, and turn it to the desired direction
+        let tilted_up = ctx.tracker.rotation.mul_vec3(Vector3::Y);
+        let rotation_required_to_fix_tilt = Quaternion::from_rotation_arc(tilted_up, up);
+        let desired_angvel = (rotation_required_to_fix_tilt.xyz() / ctx.frame_duration)
+            .clamp_length_max(self.tilt_offset_angvel);
+        let torque_to_fix_tilt = (desired_angvel - ctx.tracker.angvel)
+            .clamp_length_max(ctx.frame_duration * self.tilt_offset_angacl);
+
+        let rotation_along_up_axis = self.desired_forward.map_or(0.0, |desired_forward| {
+            let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+            rotation_arc_around_axis(
+                ctx.up_direction,
+                current_forward,
+                desired_forward.adjust_precision(),
+            )
+            .unwrap_or(0.0)
+        });
+        let desired_turn_angvel = (rotation_along_up_axis / ctx.frame_duration)
+            .clamp(-self.turning_angvel, self.turning_angvel);
+        let torque_to_turn =
+            desired_turn_angvel - ctx.tracker.angvel.dot(up) - torque_to_fix_tilt.dot(up);
+        motor.ang = TnuaVelChange::boost(torque_to_fix_tilt + torque_to_turn * up);
+    }
+
+    fn proximity_sensor_cast_range(&self, _state: &Self::State) -> Float {
+        0.0
+    }
+
+    fn displacement(&self, state: &Self::State) -> Option<Vector3> {
+        Some(state.displacement)
+    }
+
+    fn effective_velocity(&self, state: &Self::State) -> Vector3 {
+        state.effective_velocity
+    }
+
+    fn vertical_velocity(&self, state: &Self::State) -> Float {
+        state.vertical_velocity
+    }
+
+    fn neutralize(&mut self) {
+        self.desired_velocity = Vector3::ZERO;
+        self.desired_forward = None;
+    }
+
+    fn is_airborne(&self, _state: &Self::State) -> bool {
+        // The water supports the character, so it can jump out of it.
+        false
+    }
+
+    fn violate_coyote_time(&self, _state: &mut Self::State) {}
+}
+
+#[derive(Default, Debug)]
+pub struct TnuaBuiltinSwimState {
+    surface_height: Option<Float>,
+    float_offset: Float,
+    displacement: Vector3,
+    vertical_velocity: Float,
+    effective_velocity: Vector3,
+}
+
+impl TnuaBuiltinSwimState {
+    /// The height of the water's surface sampled at the character's position in the last frame.
+    pub fn surface_height(&self) -> Option<Float> {
+        self.surface_height
+    }
+
+    /// How far above its floating depth the center of the character is (negative when it is
+    /// deeper).
+    pub fn float_offset(&self) -> Float {
+        self.float_offset
+    }
+}
@@ -0,0 +1,203 @@
+use crate::math::{AdjustPrecision, Float, Quaternion, Vector3};
+use bevy::prelude::*;
+
+use crate::util::rotation_arc_around_axis;
+use crate::{TnuaBasis, TnuaBasisContext, TnuaMotor, TnuaVelChange};
+
+/// A [basis](TnuaBasis) for swinging from an anchor point on a rope - e.g. for a grappling hook.
+///
+/// The rope does not pull the character while it is slack. When the character gets as far from
+/// the [`anchor`](Self::anchor) as the length of the rope, the rope stops it from moving away -
+/// which, together with the gravity, makes it swing like a pendulum. The player can push the
+/// swing with [`desired_swing`](Self::desired_swing) and change the length of the rope with
+/// [`reel`](Self::reel).
+///
+/// When the tether starts, the length of the rope is the distance of the character from the
+/// anchor (clamped between [`min_length`](Self::min_length) and [`max_length`](Self::max_length)).
+/// The current length can be read from the [`TnuaBuiltinTetherState`].
+///
+/// To release the rope, switch back to [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk).
+/// The momentum of the swing is preserved, since Tnua does not touch the velocity when changing
+/// the basis - and a [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump) with
+/// [`allow_in_air`](crate::builtins::TnuaBuiltinJump::allow_in_air) can be fed on the same frame to
+/// jump off the rope.
+#[derive(Clone)]
+pub struct TnuaBuiltinTether {
+    /// The point, in world coordinates, the rope is attached to.
+    pub anchor: Vector3,
+
+    /// The direction, perpendicular to the rope, to push the swing in. Its length should be
+    /// between zero and one.
+    pub desired_swing: Vector3,
+
+    /// The acceleration of the swing when [`desired_swing`](Self::desired_swing) has length one.
+    pub swing_acceleration: Float,
+
+    /// The portion of the velocity perpendicular to the rope that is lost each second, to make
+    /// the swing settle down.
+    pub swing_damping: Float,
+
+    /// The input for changing the length of the rope. Positive values reel in (shorten the rope)
+    /// and negative values reel out. Should be between `-1.0` and `1.0`.
+    pub reel: Float,
+
+    /// The speed, in length units per second, of reeling when [`reel`](Self::reel) is `1.0` or
+    /// `-1.0`.
+    pub reel_speed: Float,
+
+    /// The shortest the rope can get.
+    pub min_length: Float,
+
+    /// The longest the rope can get.
+    pub max_length: Float,
+
+    /// How fast, in units of 1/second, the character is pulled back when it gets farther than the
+    /// length of the rope.
+    ///
+    /// Higher values make the rope stiffer.
+    pub stretch_correction: Float,
+
+    /// The direction the character should face. Retains the current facing when `None`.
+    pub desired_forward: Option<Dir3>,
+
+    /// The maximum angular velocity used for keeping the character upright.
+    pub tilt_offset_angvel: Float,
+
+    /// The maximum angular acceleration used for reaching `tilt_offset_angvel`.
+    pub tilt_offset_angacl: Float,
+
+    /// The maximum angular velocity used for turning the character when the direction changes.
+    pub turning_angvel: Float,
+}
+
+impl Default for TnuaBuiltinTether {
+    fn default() -> Self {
+        Self {
+            anchor: Vector3::ZERO,
+            desired_swing: Vector3::ZERO,
+            swing_acceleration: 10.0,
+            swing_damping: 0.1,
+            reel: 0.0,
+            reel_speed: 5.0,
+            min_length: 1.0,
+            max_length: 20.0,
+            stretch_correction: 20.0,
+            desired_forward: None,
+            tilt_offset_angvel: 5.0,
+            tilt_offset_angacl: 500.0,
+            turning_angvel: 10.0,
+        }
+    }
+}
+
+impl TnuaBasis for TnuaBuiltinTether {
+    const NAME: &'static str = "TnuaBuiltinTether";
+    type State = TnuaBuiltinTetherState;
+
+    fn apply(&self, state: &mut Self::State, ctx: TnuaBasisContext, motor: &mut TnuaMotor) {
+        let offset = ctx.tracker.translation - self.anchor;
+        let distance = offset.length();
+        let min_length = self.min_length.min(self.max_length);
+        let length = state
+            .length
+            .unwrap_or(distance)
+            .clamp(min_length, self.max_length);
+        let length = (length - self.reel * self.reel_speed * ctx.frame_duration)
+            .clamp(min_length, self.max_length);
+        state.length = Some(length);
+        state.effective_velocity = ctx.tracker.velocity;
+
+        let mut lin = TnuaVelChange::ZERO;
+        let radial = offset.normalize_or_zero();
+        state.taut = radial != Vector3::ZERO && length <= distance;
+        let tangential_velocity = if state.taut {
+            // The rope cannot stretch - remove the velocity that moves away from the anchor, and
+            // pull back if the character is already too far.
+            let radial_speed = ctx.tracker.velocity.dot(radial);
+            let desired_radial_speed = -(distance - length) * self.stretch_correction;
+            if desired_radial_speed < radial_speed {
+                lin.boost += (desired_radial_speed - radial_speed) * radial;
+            }
+            ctx.tracker.velocity.reject_from(radial)
+        } else {
+            ctx.tracker.velocity
+        };
+
+        let swing = if state.taut {
+            self.desired_swing.reject_from(radial)
+        } else {
+            self.desired_swing
+        };
+        lin.acceleration += self.swing_acceleration * swing.clamp_length_max(1.0);
+        lin.boost -= tangential_velocity * (ctx.frame_duration * self.swing_damping).min(1.0);
+        motor.lin = lin;
+
+        // Keep the character upright, and turn it to the desired direction
+        let up = ctx.up_direction.adjust_precision();
+        let tilted_up = ctx.tracker.rotation.mul_vec3(Vector3::Y);
+        let rotation_required_to_fix_tilt = Quaternion::from_rotation_arc(tilted_up, up);
+        let desired_angvel = (rotation_required_to_fix_tilt.xyz() / ctx.frame_duration)
+            .clamp_length_max(self.tilt_offset_angvel);
+        let torque_to_fix_tilt = (desired_angvel - ctx.tracker.angvel)
+            .clamp_length_max(ctx.frame_duration * self.tilt_offset_angacl);
+
+        let rotation_along_up_axis = self.desired_forward.map_or(0.0, |desired_forward| {
+            let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+            rotation_arc_around_axis(
+                ctx.up_direction,
+                current_forward,
+                desired_forward.adjust_precision(),
+            )
+            .unwrap_or(0.0)
+        });
+        let desired_turn_angvel = (rotation_along_up_axis / ctx.frame_duration)
+            .clamp(-self.turning_angvel, self.turning_angvel);
+        let torque_to_turn =
+            desired_turn_angvel - ctx.tracker.angvel.dot(up) - torque_to_fix_tilt.dot(up);
+        motor.ang = TnuaVelChange::boost(torque_to_fix_tilt + torque_to_turn * up);
+    }
+
+    fn proximity_sensor_cast_range(&self, _state: &Self::State) -> Float {
+        0.0
+    }
+
+    fn displacement(&self, _state: &Self::State) -> Option<Vector3> {
+        None
+    }
+
+    fn effective_velocity(&self, state: &Self::State) -> Vector3 {
+        state.effective_velocity
+    }
+
+    fn vertical_velocity(&self, _state: &Self::State) -> Float {
+        0.0
+    }
+
+    fn neutralize(&mut self) {
+        self.desired_swing = Vector3::ZERO;
+        self.reel = 0.0;
+        self.desired_forward = None;
+    }
+
+    fn is_airborne(&self, _state: &Self::State) -> bool {
+        true
+    }
+
+    fn violate_coyote_time(&self, _state: &mut Self::State) {}
+}
+
+#[derive(Default, Debug)]
+pub struct TnuaBuiltinTetherState {
+    length: Option<Float>,
+    /// Whether the rope is stretched to its full length, so that the character swings instead of
+    /// falling freely.
+    pub taut: bool,
+    effective_velocity: Vector3,
+}
+
+impl TnuaBuiltinTetherState {
+    /// The current length of the rope.
+    pub fn rope_length(&self) -> Option<Float> {
+        self.length
+    }
+}
@@ -0,0 +1,229 @@
+use crate::math::{AdjustPrecision, Float, Quaternion, Vector3};
+use bevy::prelude::*;
+
+use crate::builtins::TnuaAccelerationCurve;
+use crate::motion_math;
+use crate::util::rotation_arc_around_axis;
+use crate::{TnuaBasis, TnuaBasisContext, TnuaMotor, TnuaVelChange};
+
+/// A [basis](TnuaBasis) for top-down movement, for games like twin-stick shooters and isometric
+/// games.
+///
+/// Unlike [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk), this basis does not float the
+/// character and does not use the ground sensor. It only moves the character on a plane - the one
+/// perpendicular to [`plane_normal`](Self::plane_normal) - and leaves the motion along the normal
+/// to the physics engine (which means that with gravity the collider simply rests on the ground).
+/// Gravity along the plane, if there is any, is cancelled. Obstacles are handled by the physics
+/// engine's collisions, and the actions that work on the ground plane
+/// ([`TnuaBuiltinDash`](crate::builtins::TnuaBuiltinDash),
+/// [`TnuaBuiltinKnockback`](crate::builtins::TnuaBuiltinKnockback)) work with this basis too,
+/// since the character is never considered airborne.
+///
+/// The movement direction and the facing direction are independent, so a twin-stick shooter can
+/// move the character with one stick and aim with the other:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::builtins::TnuaBuiltinTopDown;
+/// # use bevy_tnua::math::Vector3;
+/// # let mut controller = TnuaController::default();
+/// # let (move_stick, aim_stick) = (Vector3::ZERO, Vector3::X);
+/// controller.basis(TnuaBuiltinTopDown {
+///     desired_velocity: 8.0 * move_stick,
+///     desired_forward: Dir3::new(aim_stick).ok(),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Clone)]
+pub struct TnuaBuiltinTopDown {
+    /// The direction (in the world space) and speed to move the character at. Only the part of it
+    /// on the movement plane is used.
+    pub desired_velocity: Vector3,
+
+    /// The direction the character should face. Retains the current facing when `None`.
+    pub desired_forward: Option<Dir3>,
+
+    /// The normal of the movement plane. When `None`, the up direction (the reverse of the
+    /// gravity) is used.
+    ///
+    /// 2D games without gravity must set this to `Dir3::Z`, since the up direction of a
+    /// character without gravity defaults to `Dir3::Y`.
+    pub plane_normal: Option<Dir3>,
+
+    /// The axis, in the character's coord system, that is considered its forward direction for
+    /// [`desired_forward`](Self::desired_forward).
+    ///
+    /// Must be on the movement plane. The default, `Dir3::NEG_Z`, fits 3D games - 2D games would
+    /// usually use `Dir3::X` or `Dir3::Y` instead, depending on the sprite.
+    pub forward_axis: Dir3,
+
+    /// The maximum acceleration when the character moves toward the desired velocity.
+    pub acceleration: Float,
+
+    /// Shapes the [`acceleration`](Self::acceleration) as the character approaches its desired
+    /// speed. See [`TnuaAccelerationCurve`].
+    pub acceleration_curve: TnuaAccelerationCurve,
+
+    /// The maximum angular velocity used for keeping the character perpendicular to the movement
+    /// plane.
+    ///
+    /// Has no effect in 2D, where the physics engine only rotates the character around the normal
+    /// of the plane.
+    pub tilt_offset_angvel: Float,
+
+    /// The maximum angular acceleration used for reaching `tilt_offset_angvel`.
+    pub tilt_offset_angacl: Float,
+
+    /// The maximum angular velocity used for turning the character when the direction changes.
+    pub turning_angvel: Float,
+}
+
+impl Default for TnuaBuiltinTopDown {
+    fn default() -> Self {
+        Self {
+            desired_velocity: Vector3::ZERO,
+            desired_forward: None,
+            plane_normal: None,
+            forward_axis: Dir3::NEG_Z,
+            acceleration: 60.0,
+            acceleration_curve: TnuaAccelerationCurve::Flat,
+            tilt_offset_angvel: 5.0,
+            tilt_offset_angacl: 500.0,
+            turning_angvel: 20.0,
+        }
+    }
+}
+
+impl TnuaBasis for TnuaBuiltinTopDown {
+    const NAME: &'static str = "TnuaBuiltinTopDown";
+    type State = TnuaBuiltinTopDownState;
+
+    fn apply(&self, state: &mut Self::State, ctx: TnuaBasisContext, motor: &mut TnuaMotor) {
+        let plane_normal = self.plane_normal.unwrap_or(ctx.up_direction);
+        let normal = plane_normal.adjust_precision();
+
+        state.effective_velocity = ctx.tracker.velocity;
+        let velocity_on_plane = ctx.tracker.velocity.reject_from(normal);
+        let desired_velocity = if let Some(root_motion) = &ctx.root_motion {
+            root_motion.velocity
+        } else {
+            self.desired_velocity
+        } + ctx.external_velocity;
+        let desired_velocity = desired_velocity.reject_from(normal);
+
+        let desired_speed = desired_velocity.length();
+        let speed_ratio = if 0.0 < desired_speed {
+            (velocity_on_plane.dot(desired_velocity) / desired_speed.powi(2)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let acceleration_limit = if ctx.root_motion.is_some() {
+            // Root motion is followed exactly.
+            Float::INFINITY
+        } else {
+            self.acceleration * self.acceleration_curve.multiplier(speed_ratio)
+        };
+        let max_acceleration = motion_math::velocity_matching_max_acceleration(
+            velocity_on_plane,
+            desired_velocity,
+            acceleration_limit,
+        );
+        let desired_boost = desired_velocity - velocity_on_plane;
+        let move_vel_change = if desired_velocity == Vector3::ZERO {
+            // When stopping, prefer a boost to be able to reach a precise stop (see issue #39)
+            TnuaVelChange::boost(
+                desired_boost.clamp_length_max(ctx.frame_duration * max_acceleration),
+            )
+        } else {
+            // When accelerating, prefer an acceleration because the physics backends treat it
+            // better (see issue #34)
+            TnuaVelChange::acceleration(
+                (desired_boost / ctx.frame_duration).clamp_length_max(max_acceleration),
+            )
+        };
+        motor.lin =
+            move_vel_change + TnuaVelChange::acceleration(-ctx.tracker.gravity.reject_from(normal));
+        state.running_velocity = velocity_on_plane
+            + motor.lin.boost
+            + ctx.frame_duration * motor.lin.acceleration.reject_from(normal);
+
+        // Tilt
+
+        let torque_to_fix_tilt = {
+            let tilted_normal = ctx.tracker.rotation.mul_vec3(Vector3::Y);
+            let rotation_required_to_fix_tilt =
+                Quaternion::from_rotation_arc(tilted_normal, normal);
+            let desired_angvel = (rotation_required_to_fix_tilt.xyz() / ctx.frame_duration)
+                .clamp_length_max(self.tilt_offset_angvel);
+            (desired_angvel - ctx.tracker.angvel)
+                .clamp_length_max(ctx.frame_duration * self.tilt_offset_angacl)
+        };
+
+        // Turning
+
+        let desired_angvel = if let Some(root_motion) = &ctx.root_motion {
+            root_motion.angvel.dot(normal)
+        } else {
+            let rotation_along_normal = self.desired_forward.map_or(0.0, |desired_forward| {
+                let current_forward = ctx
+                    .tracker
+                    .rotation
+                    .mul_vec3(self.forward_axis.adjust_precision());
+                rotation_arc_around_axis(
+                    plane_normal,
+                    current_forward,
+                    desired_forward.adjust_precision(),
+                )
+                .unwrap_or(0.0)
+            });
+            (rotation_along_normal / ctx.frame_duration)
+                .clamp(-self.turning_angvel, self.turning_angvel)
+        };
+        let torque_to_turn =
+            desired_angvel - ctx.tracker.angvel.dot(normal) - torque_to_fix_tilt.dot(normal);
+        motor.ang = TnuaVelChange::boost(torque_to_fix_tilt + torque_to_turn * normal);
+    }
+
+    fn proximity_sensor_cast_range(&self, _state: &Self::State) -> Float {
+        0.0
+    }
+
+    fn displacement(&self, _state: &Self::State) -> Option<Vector3> {
+        // There is no float height, so the character is always where it should be.
+        Some(Vector3::ZERO)
+    }
+
+    fn effective_velocity(&self, state: &Self::State) -> Vector3 {
+        state.effective_velocity
+    }
+
+    fn vertical_velocity(&self, _state: &Self::State) -> Float {
+        0.0
+    }
+
+    fn neutralize(&mut self) {
+        self.desired_velocity = Vector3::ZERO;
+        self.desired_forward = None;
+    }
+
+    fn is_airborne(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    fn violate_coyote_time(&self, _state: &mut Self::State) {}
+
+    fn airborne_duration(&self, _state: &Self::State) -> Option<Float> {
+        Some(0.0)
+    }
+
+    fn running_velocity(&self, state: &Self::State) -> Option<Vector3> {
+        Some(state.running_velocity)
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct TnuaBuiltinTopDownState {
+    effective_velocity: Vector3,
+    running_velocity: Vector3,
+}
@@ -0,0 +1,278 @@
+use crate::math::{AdjustPrecision, Float, Vector3};
+use bevy::prelude::*;
+
+use crate::builtins::TnuaLedge;
+use crate::util::rotation_arc_around_axis;
+use crate::{
+    prelude::*, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor, TnuaVelChange,
+};
+
+/// An [action](TnuaAction) for vaulting over low obstacles.
+///
+/// When the character runs into an obstacle whose top is within the
+/// [`min_height`](Self::min_height)..[`max_height`](Self::max_height) band above the ground, the
+/// action moves it up and over the obstacle over a fixed [`duration`](Self::duration). Once
+/// started, the vault goes on until it is done even if the action is no longer fed.
+///
+/// The obstacle can be detected with a
+/// [`TnuaLedgeDetector`](crate::control_helpers::TnuaLedgeDetector) whose two stacked forward
+/// probes are placed at the bottom and the top of the height band - the
+/// [`wall_probe_origin`](crate::control_helpers::TnuaLedgeDetector::wall_probe_origin) a little
+/// above the feet and the
+/// [`clearance_probe_origin`](crate::control_helpers::TnuaLedgeDetector::clearance_probe_origin)
+/// at the maximal vaulting height:
+///
+/// ```no_run
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::builtins::TnuaBuiltinVault;
+/// # use bevy_tnua::control_helpers::TnuaLedgeDetector;
+/// # let mut controller = TnuaController::default();
+/// # let vault_detector = TnuaLedgeDetector::default();
+/// if let Some(obstacle) = vault_detector.ledge() {
+///     controller.action(TnuaBuiltinVault {
+///         obstacle: Some(obstacle),
+///         ..Default::default()
+///     });
+/// }
+/// ```
+///
+/// The phase of the vault is available from
+/// [`TnuaController::action_stage`](crate::prelude::TnuaController::action_stage), and its
+/// [`progress`](TnuaBuiltinVaultState::progress) from the action's state, for syncing the vault
+/// animation.
+#[derive(Clone)]
+pub struct TnuaBuiltinVault {
+    /// The top edge of the obstacle to vault over.
+    ///
+    /// The action will not start if this is `None`. This input parameter is cached when the action
+    /// starts.
+    pub obstacle: Option<TnuaLedge>,
+
+    /// The minimal height of the obstacle's top above the ground for the action to start.
+    pub min_height: Float,
+
+    /// The maximal height of the obstacle's top above the ground for the action to start.
+    pub max_height: Float,
+
+    /// The maximal horizontal distance between the center of the character and the obstacle's
+    /// edge for the action to start.
+    pub max_distance: Float,
+
+    /// The minimal speed toward the obstacle for the action to start.
+    pub min_approach_speed: Float,
+
+    /// The time, in seconds, it takes to vault over the obstacle.
+    pub duration: Float,
+
+    /// The part of the [`duration`](Self::duration) (between `0.0` and `1.0`) spent rising to
+    /// above the obstacle. The character keeps moving forward while rising.
+    pub rise_fraction: Float,
+
+    /// How far above the obstacle's top the center of the character passes.
+    pub clearance: Float,
+
+    /// How far past the obstacle's edge the character is when the vault ends.
+    pub over_distance: Float,
+}
+
+impl Default for TnuaBuiltinVault {
+    fn default() -> Self {
+        Self {
+            obstacle: None,
+            min_height: 0.3,
+            max_height: 1.2,
+            max_distance: 1.0,
+            min_approach_speed: 1.0,
+            duration: 0.4,
+            rise_fraction: 0.4,
+            clearance: 1.0,
+            over_distance: 1.0,
+        }
+    }
+}
+
+impl TnuaBuiltinVault {
+    fn path_position(&self, start: Vector3, end: Vector3, up: Vector3, progress: Float) -> Vector3 {
+        let progress = progress.clamp(0.0, 1.0);
+        let rise = if 0.0 < self.rise_fraction {
+            (progress / self.rise_fraction).min(1.0)
+        } else {
+            1.0
+        };
+        let rise = rise * rise * (3.0 - 2.0 * rise);
+        let offset = end - start;
+        let vertical = offset.dot(up);
+        start + progress * (offset - vertical * up) + rise * vertical * up
+    }
+
+    fn progress(&self, elapsed: Float) -> Float {
+        if 0.0 < self.duration {
+            elapsed / self.duration
+        } else {
+            1.0
+        }
+    }
+}
+
+impl TnuaAction for TnuaBuiltinVault {
+    const NAME: &'static str = "TnuaBuiltinVault";
+    type State = TnuaBuiltinVaultState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: crate::TnuaActionContext,
+        _being_fed_for: &bevy::time::Stopwatch,
+    ) -> crate::TnuaActionInitiationDirective {
+        let Some(obstacle) = self.obstacle.as_ref() else {
+            return TnuaActionInitiationDirective::Reject;
+        };
+        if !obstacle.point.is_finite() || ctx.basis.is_airborne() {
+            return TnuaActionInitiationDirective::Reject;
+        }
+        let Some(ground) = ctx.proximity_sensor.output.as_ref() else {
+            return TnuaActionInitiationDirective::Reject;
+        };
+        let up = ctx.up_direction.adjust_precision();
+        let toward_obstacle = -obstacle.wall_normal.adjust_precision();
+
+        let ground_point = ctx.tracker.translation - ground.proximity * up;
+        let height = (obstacle.point - ground_point).dot(up);
+        let distance = (obstacle.point - ctx.tracker.translation)
+            .reject_from(up)
+            .length();
+        let approach_speed = ctx.tracker.velocity.dot(toward_obstacle);
+        if (self.min_height..=self.max_height).contains(&height)
+            && distance <= self.max_distance
+            && self.min_approach_speed <= approach_speed
+        {
+            TnuaActionInitiationDirective::Allow
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        _lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        let up = ctx.up_direction.adjust_precision();
+
+        // TODO: Once `std::mem::variant_count` gets stabilized, use that instead.
+        for _ in 0..3 {
+            return match state {
+                TnuaBuiltinVaultState::PreVault => {
+                    let Some(obstacle) = self.obstacle else {
+                        // Probably unneeded because of the `initiation_decision`, but still
+                        return TnuaActionLifecycleDirective::Finished;
+                    };
+                    *state = TnuaBuiltinVaultState::Rising {
+                        start: ctx.tracker.translation,
+                        end: obstacle.point + self.clearance * up
+                            - self.over_distance * obstacle.wall_normal.adjust_precision(),
+                        elapsed: 0.0,
+                    };
+                    continue;
+                }
+                TnuaBuiltinVaultState::Rising {
+                    start,
+                    end,
+                    elapsed,
+                }
+                | TnuaBuiltinVaultState::Crossing {
+                    start,
+                    end,
+                    elapsed,
+                } => {
+                    *elapsed += ctx.frame_duration;
+                    let (start, end, elapsed) = (*start, *end, *elapsed);
+                    let progress = self.progress(elapsed);
+
+                    // Follow the path exactly, ignoring gravity.
+                    let target = self.path_position(start, end, up, progress);
+                    motor.lin = TnuaVelChange::acceleration(-ctx.tracker.gravity);
+                    motor.lin.boost = (target - ctx.tracker.translation) / ctx.frame_duration
+                        - ctx.tracker.velocity;
+
+                    let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+                    let rotation_along_up_axis = rotation_arc_around_axis(
+                        ctx.up_direction,
+                        current_forward,
+                        (end - start).reject_from(up),
+                    )
+                    .unwrap_or(0.0);
+                    let desired_angvel = rotation_along_up_axis / ctx.frame_duration;
+                    let existing_angvel = ctx.tracker.angvel.dot(up);
+                    motor.ang.cancel_on_axis(up);
+                    motor.ang += TnuaVelChange::boost((desired_angvel - existing_angvel) * up);
+
+                    if 1.0 <= progress {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+                    if self.rise_fraction <= progress {
+                        *state = TnuaBuiltinVaultState::Crossing {
+                            start,
+                            end,
+                            elapsed,
+                        };
+                    }
+                    TnuaActionLifecycleDirective::StillActive
+                }
+            };
+        }
+        error!("Tnua could not decide on vault state");
+        TnuaActionLifecycleDirective::Finished
+    }
+
+    fn stage_name(&self, state: &Self::State) -> Option<&'static str> {
+        Some(match state {
+            TnuaBuiltinVaultState::PreVault => "PreVault",
+            TnuaBuiltinVaultState::Rising { .. } => "Rising",
+            TnuaBuiltinVaultState::Crossing { .. } => "Crossing",
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub enum TnuaBuiltinVaultState {
+    /// The action has just started and did not plan the path yet.
+    #[default]
+    PreVault,
+    /// Moving up and forward, to above the obstacle.
+    Rising {
+        start: Vector3,
+        end: Vector3,
+        elapsed: Float,
+    },
+    /// Moving forward over the obstacle.
+    Crossing {
+        start: Vector3,
+        end: Vector3,
+        elapsed: Float,
+    },
+}
+
+impl TnuaBuiltinVaultState {
+    /// The time, in seconds, since the vault started.
+    pub fn elapsed(&self) -> Float {
+        match self {
+            TnuaBuiltinVaultState::PreVault => 0.0,
+            TnuaBuiltinVaultState::Rising { elapsed, .. }
+            | TnuaBuiltinVaultState::Crossing { elapsed, .. } => *elapsed,
+        }
+    }
+
+    /// How much of the vault is done, between `0.0` and `1.0`, given the
+    /// [`duration`](TnuaBuiltinVault::duration) of the action.
+    pub fn progress(&self, duration: Float) -> Float {
+        if 0.0 < duration {
+            (self.elapsed() / duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
@@ -1,11 +1,13 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::math::{float_consts, AdjustPrecision, AsF32, Float, Quaternion, Vector3};
 use bevy::prelude::*;
 
+use crate::motion_math;
 use crate::util::rotation_arc_around_axis;
-use crate::TnuaBasisContext;
 use crate::{TnuaBasis, TnuaVelChange};
+use crate::{TnuaBasisContext, TnuaSurfaceProperties};
 
 /// The most common [basis](TnuaBasis) - walk around as a floating capsule.
 ///
@@ -19,7 +21,9 @@ use crate::{TnuaBasis, TnuaVelChange};
 /// * [`desired_velocity`](Self::desired_velocity) - while leaving this as as the default
 ///   `Vector3::ZERO`, doing so would mean that the character will not move.
 /// * [`desired_forward`](Self::desired_forward) - leaving this is the default `None` will mean
-///   that Tnua will not attempt to fix the character's rotation along the up axis.
+///   that Tnua will not attempt to fix the character's rotation along the up axis (unless
+///   [`rotation_mode`](Self::rotation_mode) is set to
+///   [`FaceMovementDirection`](TnuaWalkRotationMode::FaceMovementDirection)).
 ///
 ///   This is fine if rotation along the up axis is locked (Rapier and Avian only support locking
 ///   cardinal axes, but the up direction is based on the gravity which means it defaults to the Y
@@ -62,6 +66,40 @@ pub struct TnuaBuiltinWalk {
     /// above the `float_height`, Tnua will consider it to be in the air.
     pub cling_distance: Float,
 
+    /// Extra distance, beyond [`cling_distance`](Self::cling_distance), for snapping the character
+    /// back to the ground.
+    ///
+    /// When the character stands on the ground and loses contact with it - e.g. when running down
+    /// a slope or over a small bump - and the ground is still within this distance below the
+    /// cling range, the spring will keep pulling the character down instead of letting it become
+    /// airborne. Unlike `cling_distance`, this only extends the range while the character is
+    /// already grounded, and does not apply right after an action that
+    /// [violates coyote time](crate::TnuaAction::VIOLATES_COYOTE_TIME) (like a jump) so it will
+    /// not pull the character back when it jumps.
+    ///
+    /// Set to 0.0 (the default) to disable snapping.
+    pub snap_to_ground_distance: Float,
+
+    /// Extra distance, beyond [`snap_to_ground_distance`](Self::snap_to_ground_distance), for
+    /// keeping the character grounded when it loses contact with the ground due to the terrain's
+    /// curvature (e.g. when running over a crest) and would fall back to it within
+    /// [`short_hop_max_time`](Self::short_hop_max_time).
+    ///
+    /// This prevents a short flight (and the flicker of the falling animation) on rolling
+    /// terrain, while still letting the character become airborne when running off a real ledge.
+    /// Like snapping, it only applies while the character is grounded and not right after an
+    /// action that [violates coyote time](crate::TnuaAction::VIOLATES_COYOTE_TIME).
+    ///
+    /// Set to 0.0 (the default) to disable short hop suppression.
+    pub short_hop_max_distance: Float,
+
+    /// The maximal time, in seconds, the character would have been airborne for
+    /// [`short_hop_max_distance`](Self::short_hop_max_distance) to keep it on the ground.
+    ///
+    /// The time is estimated from the gravity, the vertical velocity of the character and the
+    /// distance to the ground.
+    pub short_hop_max_time: Float,
+
     /// The force that pushes the character to the float height.
     ///
     /// The actual force applied is in direct linear relationship to the displacement from the
@@ -84,12 +122,34 @@ pub struct TnuaBuiltinWalk {
     /// `acceleration` when doing a 180 turn.
     pub acceleration: Float,
 
+    /// Scales [`acceleration`](Self::acceleration) according to how close the character is to
+    /// the desired speed.
+    pub acceleration_curve: TnuaAccelerationCurve,
+
     /// The acceleration for horizontal movement while in the air.
     ///
     /// Set to 0.0 to completely disable air movement.
     pub air_acceleration: Float,
 
+    /// Scales [`air_acceleration`](Self::air_acceleration) according to how close the character
+    /// is to the desired speed.
+    pub air_acceleration_curve: TnuaAccelerationCurve,
+
+    /// How the directional input affects the velocity while the character is airborne.
+    pub air_control: TnuaAirControl,
+
+    /// Keep the horizontal velocity the character had when it left the ground if there is no
+    /// directional input while airborne, instead of braking to a stop.
+    ///
+    /// This prevents jump arcs from being cut short when the player releases the stick midair.
+    /// Directional input still changes the velocity according to
+    /// [`air_control`](Self::air_control).
+    pub preserve_launch_momentum: bool,
+
     /// The time, in seconds, the character can still jump after losing their footing.
+    ///
+    /// Can be modified per surface with
+    /// [`TnuaSurfaceProperties::coyote_time_multiplier`].
     pub coyote_time: Float,
 
     /// Extra gravity for free fall (fall that's not initiated by a jump or some other action that
@@ -102,6 +162,16 @@ pub struct TnuaBuiltinWalk {
     /// the jump button.
     pub free_fall_extra_gravity: Float,
 
+    /// The maximum speed of falling while the character is airborne.
+    ///
+    /// The downward velocity is clamped to it, after the gravity and the
+    /// [`free_fall_extra_gravity`](Self::free_fall_extra_gravity). Limiting it also helps the
+    /// proximity sensor detect the ground before the character passes through it.
+    ///
+    /// Note that actions (like [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump)) control
+    /// the vertical velocity by themselves, and have their own limit.
+    pub max_fall_speed: Float,
+
     /// The maximum angular velocity used for keeping the character standing upright.
     ///
     /// NOTE: The character's rotation can also be locked to prevent it from being tilted, in which
@@ -114,11 +184,36 @@ pub struct TnuaBuiltinWalk {
     /// case this paramter is redundant and can be set to 0.0.
     pub tilt_offset_angacl: Float,
 
+    /// Which direction the character turns to face.
+    pub rotation_mode: TnuaWalkRotationMode,
+
     /// The maximum angular velocity used for turning the character when the direction changes.
     pub turning_angvel: Float,
 
+    /// The maximum angular acceleration used for reaching [`turning_angvel`](Self::turning_angvel).
+    ///
+    /// Set to infinity to start and stop turning instantly.
+    pub turning_angacl: Float,
+
+    /// When the character needs to turn by more than this angle (in radians) while standing on the
+    /// ground, it will stop and turn in place before it starts moving.
+    ///
+    /// While this happens, [`TnuaBuiltinWalkState::turning_in_place`] is `true` - which can be
+    /// used for playing a pivot animation.
+    ///
+    /// Set to infinity to never turn in place. Ignored in [`TnuaWalkRotationMode::Strafe`].
+    pub turn_in_place_threshold: Float,
+
     /// The maximum slope, in radians, that the character can stand on without slipping.
     pub max_slope: Float,
+
+    /// The portion of the falling speed to convert into horizontal speed when landing on a slope.
+    ///
+    /// At `1.0`, the falling velocity is projected on the slope as if the character slid along
+    /// it, so landing on a hill will keep the downhill momentum instead of stopping abruptly. At
+    /// `0.0` (the default) the float spring absorbs all the falling speed. Has no effect when
+    /// landing on flat ground.
+    pub landing_slope_conversion: Float,
 }
 
 impl Default for TnuaBuiltinWalk {
@@ -128,16 +223,28 @@ impl Default for TnuaBuiltinWalk {
             desired_forward: None,
             float_height: 0.0,
             cling_distance: 1.0,
+            snap_to_ground_distance: 0.0,
+            short_hop_max_distance: 0.0,
+            short_hop_max_time: 0.2,
             spring_strengh: 400.0,
             spring_dampening: 1.2,
             acceleration: 60.0,
+            acceleration_curve: TnuaAccelerationCurve::Flat,
             air_acceleration: 20.0,
+            air_acceleration_curve: TnuaAccelerationCurve::Flat,
+            air_control: TnuaAirControl::Full,
+            preserve_launch_momentum: false,
             coyote_time: 0.15,
             free_fall_extra_gravity: 60.0,
+            max_fall_speed: Float::INFINITY,
             tilt_offset_angvel: 5.0,
             tilt_offset_angacl: 500.0,
+            rotation_mode: TnuaWalkRotationMode::FaceDesiredForward,
             turning_angvel: 10.0,
+            turning_angacl: Float::INFINITY,
+            turn_in_place_threshold: Float::INFINITY,
             max_slope: float_consts::FRAC_PI_2,
+            landing_slope_conversion: 0.0,
         }
     }
 }
@@ -150,14 +257,51 @@ impl TnuaBasis for TnuaBuiltinWalk {
         if let Some(stopwatch) = &mut state.airborne_timer {
             #[allow(clippy::unnecessary_cast)]
             stopwatch.tick(Duration::from_secs_f64(ctx.frame_duration as f64));
+            state.airborne_duration += ctx.frame_duration;
         }
 
+        // Snapping only makes sense when the character was on the ground, and should not undo
+        // actions like jumps that took it off the ground.
+        let snapping_allowed =
+            !std::mem::take(&mut state.snapping_suppressed) && state.airborne_timer.is_none();
+        let sensor_output = ctx
+            .proximity_sensor
+            .output
+            .as_ref()
+            .filter(|sensor_output| {
+                let proximity = sensor_output.proximity.adjust_precision();
+                let cling_limit = self.float_height + self.cling_distance;
+                if proximity <= cling_limit {
+                    return true;
+                }
+                if !snapping_allowed {
+                    return false;
+                }
+                let snap_limit = cling_limit + self.snap_to_ground_distance;
+                if proximity <= snap_limit {
+                    return true;
+                }
+                // Short hop suppression - only keep the character grounded if it would fall back
+                // quickly anyway.
+                let gravity = ctx.tracker.gravity.length();
+                if gravity <= 0.0 {
+                    return false;
+                }
+                let gap = proximity - self.float_height;
+                let upward_velocity = (ctx.tracker.velocity - sensor_output.entity_linvel)
+                    .dot(ctx.up_direction.adjust_precision());
+                let time_to_land = (upward_velocity
+                    + (upward_velocity.powi(2) + 2.0 * gravity * gap).sqrt())
+                    / gravity;
+                time_to_land <= self.short_hop_max_time
+            });
+
         let climb_vectors: Option<ClimbVectors>;
         let considered_in_air: bool;
         let impulse_to_offset: Vector3;
         let slipping_vector: Option<Vector3>;
 
-        if let Some(sensor_output) = &ctx.proximity_sensor.output {
+        if let Some(sensor_output) = sensor_output {
             state.effective_velocity = ctx.tracker.velocity - sensor_output.entity_linvel;
             let sideways_unnormalized = sensor_output
                 .normal
@@ -214,6 +358,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
                         entity: sensor_output.entity,
                         entity_linvel: sensor_output.entity_linvel,
                     });
+                    state.last_surface_properties = ctx.surface_properties.cloned();
                 } else {
                     considered_in_air = true;
                     state.standing_on = None;
@@ -229,24 +374,79 @@ impl TnuaBasis for TnuaBuiltinWalk {
         }
         state.effective_velocity += impulse_to_offset;
 
+        let forward_to_face = self.forward_to_face();
+        let previous_facing = state.facing;
+        if let Some(forward_to_face) = forward_to_face {
+            state.facing = Some(forward_to_face);
+        }
+        state.just_turned_around = match (previous_facing, forward_to_face) {
+            (Some(previous_facing), Some(forward_to_face)) => {
+                previous_facing.dot(*forward_to_face) < 0.0
+            }
+            _ => false,
+        };
+
+        let rotation_along_up_axis = forward_to_face
+            .filter(|_| ctx.root_motion.is_none())
+            .filter(|_| self.rotation_mode != TnuaWalkRotationMode::Flip)
+            .and_then(|forward_to_face| {
+                rotation_arc_around_axis(
+                    ctx.up_direction,
+                    ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z),
+                    forward_to_face.adjust_precision(),
+                )
+            })
+            .unwrap_or(0.0);
+
+        state.turning_in_place = !considered_in_air
+            && !matches!(self.rotation_mode, TnuaWalkRotationMode::Strafe)
+            && self.turn_in_place_threshold < rotation_along_up_axis.abs();
         let velocity_on_plane = state
             .effective_velocity
             .reject_from(ctx.up_direction.adjust_precision());
 
-        let desired_boost = self.desired_velocity - velocity_on_plane;
+        let desired_velocity = if let Some(root_motion) = &ctx.root_motion {
+            root_motion
+                .velocity
+                .reject_from(ctx.up_direction.adjust_precision())
+        } else if state.turning_in_place {
+            Vector3::ZERO
+        } else {
+            self.desired_velocity
+        } + ctx
+            .external_velocity
+            .reject_from(ctx.up_direction.adjust_precision());
+
+        let desired_velocity = if state.airborne_timer.is_none() {
+            desired_velocity
+        } else if self.preserve_launch_momentum && self.desired_velocity == Vector3::ZERO {
+            velocity_on_plane
+        } else {
+            self.air_control
+                .desired_velocity(velocity_on_plane, desired_velocity)
+        };
 
-        let safe_direction_coefficient = self
-            .desired_velocity
-            .normalize_or_zero()
-            .dot(velocity_on_plane.normalize_or_zero());
-        let direction_change_factor = 1.5 - 0.5 * safe_direction_coefficient;
+        let desired_boost = desired_velocity - velocity_on_plane;
 
+        let desired_speed = desired_velocity.length();
+        let speed_ratio = if 0.0 < desired_speed {
+            (velocity_on_plane.dot(desired_velocity) / desired_speed.powi(2)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
         let relevant_acceleration_limit = if considered_in_air {
-            self.air_acceleration
+            self.air_acceleration * self.air_acceleration_curve.multiplier(speed_ratio)
+        } else if ctx.root_motion.is_some() {
+            // Root motion is followed exactly.
+            Float::INFINITY
         } else {
-            self.acceleration
+            self.acceleration * self.acceleration_curve.multiplier(speed_ratio)
         };
-        let max_acceleration = direction_change_factor * relevant_acceleration_limit;
+        let max_acceleration = motion_math::velocity_matching_max_acceleration(
+            velocity_on_plane,
+            desired_velocity,
+            relevant_acceleration_limit,
+        );
 
         state.vertical_velocity = if let Some(climb_vectors) = &climb_vectors {
             state.effective_velocity.dot(climb_vectors.direction)
@@ -257,8 +457,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
             0.0
         };
 
-        let walk_vel_change = if self.desired_velocity == Vector3::ZERO && slipping_vector.is_none()
-        {
+        let walk_vel_change = if desired_velocity == Vector3::ZERO && slipping_vector.is_none() {
             // When stopping, prefer a boost to be able to reach a precise stop (see issue #39)
             let walk_boost = desired_boost.clamp_length_max(ctx.frame_duration * max_acceleration);
             let walk_boost = if let Some(climb_vectors) = &climb_vectors {
@@ -317,6 +516,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
             }
         };
 
+        let mut landing_boost = Vector3::ZERO;
         let upward_impulse: TnuaVelChange = 'upward_impulse: {
             let should_disable_due_to_slipping =
                 slipping_vector.is_some() && state.vertical_velocity <= 0.0;
@@ -325,28 +525,48 @@ impl TnuaBasis for TnuaBuiltinWalk {
                 match &mut state.airborne_timer {
                     None => {
                         if let (false, Some(sensor_output)) =
-                            (should_disable_due_to_slipping, &ctx.proximity_sensor.output)
+                            (should_disable_due_to_slipping, sensor_output)
                         {
                             // not doing the jump calculation here
                             let spring_offset =
                                 self.float_height - sensor_output.proximity.adjust_precision();
                             state.standing_offset =
                                 -spring_offset * ctx.up_direction.adjust_precision();
+                            state.float_offset = -spring_offset;
                             break 'upward_impulse self.spring_force(state, &ctx, spring_offset);
                         } else {
+                            // The surface the character was standing on, not the one it is
+                            // currently above (if any)
+                            let coyote_time_multiplier = state
+                                .last_surface_properties
+                                .as_ref()
+                                .map_or(1.0, |surface| surface.coyote_time_multiplier);
                             state.airborne_timer = Some(Timer::from_seconds(
-                                self.coyote_time as f32,
+                                (self.coyote_time * coyote_time_multiplier).max(0.0) as f32,
                                 TimerMode::Once,
                             ));
+                            state.airborne_duration = 0.0;
                             continue;
                         }
                     }
                     Some(_) => {
                         if let (false, Some(sensor_output)) =
-                            (should_disable_due_to_slipping, &ctx.proximity_sensor.output)
+                            (should_disable_due_to_slipping, sensor_output)
                         {
                             if sensor_output.proximity.adjust_precision() <= self.float_height {
                                 state.airborne_timer = None;
+                                state.airborne_duration = 0.0;
+                                let up = ctx.up_direction.adjust_precision();
+                                let fall_speed = -state.effective_velocity.dot(up);
+                                if 0.0 < fall_speed && 0.0 < self.landing_slope_conversion {
+                                    let normal = sensor_output.normal.adjust_precision();
+                                    // The horizontal part of the fall velocity projected on the
+                                    // slope.
+                                    landing_boost = self.landing_slope_conversion
+                                        * fall_speed
+                                        * normal.dot(up)
+                                        * normal.reject_from(up);
+                                }
                                 continue;
                             }
                         }
@@ -364,7 +584,19 @@ impl TnuaBasis for TnuaBuiltinWalk {
             TnuaVelChange::ZERO
         };
 
-        motor.lin = walk_vel_change + TnuaVelChange::boost(impulse_to_offset) + upward_impulse;
+        motor.lin = walk_vel_change
+            + TnuaVelChange::boost(impulse_to_offset + landing_boost)
+            + upward_impulse;
+        if state.airborne_timer.is_some() {
+            let up = ctx.up_direction.adjust_precision();
+            motor.lin.boost += up
+                * motion_math::fall_speed_limit_boost(
+                    (ctx.tracker.velocity + motor.lin.boost).dot(up),
+                    (ctx.tracker.gravity + motor.lin.acceleration).dot(up),
+                    self.max_fall_speed,
+                    ctx.frame_duration,
+                );
+        }
         let new_velocity = state.effective_velocity
             + motor.lin.boost
             + ctx.frame_duration * motor.lin.acceleration
@@ -387,26 +619,21 @@ impl TnuaBasis for TnuaBuiltinWalk {
 
         // Turning
 
-        let desired_angvel = if let Some(desired_forward) = self.desired_forward {
-            let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
-            let rotation_along_up_axis = rotation_arc_around_axis(
-                ctx.up_direction,
-                current_forward,
-                desired_forward.adjust_precision(),
-            )
-            .unwrap_or(0.0);
+        let desired_angvel = if let Some(root_motion) = &ctx.root_motion {
+            root_motion.angvel.dot(ctx.up_direction.adjust_precision())
+        } else {
             (rotation_along_up_axis / ctx.frame_duration)
                 .clamp(-self.turning_angvel, self.turning_angvel)
-        } else {
-            0.0
         };
 
         // NOTE: This is the regular axis system so we used the configured up.
         let existing_angvel = ctx.tracker.angvel.dot(ctx.up_direction.adjust_precision());
 
-        // This is the torque. Should it be clamped by an acceleration? From experimenting with
-        // this I think it's meaningless and only causes bugs.
-        let torque_to_turn = desired_angvel - existing_angvel;
+        // This is the torque. It is not clamped by default - from experimenting with this I think
+        // it's mostly meaningless - but some games want the turning to ramp up.
+        let max_turn_change = ctx.frame_duration * self.turning_angacl;
+        let torque_to_turn =
+            (desired_angvel - existing_angvel).clamp(-max_turn_change, max_turn_change);
 
         let existing_turn_torque = torque_to_fix_tilt.dot(ctx.up_direction.adjust_precision());
         let torque_to_turn = torque_to_turn - existing_turn_torque;
@@ -416,8 +643,13 @@ impl TnuaBasis for TnuaBuiltinWalk {
         );
     }
 
-    fn proximity_sensor_cast_range(&self, _state: &Self::State) -> Float {
-        self.float_height + self.cling_distance
+    fn proximity_sensor_cast_range(&self, state: &Self::State) -> Float {
+        let cast_range = self.float_height + self.cling_distance;
+        if state.airborne_timer.is_none() {
+            cast_range + self.snap_to_ground_distance + self.short_hop_max_distance
+        } else {
+            cast_range
+        }
     }
 
     fn displacement(&self, state: &Self::State) -> Option<Vector3> {
@@ -448,13 +680,45 @@ impl TnuaBasis for TnuaBuiltinWalk {
     }
 
     fn violate_coyote_time(&self, state: &mut Self::State) {
+        state.snapping_suppressed = true;
         if let Some(timer) = &mut state.airborne_timer {
             timer.set_duration(Duration::ZERO);
         }
     }
+
+    fn airborne_duration(&self, state: &Self::State) -> Option<Float> {
+        Some(state.airborne_duration)
+    }
+
+    fn float_offset(&self, state: &Self::State) -> Option<Float> {
+        match state.airborne_timer {
+            None => Some(state.float_offset),
+            Some(_) => None,
+        }
+    }
+
+    fn running_velocity(&self, state: &Self::State) -> Option<Vector3> {
+        Some(state.running_velocity)
+    }
 }
 
 impl TnuaBuiltinWalk {
+    /// The direction the character should turn to face, according to the
+    /// [`rotation_mode`](Self::rotation_mode).
+    pub fn forward_to_face(&self) -> Option<Dir3> {
+        match self.rotation_mode {
+            TnuaWalkRotationMode::FaceDesiredForward | TnuaWalkRotationMode::Strafe => {
+                self.desired_forward
+            }
+            TnuaWalkRotationMode::FaceMovementDirection => Dir3::new(self.desired_velocity.f32())
+                .ok()
+                .or(self.desired_forward),
+            TnuaWalkRotationMode::Flip => self
+                .desired_forward
+                .or_else(|| Dir3::new(self.desired_velocity.f32()).ok()),
+        }
+    }
+
     /// Calculate the vertical spring force that this basis would need to apply assuming its
     /// vertical distance from the vertical distance it needs to be at equals the `spring_offset`
     /// argument.
@@ -467,20 +731,143 @@ impl TnuaBuiltinWalk {
         ctx: &TnuaBasisContext,
         spring_offset: Float,
     ) -> TnuaVelChange {
-        let spring_force: Float = spring_offset * self.spring_strengh;
+        let up = ctx.up_direction.adjust_precision();
 
-        let relative_velocity = state
-            .effective_velocity
-            .dot(ctx.up_direction.adjust_precision())
-            - state.vertical_velocity;
-
-        let gravity_compensation = -ctx.tracker.gravity;
+        let relative_velocity = state.effective_velocity.dot(up) - state.vertical_velocity;
 
-        let dampening_boost = relative_velocity * self.spring_dampening;
+        // Only compensate for the gravity along the up axis here - the rest of the gravity vector
+        // (if there is some) is compensated right after.
+        let gravity = -ctx.tracker.gravity.dot(up);
+        let gravity_compensation = -ctx.tracker.gravity.reject_from(up);
 
         TnuaVelChange {
-            acceleration: ctx.up_direction.adjust_precision() * spring_force + gravity_compensation,
-            boost: ctx.up_direction.adjust_precision() * -dampening_boost,
+            acceleration: up
+                * motion_math::float_spring_acceleration(
+                    spring_offset,
+                    self.spring_strengh,
+                    gravity,
+                )
+                + gravity_compensation,
+            boost: up
+                * motion_math::float_spring_dampening_boost(
+                    relative_velocity,
+                    self.spring_dampening,
+                ),
+        }
+    }
+}
+
+/// Determines which direction [`TnuaBuiltinWalk`] turns the character to face.
+///
+/// In all modes, when there is no direction to face the character will keep its current facing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TnuaWalkRotationMode {
+    /// Face [`desired_velocity`](TnuaBuiltinWalk::desired_velocity). When standing still, face
+    /// [`desired_forward`](TnuaBuiltinWalk::desired_forward) (if set).
+    FaceMovementDirection,
+    /// Face [`desired_forward`](TnuaBuiltinWalk::desired_forward).
+    #[default]
+    FaceDesiredForward,
+    /// Face [`desired_forward`](TnuaBuiltinWalk::desired_forward), and never stop moving to turn
+    /// in place. Meant for games where the facing is decoupled from the movement - e.g. when the
+    /// character faces where the camera aims while moving sideways or backward.
+    Strafe,
+    /// Do not rotate the character at all - only track the direction it faces in
+    /// [`TnuaBuiltinWalkState::facing`].
+    ///
+    /// The direction is taken from [`desired_forward`](TnuaBuiltinWalk::desired_forward), or from
+    /// [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) when `desired_forward` is not set.
+    ///
+    /// Meant for 2D games, where turning around is done by flipping the sprite rather than by
+    /// rotating the physical body. See [`TnuaBuiltinWalkState::facing_sign`].
+    Flip,
+}
+
+/// Determines how the directional input of [`TnuaBuiltinWalk`] affects the velocity while the
+/// character is airborne.
+///
+/// Regardless of the mode, the velocity changes at a rate limited by
+/// [`air_acceleration`](TnuaBuiltinWalk::air_acceleration).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TnuaAirControl {
+    /// Steer toward [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) like on the ground -
+    /// including changing the direction of the velocity and slowing down.
+    #[default]
+    Full,
+    /// Only accelerate in the direction of the input, up to the desired speed in that direction.
+    /// The velocity in other directions is kept, and the character never slows down.
+    Additive,
+    /// Ignore the directional input, keeping the horizontal velocity the character had when it
+    /// left the ground.
+    Disabled,
+}
+
+impl TnuaAirControl {
+    /// The horizontal velocity to steer toward while airborne.
+    pub fn desired_velocity(&self, current_velocity: Vector3, input_velocity: Vector3) -> Vector3 {
+        match self {
+            TnuaAirControl::Full => input_velocity,
+            TnuaAirControl::Additive => {
+                let input_speed = input_velocity.length();
+                let input_direction = input_velocity.normalize_or_zero();
+                let speed_in_input_direction = current_velocity.dot(input_direction);
+                if speed_in_input_direction < input_speed {
+                    current_velocity + (input_speed - speed_in_input_direction) * input_direction
+                } else {
+                    current_velocity
+                }
+            }
+            TnuaAirControl::Disabled => current_velocity,
+        }
+    }
+}
+
+/// A multiplier for the acceleration of [`TnuaBuiltinWalk`], as a function of the speed ratio.
+///
+/// The speed ratio is the velocity of the character in the direction of
+/// [`desired_velocity`](TnuaBuiltinWalk::desired_velocity), divided by the desired speed and
+/// clamped to the `0.0..=1.0` range. When the desired velocity is zero (the character is braking
+/// to a stop) the speed ratio is `0.0`.
+///
+/// ```
+/// # use bevy_tnua::builtins::{TnuaAccelerationCurve, TnuaBuiltinWalk};
+/// // Accelerate fast from standing still, and taper off near full speed.
+/// let walk = TnuaBuiltinWalk {
+///     acceleration: 60.0,
+///     acceleration_curve: TnuaAccelerationCurve::Linear {
+///         at_rest: 1.5,
+///         at_full_speed: 0.5,
+///     },
+///     ..Default::default()
+/// };
+/// assert_eq!(walk.acceleration_curve.multiplier(0.5), 1.0);
+/// ```
+#[derive(Clone, Default)]
+pub enum TnuaAccelerationCurve {
+    /// Always `1.0` - use the acceleration as is.
+    #[default]
+    Flat,
+    /// Interpolate linearly between two multipliers.
+    Linear {
+        /// The multiplier when the speed ratio is `0.0`.
+        at_rest: Float,
+        /// The multiplier when the speed ratio is `1.0`.
+        at_full_speed: Float,
+    },
+    /// Call a function with the speed ratio.
+    Custom(Arc<dyn Send + Sync + Fn(Float) -> Float>),
+}
+
+impl TnuaAccelerationCurve {
+    /// The multiplier for the given speed ratio.
+    pub fn multiplier(&self, speed_ratio: Float) -> Float {
+        match self {
+            TnuaAccelerationCurve::Flat => 1.0,
+            TnuaAccelerationCurve::Linear {
+                at_rest,
+                at_full_speed,
+            } => at_rest + (at_full_speed - at_rest) * speed_ratio,
+            TnuaAccelerationCurve::Custom(curve) => curve(speed_ratio),
         }
     }
 }
@@ -494,9 +881,13 @@ struct StandingOnState {
 #[derive(Default)]
 pub struct TnuaBuiltinWalkState {
     airborne_timer: Option<Timer>,
+    airborne_duration: Float,
+    float_offset: Float,
     /// The current distance of the character from the distance its supposed to float at.
     pub standing_offset: Vector3,
     standing_on: Option<StandingOnState>,
+    last_surface_properties: Option<TnuaSurfaceProperties>,
+    snapping_suppressed: bool,
     effective_velocity: Vector3,
     vertical_velocity: Float,
     /// The velocity, perpendicular to the up direction, that the character is supposed to move at.
@@ -505,6 +896,16 @@ pub struct TnuaBuiltinWalkState {
     /// ([`standing_on_entity`](Self::standing_on_entity) returns `Some`) then the
     /// `running_velocity` will be relative to the velocity of that entity.
     pub running_velocity: Vector3,
+    /// Whether the character stopped to turn in place because it needs to turn more than
+    /// [`turn_in_place_threshold`](TnuaBuiltinWalk::turn_in_place_threshold).
+    pub turning_in_place: bool,
+    /// The direction the character faces (or turns to face). Retains its last value when there is
+    /// no direction to face.
+    pub facing: Option<Dir3>,
+    /// Whether [`facing`](Self::facing) was reversed this frame (changed by more than 90 degrees).
+    ///
+    /// Can be used for playing a turn-around animation or for flipping a sprite.
+    pub just_turned_around: bool,
 }
 
 impl TnuaBuiltinWalkState {
@@ -512,6 +913,23 @@ impl TnuaBuiltinWalkState {
     pub fn standing_on_entity(&self) -> Option<Entity> {
         Some(self.standing_on.as_ref()?.entity)
     }
+
+    /// The signed direction, along `axis`, that the character faces.
+    ///
+    /// Returns `1.0` when the character faces toward `axis`, `-1.0` when it faces away from it, and
+    /// `0.0` when its facing is unknown or perpendicular to `axis`. For 2D side view games, use
+    /// `Dir3::X` and flip the sprite when this is negative.
+    pub fn facing_sign(&self, axis: Dir3) -> Float {
+        let Some(facing) = self.facing else {
+            return 0.0;
+        };
+        let projection = facing.dot(*axis);
+        if projection == 0.0 {
+            0.0
+        } else {
+            projection.signum().adjust_precision()
+        }
+    }
 }
 
 struct ClimbVectors {
@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+
+use crate::controller::TnuaController;
+use crate::math::{Float, Vector3};
+use crate::stage_tracing::TnuaStageTrace;
+use crate::{
+    TnuaGhostSensor, TnuaMotor, TnuaPipelineStages, TnuaProximitySensor, TnuaProximitySensorOutput,
+    TnuaRigidBodyTracker, TnuaToggle, TnuaUpdateRate,
+};
+
+/// Makes a character feel the weight of other characters that stand on it.
+///
+/// Characters that stand on another character (any entity with a
+/// [`TnuaController`](crate::prelude::TnuaController)) move with it like they would on a moving
+/// platform. Without this component, the character below does not notice them at all - its float
+/// spring carries them at no cost. With it, each character standing on it pushes it down with
+/// [`rider_weight`](Self::rider_weight), so its spring sinks a little under the load.
+///
+/// The weight is constant and does not depend on the float spring of the rider, so the two springs
+/// do not feed into each other. Only characters standing directly on this character are counted -
+/// in a stack of three characters, the bottom one only feels the weight of the middle one.
+#[derive(Component, Clone, Debug)]
+pub struct TnuaCarrier {
+    /// The acceleration, as a fraction of the gravity, that each character standing on this
+    /// character adds to it.
+    ///
+    /// A value of `1.0` means a rider weighs as much as the carrier itself.
+    pub rider_weight: Float,
+}
+
+impl Default for TnuaCarrier {
+    fn default() -> Self {
+        Self { rider_weight: 0.5 }
+    }
+}
+
+/// Stops a character from moving with the characters it stands on.
+///
+/// By default, a character standing on another character inherits its horizontal motion and its
+/// rotation, like it would on a moving platform. A character with this component stands on other
+/// characters like it would on static ground - so it stays in place when they walk away from under
+/// it.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TnuaNotCarried;
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_carried_sensors_system(
+    mut query: Query<(
+        Entity,
+        &TnuaRigidBodyTracker,
+        &mut TnuaProximitySensor,
+        Option<&mut TnuaGhostSensor>,
+        Has<TnuaNotCarried>,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+    characters_query: Query<(), With<TnuaController>>,
+) {
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::SubservientSensors);
+    query.par_iter_mut().for_each(
+        |(entity, tracker, mut sensor, ghost_sensor, not_carried, tnua_toggle, update_rate)| {
+            let _span = stage_trace.character(entity);
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            // If the sensor was not cast this frame, its output was already adjusted.
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            let up = (-tracker.gravity).try_normalize().unwrap_or(Vector3::Y);
+            let is_character =
+                |output: &TnuaProximitySensorOutput| characters_query.contains(output.entity);
+            let adjust = |output: &mut TnuaProximitySensorOutput| {
+                if !is_character(output) {
+                    return;
+                }
+                if not_carried {
+                    output.entity_linvel = Vector3::ZERO;
+                    output.entity_angvel = Vector3::ZERO;
+                } else {
+                    // The vertical velocity of the character below comes mostly from its own
+                    // float spring. Following it would make the float spring of this character
+                    // chase it - and the two springs would oscillate. The float spring tracks the
+                    // height of the character below anyway.
+                    output.entity_linvel = output.entity_linvel.reject_from(up);
+                }
+            };
+            // Only access the sensors mutably when there is something to adjust, so that their
+            // change detection is not triggered for every character on every frame.
+            if sensor.output.as_ref().is_some_and(is_character) {
+                if let Some(output) = sensor.output.as_mut() {
+                    adjust(output);
+                }
+            }
+            if let Some(mut ghost_sensor) = ghost_sensor {
+                if ghost_sensor.0.iter().any(is_character) {
+                    for output in ghost_sensor.0.iter_mut() {
+                        adjust(output);
+                    }
+                }
+            }
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_carried_weight_system(
+    riders_query: Query<(&TnuaController, &TnuaProximitySensor, Option<&TnuaToggle>)>,
+    mut carriers_query: Query<(
+        &TnuaCarrier,
+        &TnuaRigidBodyTracker,
+        &mut TnuaMotor,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+) {
+    if carriers_query.is_empty() {
+        return;
+    }
+    for (controller, sensor, tnua_toggle) in riders_query.iter() {
+        match tnua_toggle.copied().unwrap_or_default() {
+            TnuaToggle::Disabled => continue,
+            TnuaToggle::SenseOnly => {}
+            TnuaToggle::Enabled => {}
+        }
+        if controller.is_airborne().unwrap_or(true) {
+            continue;
+        }
+        let Some(output) = sensor.output.as_ref() else {
+            continue;
+        };
+        let Ok((carrier, carrier_tracker, mut carrier_motor, carrier_toggle, carrier_update_rate)) =
+            carriers_query.get_mut(output.entity)
+        else {
+            continue;
+        };
+        match carrier_toggle.copied().unwrap_or_default() {
+            TnuaToggle::Disabled | TnuaToggle::SenseOnly => continue,
+            TnuaToggle::Enabled => {}
+        }
+        // The motor of the carrier is only recalculated on the frames it is updated - adding the
+        // weight on other frames would accumulate it.
+        if carrier_update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+            continue;
+        }
+        carrier_motor.lin.acceleration += carrier.rider_weight * carrier_tracker.gravity;
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::math::Float;
+use crate::prelude::*;
+
+/// Declarative conditions for starting an action, checked by [`TnuaActionGate`] before it feeds
+/// the action.
+///
+/// The [`Default`] has no requirements.
+#[derive(Clone, Debug, Default)]
+pub struct TnuaActionRequirements {
+    /// If set, the action can only start when the character is grounded (`Some(true)`) or only
+    /// when it is airborne (`Some(false)`).
+    ///
+    /// This uses [`TnuaController::is_airborne`], so coyote time counts as grounded.
+    pub grounded: Option<bool>,
+
+    /// The minimal time, in seconds, the character must be airborne before the action can start
+    /// (see [`TnuaController::airborne_duration`]).
+    pub min_air_time: Float,
+
+    /// The action can only start when the gate was told about a wall contact (see
+    /// [`set_wall_contact`](TnuaActionGate::set_wall_contact)).
+    pub requires_wall_contact: bool,
+
+    /// The cooldown group of the action, and the cooldown duration in seconds.
+    ///
+    /// When the action starts, all the actions in the same group cannot start for that duration -
+    /// e.g. to prevent chaining a dash into a lunge.
+    pub cooldown: Option<(&'static str, Float)>,
+}
+
+/// Why [`TnuaActionGate`] did not feed an action.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum TnuaActionRequirementFailure {
+    #[error("The action requires the character to be grounded")]
+    NotGrounded,
+    #[error("The action requires the character to be airborne")]
+    NotAirborne,
+    #[error("The character has not been airborne long enough for the action")]
+    NotEnoughAirTime,
+    #[error("The action requires a wall contact")]
+    NoWallContact,
+    #[error("The cooldown group {group:?} is cooling down for {remaining} more seconds")]
+    CoolingDown {
+        group: &'static str,
+        remaining: Float,
+    },
+}
+
+/// Feeds actions only when their [`TnuaActionRequirements`] are met, and manages cooldown groups
+/// that are shared between actions.
+///
+/// Its [`update`](Self::update) must be called every frame, before feeding the actions through
+/// it:
+///
+/// ```no_run
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::builtins::TnuaBuiltinDash;
+/// # use bevy_tnua::control_helpers::{TnuaActionGate, TnuaActionRequirements};
+/// # use bevy_tnua::math::Vector3;
+/// # let mut controller = TnuaController::default();
+/// # let mut gate = TnuaActionGate::default();
+/// # let (frame_duration, dash_pressed) = (1.0 / 60.0, true);
+/// gate.update(&controller, frame_duration);
+/// if dash_pressed {
+///     let _ = gate.action(
+///         &mut controller,
+///         &TnuaActionRequirements {
+///             grounded: Some(true),
+///             cooldown: Some(("dash", 0.5)),
+///             ..Default::default()
+///         },
+///         TnuaBuiltinDash {
+///             displacement: Vector3::X * 5.0,
+///             ..Default::default()
+///         },
+///     );
+/// }
+/// ```
+///
+/// Note that the requirements are only checked before the action starts. Once the action is
+/// running it is fed regardless of the requirements, so that e.g. a jump that requires the
+/// character to be grounded will not be cut off when it leaves the ground.
+#[derive(Component, Default)]
+pub struct TnuaActionGate {
+    cooldowns: HashMap<&'static str, Float>,
+    cooldown_of_action: HashMap<&'static str, (&'static str, Float)>,
+    wall_contact: bool,
+}
+
+impl TnuaActionGate {
+    /// Call this every frame, before feeding the actions, to start and advance the cooldowns.
+    pub fn update(&mut self, controller: &TnuaController, frame_duration: Float) {
+        self.cooldowns.retain(|_, remaining| {
+            *remaining -= frame_duration;
+            0.0 < *remaining
+        });
+        if let Some(action_name) = controller.action_flow_status().just_starting() {
+            if let Some((group, duration)) = self.cooldown_of_action.get(action_name) {
+                self.start_cooldown(group, *duration);
+            }
+        }
+    }
+
+    /// Tell the gate whether the character is currently touching a wall, for
+    /// [`requires_wall_contact`](TnuaActionRequirements::requires_wall_contact).
+    ///
+    /// Tnua does not detect walls by itself - use something like
+    /// [`TnuaObstructionDetector`](crate::control_helpers::TnuaObstructionDetector) or the
+    /// physics backend's collision events.
+    pub fn set_wall_contact(&mut self, wall_contact: bool) {
+        self.wall_contact = wall_contact;
+    }
+
+    /// Start (or restart) the cooldown of a group, as if an action in it has just started.
+    pub fn start_cooldown(&mut self, group: &'static str, duration: Float) {
+        let remaining = self.cooldowns.entry(group).or_default();
+        *remaining = remaining.max(duration);
+    }
+
+    /// The time, in seconds, until actions in the cooldown group can start again.
+    pub fn cooldown_remaining(&self, group: &'static str) -> Float {
+        self.cooldowns.get(group).copied().unwrap_or(0.0)
+    }
+
+    /// Check if an action with these requirements can start.
+    pub fn check(
+        &self,
+        controller: &TnuaController,
+        requirements: &TnuaActionRequirements,
+    ) -> Result<(), TnuaActionRequirementFailure> {
+        if let Some(grounded) = requirements.grounded {
+            let is_airborne = controller.is_airborne().unwrap_or(false);
+            if grounded && is_airborne {
+                return Err(TnuaActionRequirementFailure::NotGrounded);
+            } else if !grounded && !is_airborne {
+                return Err(TnuaActionRequirementFailure::NotAirborne);
+            }
+        }
+        if 0.0 < requirements.min_air_time
+            && controller.airborne_duration().unwrap_or(0.0) < requirements.min_air_time
+        {
+            return Err(TnuaActionRequirementFailure::NotEnoughAirTime);
+        }
+        if requirements.requires_wall_contact && !self.wall_contact {
+            return Err(TnuaActionRequirementFailure::NoWallContact);
+        }
+        if let Some((group, _)) = requirements.cooldown {
+            let remaining = self.cooldown_remaining(group);
+            if 0.0 < remaining {
+                return Err(TnuaActionRequirementFailure::CoolingDown { group, remaining });
+            }
+        }
+        Ok(())
+    }
+
+    /// Feed an action with [its default name](TnuaAction::NAME), if its requirements are met.
+    pub fn action<A: TnuaAction>(
+        &mut self,
+        controller: &mut TnuaController,
+        requirements: &TnuaActionRequirements,
+        action: A,
+    ) -> Result<(), TnuaActionRequirementFailure> {
+        self.named_action(controller, A::NAME, requirements, action)
+    }
+
+    /// Feed an action with a custom name, if its requirements are met.
+    pub fn named_action<A: TnuaAction>(
+        &mut self,
+        controller: &mut TnuaController,
+        name: &'static str,
+        requirements: &TnuaActionRequirements,
+        action: A,
+    ) -> Result<(), TnuaActionRequirementFailure> {
+        if controller.action_name() != Some(name) {
+            self.check(controller, requirements)?;
+        }
+        match requirements.cooldown {
+            Some(cooldown) => {
+                self.cooldown_of_action.insert(name, cooldown);
+            }
+            None => {
+                self.cooldown_of_action.remove(name);
+            }
+        }
+        controller.named_action(name, action);
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::builtins::TnuaBuiltinJumpState;
+use crate::controller::TnuaController;
+use crate::motion_math;
+use crate::prelude::*;
+use crate::{TnuaActionRejectionReason, TnuaRigidBodyTracker};
+
+use super::TnuaLedgeDetector;
+
+/// A plugin required for making [`TnuaAiDriver`] work.
+pub struct TnuaAiDriverPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaAiDriverPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaAiDriverPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaAiDriverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            ai_driver_system.in_set(TnuaUserControlsSystemSet),
+        );
+    }
+}
+
+/// Read-only questions an AI can ask about a Tnua character.
+///
+/// These are meant to be used as the conditions (or the inputs of scorers) of AI crates like
+/// `bevy_behave` or `big-brain`. Construct it inside the condition system from the components of
+/// the character entity:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::TnuaRigidBodyTracker;
+/// # use bevy_tnua::control_helpers::TnuaAiQueries;
+/// fn is_grounded_condition(query: Query<(&TnuaController, &TnuaRigidBodyTracker)>) -> bool {
+///     query.iter().all(|(controller, tracker)| {
+///         TnuaAiQueries::new(controller, tracker).is_grounded()
+///     })
+/// }
+/// ```
+pub struct TnuaAiQueries<'a> {
+    pub controller: &'a TnuaController,
+    pub tracker: &'a TnuaRigidBodyTracker,
+    /// Required for [`distance_to_ledge`](Self::distance_to_ledge).
+    pub ledge_detector: Option<&'a TnuaLedgeDetector>,
+}
+
+impl<'a> TnuaAiQueries<'a> {
+    pub fn new(controller: &'a TnuaController, tracker: &'a TnuaRigidBodyTracker) -> Self {
+        Self {
+            controller,
+            tracker,
+            ledge_detector: None,
+        }
+    }
+
+    pub fn with_ledge_detector(mut self, ledge_detector: &'a TnuaLedgeDetector) -> Self {
+        self.ledge_detector = Some(ledge_detector);
+        self
+    }
+
+    fn up(&self) -> Vector3 {
+        Dir3::new(-self.tracker.gravity.f32())
+            .unwrap_or(Dir3::Y)
+            .adjust_precision()
+    }
+
+    /// Check if the character stands on the ground (or is in coyote time).
+    ///
+    /// A character with no basis is not considered grounded.
+    pub fn is_grounded(&self) -> bool {
+        !self.controller.is_airborne().unwrap_or(true)
+    }
+
+    /// The horizontal distance from the center of the character to `target`.
+    pub fn horizontal_distance_to(&self, target: Vector3) -> Float {
+        (target - self.tracker.translation)
+            .reject_from(self.up())
+            .length()
+    }
+
+    /// Check if a full height `jump` can land the center of the character on `target`, without
+    /// moving horizontally faster than `max_horizontal_speed`.
+    ///
+    /// Like [`TnuaBuiltinJump::landing_target`], `target` refers to the center of the character -
+    /// so to check a point on the ground, the float height should be added to it.
+    pub fn can_reach(
+        &self,
+        target: Vector3,
+        jump: &TnuaBuiltinJump,
+        max_horizontal_speed: Float,
+    ) -> bool {
+        let up = self.up();
+        let offset = target - self.tracker.translation;
+        let gravity = self.tracker.gravity.dot(-up);
+        let Some(time_to_land) = motion_math::jump_time_to_land_at(jump, gravity, offset.dot(up))
+        else {
+            return false;
+        };
+        offset.reject_from(up).length() <= max_horizontal_speed * time_to_land
+    }
+
+    /// The horizontal distance to the ledge detected by the
+    /// [`ledge_detector`](Self::ledge_detector), if one is set and a ledge is detected.
+    pub fn distance_to_ledge(&self) -> Option<Float> {
+        let ledge = self.ledge_detector?.ledge()?;
+        Some(self.horizontal_distance_to(ledge.point))
+    }
+}
+
+/// A high level command for [`TnuaAiDriver`], to be issued by an AI action.
+#[derive(Clone, Debug)]
+pub enum TnuaAiCommand {
+    /// Stand still. Succeeds immediately.
+    Idle,
+
+    /// Walk toward a point, and succeed once the character is horizontally within `stop_distance`
+    /// from it.
+    WalkTo {
+        target: Vector3,
+        stop_distance: Float,
+    },
+
+    /// Jump while walking in a direction (e.g. over an obstacle), and succeed once the character
+    /// lands.
+    ///
+    /// Fails if the jump could not start (e.g. because the character was in the air).
+    JumpOver { direction: Dir3 },
+
+    /// Stand still and turn to face a direction. Succeeds once the character faces it.
+    ///
+    /// Only the horizontal part of the direction is used. Fails if there is no such part (the
+    /// direction is straight up or down), or if the character is tilted so that its forward
+    /// direction has no horizontal part.
+    Face(Dir3),
+}
+
+/// The status of the current [`TnuaAiCommand`] - to be reported back to the AI crate (e.g. as
+/// `bevy_behave`'s trigger results or `big-brain`'s `ActionState`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TnuaAiCommandStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Drives a character according to [`TnuaAiCommand`]s.
+///
+/// This allows AI actions to drive Tnua characters declaratively - the action issues a
+/// [`command`](Self::command) when it starts, and then polls the [`status`](Self::status) every
+/// frame until it is no longer [`Running`](TnuaAiCommandStatus::Running):
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::control_helpers::{TnuaAiCommand, TnuaAiCommandStatus, TnuaAiDriver};
+/// # use bevy_tnua::math::Vector3;
+/// # let mut driver = TnuaAiDriver::default();
+/// // When the AI action starts:
+/// driver.command(TnuaAiCommand::WalkTo {
+///     target: Vector3::new(10.0, 0.0, 0.0),
+///     stop_distance: 0.5,
+/// });
+///
+/// // Every frame, while the AI action runs:
+/// match driver.status() {
+///     TnuaAiCommandStatus::Running => {}
+///     TnuaAiCommandStatus::Succeeded => { /* report success to the AI crate */ }
+///     TnuaAiCommandStatus::Failed => { /* report failure to the AI crate */ }
+/// }
+/// ```
+///
+/// Using it requires:
+///
+/// 1. Adding the plugin [`TnuaAiDriverPlugin`].
+/// 2. Adding [`TnuaAiDriver`] as a component to the character entity, instead of feeding the
+///    [`TnuaController`] from a controls system.
+///
+/// After a command finishes, the driver keeps the character standing still until it gets a new
+/// command.
+///
+/// Only available with the `ai` feature.
+#[derive(Component)]
+pub struct TnuaAiDriver {
+    /// The walk basis configuration.
+    ///
+    /// [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) and
+    /// [`desired_forward`](TnuaBuiltinWalk::desired_forward) will be overwritten by the driver.
+    pub walk: TnuaBuiltinWalk,
+
+    /// The jump action to use for [`JumpOver`](TnuaAiCommand::JumpOver).
+    pub jump: TnuaBuiltinJump,
+
+    /// The speed to walk at.
+    pub speed: Float,
+
+    /// How close, in radians, the character needs to get to the direction of a
+    /// [`Face`](TnuaAiCommand::Face) command for it to succeed.
+    pub facing_tolerance: Float,
+
+    command: TnuaAiCommand,
+    status: TnuaAiCommandStatus,
+    jump_started: bool,
+}
+
+impl Default for TnuaAiDriver {
+    fn default() -> Self {
+        Self {
+            walk: Default::default(),
+            jump: Default::default(),
+            speed: 10.0,
+            facing_tolerance: 0.1,
+            command: TnuaAiCommand::Idle,
+            status: TnuaAiCommandStatus::Succeeded,
+            jump_started: false,
+        }
+    }
+}
+
+impl TnuaAiDriver {
+    /// Start a new command, replacing the current one.
+    pub fn command(&mut self, command: TnuaAiCommand) {
+        self.command = command;
+        self.status = TnuaAiCommandStatus::Running;
+        self.jump_started = false;
+    }
+
+    /// The command the driver is executing (or has finished executing).
+    pub fn current_command(&self) -> &TnuaAiCommand {
+        &self.command
+    }
+
+    /// The status of the [current command](Self::current_command).
+    pub fn status(&self) -> TnuaAiCommandStatus {
+        self.status
+    }
+}
+
+pub(crate) fn ai_driver_system(
+    mut query: Query<(
+        &mut TnuaController,
+        &mut TnuaAiDriver,
+        &TnuaRigidBodyTracker,
+    )>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(mut controller, mut driver, tracker)| {
+            let up = Dir3::new(-tracker.gravity.f32())
+                .unwrap_or(Dir3::Y)
+                .adjust_precision();
+            let running = driver.status == TnuaAiCommandStatus::Running;
+
+            let mut desired_velocity = Vector3::ZERO;
+            let mut desired_forward = None;
+            let mut hold_jump = false;
+
+            match driver.command.clone() {
+                TnuaAiCommand::Idle => {
+                    driver.status = TnuaAiCommandStatus::Succeeded;
+                }
+                TnuaAiCommand::WalkTo {
+                    target,
+                    stop_distance,
+                } => {
+                    let offset = (target - tracker.translation).reject_from(up);
+                    if offset.length() <= stop_distance {
+                        driver.status = TnuaAiCommandStatus::Succeeded;
+                    } else if running {
+                        desired_velocity = offset.normalize() * driver.speed;
+                        desired_forward = Dir3::new(offset.f32()).ok();
+                    }
+                }
+                TnuaAiCommand::JumpOver { direction } if running => {
+                    desired_velocity = direction.adjust_precision() * driver.speed;
+                    desired_forward = Some(direction);
+                    match controller.concrete_action::<TnuaBuiltinJump>() {
+                        Some((_, state)) => {
+                            driver.jump_started = true;
+                            // Stop holding once falling, so that the jump will not restart after
+                            // landing.
+                            hold_jump = !matches!(state, TnuaBuiltinJumpState::FallSection);
+                        }
+                        None if driver.jump_started => {
+                            driver.status = TnuaAiCommandStatus::Succeeded;
+                            desired_velocity = Vector3::ZERO;
+                        }
+                        None => {
+                            let rejected = controller.rejected_actions().any(|(action, reason)| {
+                                action == TnuaBuiltinJump::NAME
+                                    && reason == TnuaActionRejectionReason::InitiationDecision
+                            });
+                            if rejected {
+                                driver.status = TnuaAiCommandStatus::Failed;
+                                desired_velocity = Vector3::ZERO;
+                            } else {
+                                hold_jump = true;
+                            }
+                        }
+                    }
+                }
+                TnuaAiCommand::JumpOver { .. } => {}
+                TnuaAiCommand::Face(direction) => {
+                    let target = direction.adjust_precision().reject_from(up).try_normalize();
+                    desired_forward = target.and_then(|target| Dir3::new(target.f32()).ok());
+                    let forward = tracker
+                        .rotation
+                        .mul_vec3(-Vector3::Z)
+                        .reject_from(up)
+                        .try_normalize();
+                    if running {
+                        match (forward, target) {
+                            (Some(forward), Some(target)) => {
+                                if forward.angle_between(target) <= driver.facing_tolerance {
+                                    driver.status = TnuaAiCommandStatus::Succeeded;
+                                }
+                            }
+                            _ => {
+                                driver.status = TnuaAiCommandStatus::Failed;
+                            }
+                        }
+                    }
+                }
+            }
+
+            controller.basis(TnuaBuiltinWalk {
+                desired_velocity,
+                desired_forward,
+                ..driver.walk.clone()
+            });
+            if hold_jump {
+                controller.action(driver.jump.clone());
+            }
+        });
+}
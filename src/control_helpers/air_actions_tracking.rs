@@ -230,3 +230,128 @@ impl TnuaSimpleAirActionsCounter {
         self.air_actions_count
     }
 }
+
+/// A pool of charges that air actions (like air jumps and air dashes) consume, with configurable
+/// rules for refreshing it.
+///
+/// It's [`update`](Self::update) must be called every frame, and actions should only be fed if
+/// [`can_use`](Self::can_use) allows them:
+///
+/// ```no_run
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaAirActionCharges;
+/// # let mut controller = TnuaController::default();
+/// # let mut charges = TnuaAirActionCharges::default();
+/// # let jump_pressed = true;
+/// charges.update(&controller);
+/// if jump_pressed && charges.can_use(TnuaBuiltinJump::NAME) {
+///     controller.action(TnuaBuiltinJump {
+///         height: 4.0,
+///         allow_in_air: true,
+///         ..Default::default()
+///     });
+/// }
+/// ```
+///
+/// Actions that start on the ground (or in coyote time) are free. Each action that starts in the
+/// air consumes one charge. The charges are refreshed:
+///
+/// * When the character lands, if [`refresh_on_landing`](Self::refresh_on_landing) is set.
+/// * When the game calls [`notify_wall_touch`](Self::notify_wall_touch), if
+///   [`refresh_on_wall_touch`](Self::refresh_on_wall_touch) is set. Tnua does not detect wall
+///   touches by itself - use collision events or a probe for that.
+/// * When the game calls [`add_charges`](Self::add_charges) (e.g. when the character collects a
+///   pickup) or [`refresh`](Self::refresh).
+#[derive(Component)]
+pub struct TnuaAirActionCharges {
+    /// The number of charges when the pool is full.
+    pub max_charges: usize,
+
+    /// Refill the pool when the character lands.
+    pub refresh_on_landing: bool,
+
+    /// Refill the pool when [`notify_wall_touch`](Self::notify_wall_touch) is called.
+    pub refresh_on_wall_touch: bool,
+
+    tracker: TnuaAirActionsTracker,
+    current_action: Option<&'static str>,
+    charges: usize,
+}
+
+impl Default for TnuaAirActionCharges {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl TnuaAirActionCharges {
+    /// Create a full pool with `max_charges` charges that refreshes when landing.
+    pub fn new(max_charges: usize) -> Self {
+        Self {
+            max_charges,
+            refresh_on_landing: true,
+            refresh_on_wall_touch: false,
+            tracker: Default::default(),
+            current_action: None,
+            charges: max_charges,
+        }
+    }
+
+    /// Call this every frame to track the air actions.
+    pub fn update(&mut self, controller: &TnuaController) {
+        let was_in_air = self.tracker.considered_in_air;
+        match self.tracker.update(controller) {
+            TnuaAirActionsUpdate::NoChange | TnuaAirActionsUpdate::FreeFallStarted => {}
+            TnuaAirActionsUpdate::AirActionStarted(action_name) => {
+                if was_in_air {
+                    self.charges = self.charges.saturating_sub(1);
+                }
+                self.current_action = Some(action_name);
+            }
+            TnuaAirActionsUpdate::ActionFinishedInAir => {
+                self.current_action = None;
+            }
+            TnuaAirActionsUpdate::JustLanded => {
+                self.current_action = None;
+                if self.refresh_on_landing {
+                    self.refresh();
+                }
+            }
+        }
+    }
+
+    /// Check if an action can be fed.
+    ///
+    /// This is always `true` on the ground, and for the action that is currently running (which
+    /// already consumed its charge, if it needed one). Otherwise it's `true` only if there are
+    /// charges left.
+    pub fn can_use(&self, action_name: &str) -> bool {
+        !self.tracker.considered_in_air
+            || self.current_action == Some(action_name)
+            || 0 < self.charges
+    }
+
+    /// The number of charges left.
+    pub fn charges(&self) -> usize {
+        self.charges
+    }
+
+    /// Refill the pool to [`max_charges`](Self::max_charges).
+    pub fn refresh(&mut self) {
+        self.charges = self.charges.max(self.max_charges);
+    }
+
+    /// Add charges to the pool (e.g. when collecting a pickup), up to
+    /// [`max_charges`](Self::max_charges).
+    pub fn add_charges(&mut self, count: usize) {
+        self.charges = (self.charges + count).min(self.max_charges.max(self.charges));
+    }
+
+    /// Tell the pool that the character touched a wall, so that it will refresh if
+    /// [`refresh_on_wall_touch`](Self::refresh_on_wall_touch) is set.
+    pub fn notify_wall_touch(&mut self) {
+        if self.refresh_on_wall_touch {
+            self.refresh();
+        }
+    }
+}
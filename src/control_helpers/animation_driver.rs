@@ -0,0 +1,249 @@
+use std::time::Duration;
+
+use bevy::animation::graph::AnimationNodeIndex;
+use bevy::animation::transition::AnimationTransitions;
+use bevy::animation::AnimationPlayer;
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+
+use crate::builtins::{TnuaBuiltinCrouch, TnuaBuiltinDash, TnuaBuiltinJump, TnuaBuiltinJumpState};
+use crate::math::{AsF32, Float};
+use crate::prelude::*;
+use crate::{TnuaAnimatingState, TnuaAnimatingStateDirective};
+
+/// A plugin required for making [`TnuaAnimationDriver`] work.
+pub struct TnuaAnimationDriverPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaAnimationDriverPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaAnimationDriverPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaAnimationDriverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            drive_animations.after(TnuaPipelineStages::Logic),
+        );
+    }
+}
+
+/// The animation [`TnuaAnimationDriver`] decided to play.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TnuaAnimationDriverState {
+    /// Standing on the ground without moving.
+    Idle,
+    /// Running on the ground, at the given speed.
+    Running(Float),
+    /// Going up during a [jump](TnuaBuiltinJump).
+    Rising,
+    /// Falling - either at the end of a jump or after walking off a ledge.
+    Falling,
+    /// Performing a [dash](TnuaBuiltinDash).
+    Dashing,
+    /// [Crouching](TnuaBuiltinCrouch).
+    Crouching,
+}
+
+/// Plays animations according to the state of the [`TnuaController`], for games that do not need
+/// custom animation logic.
+///
+/// Add this component (and [`TnuaAnimationDriverPlugin`]) to the character entity, pointing it at
+/// the entity with the [`AnimationPlayer`] (which, with glTF scenes, is usually a descendant of
+/// the character entity) and at the nodes of the animation graph to play in each state:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::animation::graph::AnimationNodeIndex;
+/// # use bevy_tnua::control_helpers::TnuaAnimationDriver;
+/// # let player_entity = Entity::PLACEHOLDER;
+/// # let [idle, run, jump, fall] = [0, 1, 2, 3].map(AnimationNodeIndex::new);
+/// let driver = TnuaAnimationDriver::new(player_entity, idle)
+///     // The animation speed is the running speed multiplied by this factor:
+///     .with_run(run, 0.1)
+///     .with_jump(jump, fall);
+/// ```
+///
+/// Only the builtin [jump](TnuaBuiltinJump), [dash](TnuaBuiltinDash) and
+/// [crouch](TnuaBuiltinCrouch) actions (with their default names) are recognized. States without
+/// an animation fall back to the animation of the basis - running or idle. If the
+/// [`AnimationPlayer`]'s entity also has [`AnimationTransitions`], the animations are crossfaded
+/// over the [transition duration](Self::with_transition_duration).
+///
+/// For anything more elaborate, use [`TnuaAnimatingState`] directly.
+#[derive(Component)]
+pub struct TnuaAnimationDriver {
+    player: Entity,
+    idle: AnimationNodeIndex,
+    run: Option<(AnimationNodeIndex, Float)>,
+    rise: Option<AnimationNodeIndex>,
+    fall: Option<AnimationNodeIndex>,
+    dash: Option<AnimationNodeIndex>,
+    crouch: Option<AnimationNodeIndex>,
+    transition_duration: Duration,
+    animating_state: TnuaAnimatingState<TnuaAnimationDriverState>,
+}
+
+impl TnuaAnimationDriver {
+    /// Create a driver for the [`AnimationPlayer`] on the `player` entity, that plays `idle` when
+    /// the character does nothing.
+    pub fn new(player: Entity, idle: AnimationNodeIndex) -> Self {
+        Self {
+            player,
+            idle,
+            run: None,
+            rise: None,
+            fall: None,
+            dash: None,
+            crouch: None,
+            transition_duration: Duration::from_millis(200),
+            animating_state: Default::default(),
+        }
+    }
+
+    /// Play `run` when the character moves on the ground, at a speed of `speed_factor` times the
+    /// running speed.
+    pub fn with_run(mut self, run: AnimationNodeIndex, speed_factor: Float) -> Self {
+        self.run = Some((run, speed_factor));
+        self
+    }
+
+    /// Play `rise` during the upward part of a jump, and `fall` when the character falls.
+    pub fn with_jump(mut self, rise: AnimationNodeIndex, fall: AnimationNodeIndex) -> Self {
+        self.rise = Some(rise);
+        self.fall = Some(fall);
+        self
+    }
+
+    /// Play `dash` while dashing.
+    pub fn with_dash(mut self, dash: AnimationNodeIndex) -> Self {
+        self.dash = Some(dash);
+        self
+    }
+
+    /// Play `crouch` while crouching.
+    pub fn with_crouch(mut self, crouch: AnimationNodeIndex) -> Self {
+        self.crouch = Some(crouch);
+        self
+    }
+
+    /// The duration of the crossfade between animations, if the [`AnimationPlayer`]'s entity has
+    /// [`AnimationTransitions`]. Defaults to 200 milliseconds.
+    pub fn with_transition_duration(mut self, transition_duration: Duration) -> Self {
+        self.transition_duration = transition_duration;
+        self
+    }
+
+    /// The animation currently played.
+    pub fn state(&self) -> Option<TnuaAnimationDriverState> {
+        self.animating_state.get().copied()
+    }
+
+    fn node(&self, state: TnuaAnimationDriverState) -> Option<AnimationNodeIndex> {
+        match state {
+            TnuaAnimationDriverState::Idle => Some(self.idle),
+            TnuaAnimationDriverState::Running(_) => self.run.map(|(run, _)| run),
+            TnuaAnimationDriverState::Rising => self.rise,
+            TnuaAnimationDriverState::Falling => self.fall,
+            TnuaAnimationDriverState::Dashing => self.dash,
+            TnuaAnimationDriverState::Crouching => self.crouch,
+        }
+    }
+
+    fn decide_state(&self, controller: &TnuaController) -> TnuaAnimationDriverState {
+        let action_state = match controller.action_name() {
+            Some(TnuaBuiltinJump::NAME) => match controller.concrete_action::<TnuaBuiltinJump>() {
+                Some((_, TnuaBuiltinJumpState::FallSection)) => {
+                    Some(TnuaAnimationDriverState::Falling)
+                }
+                Some(_) => Some(TnuaAnimationDriverState::Rising),
+                None => None,
+            },
+            Some(TnuaBuiltinDash::NAME) => Some(TnuaAnimationDriverState::Dashing),
+            Some(TnuaBuiltinCrouch::NAME) => Some(TnuaAnimationDriverState::Crouching),
+            _ => None,
+        };
+        if let Some(action_state) = action_state.filter(|state| self.node(*state).is_some()) {
+            return action_state;
+        }
+        if controller.is_airborne().unwrap_or(false) && self.fall.is_some() {
+            return TnuaAnimationDriverState::Falling;
+        }
+        match controller.running_speed() {
+            Some(speed) if 0.01 < speed && self.run.is_some() => {
+                TnuaAnimationDriverState::Running(speed)
+            }
+            _ => TnuaAnimationDriverState::Idle,
+        }
+    }
+}
+
+fn drive_animations(
+    mut query: Query<(&TnuaController, &mut TnuaAnimationDriver)>,
+    mut players_query: Query<(&mut AnimationPlayer, Option<&mut AnimationTransitions>)>,
+) {
+    for (controller, mut driver) in query.iter_mut() {
+        let driver = driver.as_mut();
+        let Ok((mut player, transitions)) = players_query.get_mut(driver.player) else {
+            continue;
+        };
+        let new_state = driver.decide_state(controller);
+        let run_speed_factor = driver.run.map_or(1.0, |(_, speed_factor)| speed_factor);
+        let animation_speed = |state: &TnuaAnimationDriverState| match state {
+            TnuaAnimationDriverState::Running(speed) => (speed * run_speed_factor).f32(),
+            _ => 1.0,
+        };
+        let (state, changed) = match driver.animating_state.update_by_discriminant(new_state) {
+            TnuaAnimatingStateDirective::Maintain { state } => (*state, false),
+            TnuaAnimatingStateDirective::Alter {
+                old_state: _,
+                state,
+            }
+            | TnuaAnimatingStateDirective::Interrupt {
+                old_state: _,
+                state,
+            } => (*state, true),
+        };
+        if !changed {
+            if let Some(animation) = driver
+                .node(state)
+                .and_then(|node| player.animation_mut(node))
+            {
+                animation.set_speed(animation_speed(&state));
+            }
+            continue;
+        }
+        let Some(node) = driver.node(state) else {
+            continue;
+        };
+        let animation = if let Some(mut transitions) = transitions {
+            transitions.play(&mut player, node, driver.transition_duration)
+        } else {
+            player.stop_all();
+            player.start(node)
+        };
+        animation.set_speed(animation_speed(&state));
+        match state {
+            // These animations are played once, and stay on their last frame if the state lasts
+            // longer than them.
+            TnuaAnimationDriverState::Rising | TnuaAnimationDriverState::Dashing => {}
+            TnuaAnimationDriverState::Idle
+            | TnuaAnimationDriverState::Running(_)
+            | TnuaAnimationDriverState::Falling
+            | TnuaAnimationDriverState::Crouching => {
+                animation.repeat();
+            }
+        }
+    }
+}
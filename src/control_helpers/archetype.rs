@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::builtins::{TnuaBuiltinCrouch, TnuaBuiltinDash, TnuaBuiltinJump, TnuaBuiltinWalk};
+use crate::math::{AsF32, Float, Vector3};
+
+/// Derives the configuration of the builtin basis and actions from a handful of high level
+/// parameters, so that games with many types of characters can generate consistent
+/// configurations.
+///
+/// All the parameters except [`float_height`](Self::float_height) are relative - `1.0` means a
+/// character that behaves like the defaults of the builtin basis and actions. The derived fields
+/// are scaled from these defaults using the following formulas:
+///
+/// | Parameter                        | Formula                                |
+/// |----------------------------------|----------------------------------------|
+/// | Walk and dash accelerations      | `default * agility / weight`           |
+/// | Turning speed                    | `default * agility / sqrt(weight)`     |
+/// | Dash speed                       | `default * agility / sqrt(weight)`     |
+/// | Dash brake-to speed              | `2.0 * walk_speed`                     |
+/// | Extra gravities (walk and jump)  | `default * weight`                     |
+/// | Jump height                      | `4.0 * jump_class`                     |
+/// | Coyote time and input buffering  | `default * sqrt(agility)`              |
+///
+/// Fields that are not listed here keep their default values. Everything returned by this
+/// helper is a regular config struct, so any field can still be overridden:
+///
+/// ```
+/// # use bevy_tnua::control_helpers::TnuaCharacterArchetype;
+/// # use bevy_tnua::builtins::TnuaBuiltinWalk;
+/// # use bevy_tnua::math::Vector3;
+/// let heavy = TnuaCharacterArchetype {
+///     float_height: 1.5,
+///     ..TnuaCharacterArchetype::HEAVY
+/// };
+/// let light = TnuaCharacterArchetype {
+///     float_height: 1.0,
+///     ..TnuaCharacterArchetype::LIGHT
+/// };
+/// assert!(heavy.walk().acceleration < light.walk().acceleration);
+/// assert!(heavy.jump().height < light.jump().height);
+///
+/// let walk = TnuaBuiltinWalk {
+///     desired_velocity: Vector3::X * heavy.walk_speed,
+///     ..heavy.walk()
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct TnuaCharacterArchetype {
+    /// The [`float_height`](TnuaBuiltinWalk::float_height) of the walk basis.
+    ///
+    /// This depends on the model of the character, so unlike the other parameters it is not
+    /// relative.
+    pub float_height: Float,
+
+    /// How heavy the character is. Heavier characters accelerate and turn slower, and fall
+    /// faster.
+    pub weight: Float,
+
+    /// How agile the character is. More agile characters accelerate and turn faster, and have
+    /// more lenient timing windows.
+    pub agility: Float,
+
+    /// How high the character jumps, relative to the default height of `4.0`.
+    pub jump_class: Float,
+
+    /// The speed of the character when walking at full speed.
+    ///
+    /// This is not used directly by the derived configs (since it depends on the input) - it is
+    /// provided so that the control system can multiply the input direction by it.
+    pub walk_speed: Float,
+}
+
+impl TnuaCharacterArchetype {
+    /// A character that behaves like the defaults of the builtin basis and actions.
+    pub const MEDIUM: Self = Self {
+        float_height: 0.0,
+        weight: 1.0,
+        agility: 1.0,
+        jump_class: 1.0,
+        walk_speed: 10.0,
+    };
+
+    /// A slow and heavy character.
+    pub const HEAVY: Self = Self {
+        float_height: 0.0,
+        weight: 2.0,
+        agility: 0.75,
+        jump_class: 0.75,
+        walk_speed: 7.0,
+    };
+
+    /// A fast and light character.
+    pub const LIGHT: Self = Self {
+        float_height: 0.0,
+        weight: 0.6,
+        agility: 1.5,
+        jump_class: 1.25,
+        walk_speed: 12.0,
+    };
+
+    /// The walk basis, with zero [`desired_velocity`](TnuaBuiltinWalk::desired_velocity).
+    pub fn walk(&self) -> TnuaBuiltinWalk {
+        let default = TnuaBuiltinWalk::default();
+        TnuaBuiltinWalk {
+            float_height: self.float_height,
+            acceleration: default.acceleration * self.agility / self.weight,
+            air_acceleration: default.air_acceleration * self.agility / self.weight,
+            turning_angvel: default.turning_angvel * self.agility / self.weight.sqrt(),
+            coyote_time: default.coyote_time * self.agility.sqrt(),
+            free_fall_extra_gravity: default.free_fall_extra_gravity * self.weight,
+            ..default
+        }
+    }
+
+    /// The walk basis, moving at [`walk_speed`](Self::walk_speed) times `direction` and facing
+    /// it.
+    ///
+    /// The length of `direction` should be between zero and one.
+    pub fn walk_in_direction(&self, direction: Vector3) -> TnuaBuiltinWalk {
+        TnuaBuiltinWalk {
+            desired_velocity: self.walk_speed * direction,
+            desired_forward: Dir3::new(direction.f32()).ok(),
+            ..self.walk()
+        }
+    }
+
+    /// The jump action, at full height.
+    pub fn jump(&self) -> TnuaBuiltinJump {
+        let default = TnuaBuiltinJump::default();
+        TnuaBuiltinJump {
+            height: 4.0 * self.jump_class,
+            upslope_extra_gravity: default.upslope_extra_gravity * self.weight,
+            takeoff_extra_gravity: default.takeoff_extra_gravity * self.weight,
+            fall_extra_gravity: default.fall_extra_gravity * self.weight,
+            shorten_extra_gravity: default.shorten_extra_gravity * self.weight,
+            peak_prevention_extra_gravity: default.peak_prevention_extra_gravity * self.weight,
+            input_buffer_time: default.input_buffer_time * self.agility.sqrt(),
+            ..default
+        }
+    }
+
+    /// The dash action, with zero [`displacement`](TnuaBuiltinDash::displacement).
+    pub fn dash(&self) -> TnuaBuiltinDash {
+        let default = TnuaBuiltinDash::default();
+        TnuaBuiltinDash {
+            speed: default.speed * self.agility / self.weight.sqrt(),
+            brake_to_speed: 2.0 * self.walk_speed,
+            acceleration: default.acceleration * self.agility / self.weight,
+            brake_acceleration: default.brake_acceleration * self.agility / self.weight,
+            input_buffer_time: default.input_buffer_time * self.agility.sqrt(),
+            ..default
+        }
+    }
+
+    /// The crouch action, lowering the character to half its float height.
+    pub fn crouch(&self) -> TnuaBuiltinCrouch {
+        TnuaBuiltinCrouch {
+            float_offset: -0.5 * self.float_height,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TnuaCharacterArchetype {
+    fn default() -> Self {
+        Self::MEDIUM
+    }
+}
@@ -0,0 +1,260 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+
+use crate::controller::TnuaController;
+use crate::math::AsF32;
+use crate::prelude::*;
+use crate::{TnuaCharacterSnapshot, TnuaRigidBodyTracker, TnuaUserControlsSystemSet};
+
+/// A plugin required for making [`TnuaControlsRecorder`] work, for a specific type of controls.
+pub struct TnuaControlsRecorderPlugin<T> {
+    schedule: InternedScheduleLabel,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> TnuaControlsRecorderPlugin<T> {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for TnuaControlsRecorderPlugin<T> {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl<T: 'static + Send + Sync + Clone> Plugin for TnuaControlsRecorderPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            controls_recorder_system::<T>
+                .after(TnuaUserControlsSystemSet)
+                .before(TnuaPipelineStages::Logic),
+        );
+    }
+}
+
+/// Controls recorded by a [`TnuaControlsRecorder`], one value per frame.
+///
+/// With the `serialize` feature, this can be serialized with serde (if `T` can), for saving
+/// replays and attaching them to bug reports.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TnuaControlsRecording<T> {
+    /// The state of the character when the recording started.
+    pub initial_state: Option<TnuaCharacterSnapshot>,
+
+    /// The controls of each frame.
+    pub frames: Vec<T>,
+}
+
+impl<T> Default for TnuaControlsRecording<T> {
+    fn default() -> Self {
+        Self {
+            initial_state: None,
+            frames: Vec::new(),
+        }
+    }
+}
+
+/// What a [`TnuaControlsRecorder`] is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TnuaControlsRecorderMode {
+    /// Feeding the controller with the [input](TnuaControlsRecorder::set_input) without recording
+    /// it.
+    Live,
+    /// Feeding the controller with the input and recording it.
+    Recording,
+    /// Feeding the controller from a recording, ignoring the input. Holds the index of the next
+    /// frame to play.
+    Playing(usize),
+    /// Done playing a recording. The controller is no longer fed until the recorder is switched
+    /// to another mode.
+    FinishedPlaying,
+}
+
+/// Records the controls of a character, and plays them back - for ghost racers, kill-cam replays
+/// and reproducing physics bugs.
+///
+/// Instead of feeding the controller directly, the controls system should pass a value that
+/// describes the state of the controls (e.g. which direction the stick points to and whether the
+/// jump button is held) to [`set_input`](Self::set_input). The recorder then feeds the controller
+/// with it, using the function given to [`new`](Self::new), after [`TnuaUserControlsSystemSet`].
+/// If no input is set in a frame, the previous one is used again - which means the values must
+/// describe states ("the jump button is pressed") and not changes ("start jumping").
+///
+/// Because the recording is made of these values and not of the basis and actions themselves, it
+/// is as small as the game's controls type, and the game can serialize it in any format it wants.
+/// Requires [`TnuaControlsRecorderPlugin`] for that controls type.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaControlsRecorder;
+/// # use bevy_tnua::math::Vector3;
+/// # let mut commands: Commands = panic!();
+/// #[derive(Clone)]
+/// struct Controls {
+///     direction: Vector3,
+///     jump: bool,
+/// }
+///
+/// commands.spawn(TnuaControlsRecorder::new(|controls: &Controls, controller| {
+///     controller.basis(TnuaBuiltinWalk {
+///         desired_velocity: 10.0 * controls.direction,
+///         float_height: 1.5,
+///         ..Default::default()
+///     });
+///     if controls.jump {
+///         controller.action(TnuaBuiltinJump {
+///             height: 4.0,
+///             ..Default::default()
+///         });
+///     }
+/// }));
+///
+/// fn controls_system(
+///     keyboard: Res<ButtonInput<KeyCode>>,
+///     mut query: Query<&mut TnuaControlsRecorder<Controls>>,
+/// ) {
+///     for mut recorder in query.iter_mut() {
+///         recorder.set_input(Controls {
+///             direction: Vector3::ZERO, // - read it from the keyboard
+///             jump: keyboard.pressed(KeyCode::Space),
+///         });
+///         if keyboard.just_pressed(KeyCode::KeyR) {
+///             recorder.start_recording();
+///         }
+///         if keyboard.just_pressed(KeyCode::KeyP) {
+///             if let Some(recording) = recorder.stop() {
+///                 recorder.play(recording);
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// For the playback to follow the recording, the simulation must be deterministic - which usually
+/// means running the physics and Tnua in `FixedUpdate`. When playback starts, the `Transform` of
+/// the character is restored from the [initial state](TnuaControlsRecording::initial_state), but
+/// the velocity is not - setting it depends on the physics backend, so the game should do it if
+/// the recording did not start at rest.
+#[derive(Component)]
+pub struct TnuaControlsRecorder<T> {
+    feed: Box<FeedFn<T>>,
+    input: Option<T>,
+    mode: TnuaControlsRecorderMode,
+    recording: TnuaControlsRecording<T>,
+    restore_initial_state: bool,
+}
+
+type FeedFn<T> = dyn 'static + Send + Sync + Fn(&T, &mut TnuaController);
+
+impl<T: 'static + Send + Sync + Clone> TnuaControlsRecorder<T> {
+    /// Create a recorder in [`Live`](TnuaControlsRecorderMode::Live) mode.
+    ///
+    /// `feed` is called every frame with the input or with the recorded frame.
+    pub fn new(feed: impl 'static + Send + Sync + Fn(&T, &mut TnuaController)) -> Self {
+        Self {
+            feed: Box::new(feed),
+            input: None,
+            mode: TnuaControlsRecorderMode::Live,
+            recording: Default::default(),
+            restore_initial_state: false,
+        }
+    }
+
+    /// Set the state of the controls for this frame (and for the next ones, until it is set
+    /// again).
+    ///
+    /// This is ignored during playback.
+    pub fn set_input(&mut self, input: T) {
+        self.input = Some(input);
+    }
+
+    /// The current mode of the recorder.
+    pub fn mode(&self) -> TnuaControlsRecorderMode {
+        self.mode
+    }
+
+    /// Start a new recording, discarding the current one. The state of the character is captured
+    /// on the first recorded frame.
+    pub fn start_recording(&mut self) {
+        self.recording = Default::default();
+        self.mode = TnuaControlsRecorderMode::Recording;
+    }
+
+    /// Play a recording from its beginning.
+    pub fn play(&mut self, recording: TnuaControlsRecording<T>) {
+        self.recording = recording;
+        self.restore_initial_state = true;
+        self.mode = TnuaControlsRecorderMode::Playing(0);
+    }
+
+    /// Go back to [`Live`](TnuaControlsRecorderMode::Live) mode, returning the recording if the
+    /// recorder was recording.
+    pub fn stop(&mut self) -> Option<TnuaControlsRecording<T>> {
+        let mode = std::mem::replace(&mut self.mode, TnuaControlsRecorderMode::Live);
+        let recording = std::mem::take(&mut self.recording);
+        (mode == TnuaControlsRecorderMode::Recording).then_some(recording)
+    }
+
+    /// The recording that is currently being recorded or played.
+    pub fn recording(&self) -> &TnuaControlsRecording<T> {
+        &self.recording
+    }
+}
+
+fn controls_recorder_system<T: 'static + Send + Sync + Clone>(
+    mut query: Query<(
+        &mut TnuaController,
+        &mut TnuaControlsRecorder<T>,
+        &TnuaRigidBodyTracker,
+        &mut Transform,
+    )>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(mut controller, mut recorder, tracker, mut transform)| {
+            let recorder = recorder.as_mut();
+            match recorder.mode {
+                TnuaControlsRecorderMode::Live => {
+                    if let Some(input) = recorder.input.as_ref() {
+                        (recorder.feed)(input, controller.as_mut());
+                    }
+                }
+                TnuaControlsRecorderMode::Recording => {
+                    let Some(input) = recorder.input.as_ref() else {
+                        return;
+                    };
+                    if recorder.recording.frames.is_empty() {
+                        recorder.recording.initial_state =
+                            Some(TnuaCharacterSnapshot::capture(tracker, controller.as_ref()));
+                    }
+                    recorder.recording.frames.push(input.clone());
+                    (recorder.feed)(input, controller.as_mut());
+                }
+                TnuaControlsRecorderMode::Playing(index) => {
+                    if std::mem::take(&mut recorder.restore_initial_state) {
+                        if let Some(initial_state) = recorder.recording.initial_state.as_ref() {
+                            transform.translation = initial_state.translation.f32();
+                            transform.rotation = initial_state.rotation.f32();
+                        }
+                    }
+                    if let Some(frame) = recorder.recording.frames.get(index) {
+                        (recorder.feed)(frame, controller.as_mut());
+                        recorder.mode = TnuaControlsRecorderMode::Playing(index + 1);
+                    } else {
+                        recorder.mode = TnuaControlsRecorderMode::FinishedPlaying;
+                    }
+                }
+                TnuaControlsRecorderMode::FinishedPlaying => {}
+            }
+        });
+}
@@ -0,0 +1,183 @@
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+
+use crate::controller::TnuaController;
+use crate::TnuaUserControlsSystemSet;
+
+/// A plugin required for making [`TnuaControlsFromSource`] work.
+pub struct TnuaControlsSourcePlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaControlsSourcePlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaControlsSourcePlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaControlsSourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            poll_controls_sources_system.in_set(TnuaUserControlsSystemSet),
+        );
+    }
+}
+
+/// Something that can feed a [`TnuaController`] from outside the ECS - e.g. a network
+/// connection, a scripting VM or an async task.
+///
+/// Sources are polled once per frame, inside [`TnuaUserControlsSystemSet`], so that the controls
+/// they produce are sampled at the correct point of the frame regardless of when they were
+/// produced. See [`TnuaChannelControlsSource`] for a ready-made source that receives the controls
+/// through a channel.
+pub trait TnuaControlsSource: 'static + Send + Sync {
+    /// Feed the controller with the controls for the current frame.
+    ///
+    /// Like any other controls system, this should feed the basis and the actions every frame -
+    /// and not only when the controls change.
+    fn feed(&mut self, controller: &mut TnuaController);
+}
+
+/// Feeds the [`TnuaController`] of the entity from a [`TnuaControlsSource`].
+///
+/// Add this component to the character entity instead of feeding the controller from a controls
+/// system. Requires [`TnuaControlsSourcePlugin`].
+#[derive(Component)]
+pub struct TnuaControlsFromSource(pub Box<dyn TnuaControlsSource>);
+
+impl TnuaControlsFromSource {
+    pub fn new(source: impl TnuaControlsSource) -> Self {
+        Self(Box::new(source))
+    }
+}
+
+/// A [`TnuaControlsSource`] that receives the controls through a channel.
+///
+/// The sending side of the channel can be moved to another thread or an async task, which sends
+/// values that describe the full state of the controls (e.g. which direction to walk in and
+/// whether or not the jump button is held). Every frame, the source drains the channel and feeds
+/// the controller according to the latest value received - so if no new value was sent since the
+/// previous frame, the previous one is used again. This means that values must not describe
+/// changes ("start jumping") - only states ("the jump button is pressed").
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::{TnuaChannelControlsSource, TnuaControlsFromSource};
+/// # use bevy_tnua::math::Vector3;
+/// # let mut commands: Commands = panic!();
+/// struct NetworkControls {
+///     direction: Vector3,
+///     jump: bool,
+/// }
+///
+/// let (sender, source) =
+///     TnuaChannelControlsSource::new(|controls: &NetworkControls, controller| {
+///         controller.basis(TnuaBuiltinWalk {
+///             desired_velocity: 10.0 * controls.direction,
+///             float_height: 1.5,
+///             ..Default::default()
+///         });
+///         if controls.jump {
+///             controller.action(TnuaBuiltinJump {
+///                 height: 4.0,
+///                 ..Default::default()
+///             });
+///         }
+///     });
+/// commands.spawn(TnuaControlsFromSource::new(source));
+///
+/// std::thread::spawn(move || {
+///     // Receive the controls from the network, and:
+///     sender.send(NetworkControls {
+///         direction: Vector3::X,
+///         jump: false,
+///     }).unwrap();
+/// });
+/// ```
+pub struct TnuaChannelControlsSource<T> {
+    receiver: Mutex<Receiver<T>>,
+    latest: Option<T>,
+    disconnected: bool,
+    feed: Box<FeedFn<T>>,
+}
+
+type FeedFn<T> = dyn 'static + Send + Sync + Fn(&T, &mut TnuaController);
+
+impl<T: 'static + Send + Sync> TnuaChannelControlsSource<T> {
+    /// Create the source, together with the sending side of its channel.
+    ///
+    /// `feed` is called every frame with the latest value received from the channel.
+    pub fn new(
+        feed: impl 'static + Send + Sync + Fn(&T, &mut TnuaController),
+    ) -> (Sender<T>, Self) {
+        let (sender, receiver) = channel();
+        (
+            sender,
+            Self {
+                receiver: Mutex::new(receiver),
+                latest: None,
+                disconnected: false,
+                feed: Box::new(feed),
+            },
+        )
+    }
+
+    /// The latest value received from the channel.
+    pub fn latest(&self) -> Option<&T> {
+        self.latest.as_ref()
+    }
+
+    /// Check if all the senders of the channel were dropped.
+    ///
+    /// The latest value received will still be used after the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+}
+
+impl<T: 'static + Send + Sync> TnuaControlsSource for TnuaChannelControlsSource<T> {
+    fn feed(&mut self, controller: &mut TnuaController) {
+        let receiver = self
+            .receiver
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        loop {
+            match receiver.try_recv() {
+                Ok(value) => {
+                    self.latest = Some(value);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.disconnected = true;
+                    break;
+                }
+            }
+        }
+        if let Some(latest) = self.latest.as_ref() {
+            (self.feed)(latest, controller);
+        }
+    }
+}
+
+fn poll_controls_sources_system(
+    mut query: Query<(&mut TnuaController, &mut TnuaControlsFromSource)>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(mut controller, mut source)| {
+            source.0.feed(controller.as_mut());
+        });
+}
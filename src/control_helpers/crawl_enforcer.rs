@@ -0,0 +1,273 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::builtins::{TnuaBuiltinCrouch, TnuaBuiltinWalk};
+use crate::controller::TnuaController;
+use crate::{TnuaPipelineStages, TnuaRigidBodyTracker, TnuaUserControlsSystemSet};
+
+use super::subservient_probe::{ProbeSensorsQuery, SubservientProbe};
+
+/// A plugin required for making [`TnuaCrawlEnforcer`] work.
+pub struct TnuaCrawlEnforcerPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaCrawlEnforcerPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaCrawlEnforcerPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaCrawlEnforcerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            update_crawl_enforcer_probes.in_set(TnuaPipelineStages::SubservientSensors),
+        );
+        app.add_systems(
+            self.schedule,
+            apply_crawl_enforcer
+                .after(TnuaUserControlsSystemSet)
+                .before(TnuaPipelineStages::Logic),
+        );
+    }
+}
+
+/// Automatically makes the character crawl under low ceilings - e.g. in tunnels and vents - and
+/// only lets it stand up again once there is room for it.
+///
+/// Unlike [`TnuaCrouchEnforcer`](super::TnuaCrouchEnforcer), which only keeps a crouch the player
+/// started, this helper starts the crawl by itself. It uses three proximity sensors on child
+/// entities:
+///
+/// * A head probe, cast forward (in the direction of the
+///   [`desired_velocity`](TnuaBuiltinWalk::desired_velocity)) from
+///   [`head_probe_origin`](Self::head_probe_origin).
+/// * A low probe, cast forward from the height the head would be at when crawling. When the head
+///   probe hits something but the low probe does not, the character is walking into a tunnel.
+/// * A ceiling probe, cast upward from [`ceiling_probe_origin`](Self::ceiling_probe_origin) while
+///   crawling. As long as it finds a ceiling closer than the height the character needs for
+///   standing up, the crawl continues. Like with
+///   [`TnuaCrouchEnforcer`](super::TnuaCrouchEnforcer), this probe should usually cast a shape
+///   (see [`with_probe_modifier`](Self::with_probe_modifier)).
+///
+/// While the crawl is [forced](Self::is_forced_crawling), the [`crawl`](Self::crawl) action is fed
+/// to the controller (as uncancellable) and the desired velocity of the [`TnuaBuiltinWalk`] basis
+/// is multiplied by [`speed_factor`](Self::speed_factor). This happens after
+/// [`TnuaUserControlsSystemSet`], so the controls system does not need to know about it - but the
+/// basis must be fed before that set.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::builtins::TnuaBuiltinCrouch;
+/// # use bevy_tnua::control_helpers::TnuaCrawlEnforcer;
+/// # let mut commands: Commands = panic!();
+/// # let mut cmd = commands.spawn(());
+/// // For a capsule collider with a half height of 0.5 and a radius of 0.5, centered on the
+/// // character entity:
+/// cmd.insert(TnuaCrawlEnforcer::for_collider(
+///     2.0,
+///     0.5,
+///     TnuaBuiltinCrouch {
+///         float_offset: -0.9,
+///         ..Default::default()
+///     },
+/// ));
+/// ```
+///
+/// Requires [`TnuaCrawlEnforcerPlugin`].
+#[derive(Component)]
+pub struct TnuaCrawlEnforcer {
+    /// The action to feed while crawling. Its [`float_offset`](TnuaBuiltinCrouch::float_offset)
+    /// also determines how much lower the character is when crawling.
+    pub crawl: TnuaBuiltinCrouch,
+
+    /// Multiply the desired velocity of the walk basis by this while crawling.
+    pub speed_factor: Float,
+
+    /// The origin of the forward head probe when standing, in the character's coord system.
+    ///
+    /// Should typically be placed a little below the top of the collider.
+    pub head_probe_origin: Vector3,
+
+    /// The range of the forward probes.
+    ///
+    /// Should be a little more than the horizontal distance from the probe origin to the edge of
+    /// the collider, so that the tunnel is detected before the collider hits its entrance.
+    pub probe_range: Float,
+
+    /// The origin of the ceiling probe, in the character's coord system.
+    ///
+    /// Should be placed at the top of the collider.
+    pub ceiling_probe_origin: Vector3,
+
+    /// The free space, above the height of the standing character, required for standing up.
+    pub clearance_margin: Float,
+
+    modify_probe: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+    head_probe: SubservientProbe,
+    low_probe: SubservientProbe,
+    ceiling_probe: SubservientProbe,
+    forced_crawling: bool,
+}
+
+impl TnuaCrawlEnforcer {
+    /// Create a crawl enforcer that feeds the `crawl` action.
+    ///
+    /// The probe origins default to the character's center - they should be set according to
+    /// the collider, or use [`for_collider`](Self::for_collider) instead.
+    pub fn new(crawl: TnuaBuiltinCrouch) -> Self {
+        Self {
+            crawl,
+            speed_factor: 0.5,
+            head_probe_origin: Vector3::ZERO,
+            probe_range: 0.6,
+            ceiling_probe_origin: Vector3::ZERO,
+            clearance_margin: 0.1,
+            modify_probe: Box::new(|_| {}),
+            head_probe: Default::default(),
+            low_probe: Default::default(),
+            ceiling_probe: Default::default(),
+            forced_crawling: false,
+        }
+    }
+
+    /// Create a crawl enforcer with probes placed for a collider of the given total height and
+    /// horizontal radius, centered on the character entity.
+    pub fn for_collider(height: Float, radius: Float, crawl: TnuaBuiltinCrouch) -> Self {
+        let half_height = 0.5 * height;
+        Self {
+            head_probe_origin: 0.8 * half_height * Vector3::Y,
+            probe_range: radius + 0.3,
+            ceiling_probe_origin: half_height * Vector3::Y,
+            ..Self::new(crawl)
+        }
+    }
+
+    /// Set a function that will be called with the command that creates the ceiling probe. This
+    /// function has the opportunity to add things to the probe entity - mostly cast-shape
+    /// components.
+    ///
+    /// A ray cast from the center of the collider's top stops finding the ceiling while the edge
+    /// of the collider is still under it, so it is recommended to cast a shape about as wide as
+    /// the collider. The forward probes compare heights, so they always cast rays.
+    pub fn with_probe_modifier(
+        mut self,
+        modify_probe: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        self.modify_probe = Box::new(modify_probe);
+        self
+    }
+
+    /// Whether the character is currently forced to crawl because there is no room for it to
+    /// stand.
+    pub fn is_forced_crawling(&self) -> bool {
+        self.forced_crawling
+    }
+
+    fn crawl_drop(&self) -> Float {
+        -self.crawl.float_offset
+    }
+}
+
+fn update_crawl_enforcer_probes(
+    mut query: Query<(
+        Entity,
+        &TnuaController,
+        &mut TnuaCrawlEnforcer,
+        &TnuaRigidBodyTracker,
+    )>,
+    mut sensors_query: ProbeSensorsQuery,
+    mut commands: Commands,
+) {
+    for (owner_entity, controller, mut enforcer, tracker) in query.iter_mut() {
+        let enforcer = enforcer.as_mut();
+
+        let Ok(up_direction) = Dir3::new(-tracker.gravity.f32()) else {
+            continue;
+        };
+        let up = up_direction.adjust_precision();
+        // The probe origins are in the character's coord system, which moves down with it when
+        // it crawls.
+        let is_crawling = controller.concrete_action::<TnuaBuiltinCrouch>().is_some();
+        let crawl_drop = enforcer.crawl_drop();
+        let standing_offset = if is_crawling {
+            crawl_drop * up
+        } else {
+            Vector3::ZERO
+        };
+
+        let head_hit = enforcer.head_probe.output(&sensors_query).is_some();
+        let low_hit = enforcer.low_probe.output(&sensors_query).is_some();
+        let ceiling_hit = enforcer.ceiling_probe.output(&sensors_query).is_some();
+        enforcer.forced_crawling = ceiling_hit || (head_hit && !low_hit);
+
+        let direction = controller
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .and_then(|(walk, _)| Dir3::new(walk.desired_velocity.reject_from(up).f32()).ok());
+        if let Some(direction) = direction {
+            let head_probe_origin = enforcer.head_probe_origin + standing_offset;
+            enforcer.head_probe.cast(
+                owner_entity,
+                &mut sensors_query,
+                &mut commands,
+                head_probe_origin,
+                direction,
+                enforcer.probe_range,
+                &|_| {},
+            );
+            enforcer.low_probe.cast(
+                owner_entity,
+                &mut sensors_query,
+                &mut commands,
+                head_probe_origin - crawl_drop * up,
+                direction,
+                enforcer.probe_range,
+                &|_| {},
+            );
+        } else {
+            enforcer.head_probe.turn_off(&mut sensors_query);
+            enforcer.low_probe.turn_off(&mut sensors_query);
+        }
+
+        if is_crawling {
+            enforcer.ceiling_probe.cast(
+                owner_entity,
+                &mut sensors_query,
+                &mut commands,
+                enforcer.ceiling_probe_origin,
+                up_direction,
+                crawl_drop + enforcer.clearance_margin,
+                &enforcer.modify_probe,
+            );
+        } else {
+            enforcer.ceiling_probe.turn_off(&mut sensors_query);
+        }
+    }
+}
+
+fn apply_crawl_enforcer(mut query: Query<(&mut TnuaController, &TnuaCrawlEnforcer)>) {
+    for (mut controller, enforcer) in query.iter_mut() {
+        if !enforcer.forced_crawling {
+            continue;
+        }
+        let mut crawl = enforcer.crawl.clone();
+        crawl.uncancellable = true;
+        controller.action(crawl);
+        if let Some((walk, _)) = controller.concrete_basis::<TnuaBuiltinWalk>() {
+            let mut walk = walk.clone();
+            walk.desired_velocity *= enforcer.speed_factor;
+            controller.basis(walk);
+        }
+    }
+}
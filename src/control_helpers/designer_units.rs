@@ -0,0 +1,185 @@
+use crate::builtins::{TnuaBuiltinJump, TnuaBuiltinWalk};
+use crate::math::Float;
+
+/// Converts values authored in "designer units" - tiles and frames - into the world units and
+/// seconds Tnua works with.
+///
+/// This is mostly useful for 2D pixel-art games, where level designers think of a jump as "3
+/// tiles high" and of a timing window as "6 frames", not in meters and seconds.
+///
+/// ```
+/// # use bevy_tnua::control_helpers::TnuaDesignerUnits;
+/// // Tiles of 16 pixels, where one pixel is one world unit.
+/// let units = TnuaDesignerUnits {
+///     tile_size: 16.0,
+///     ..Default::default()
+/// };
+/// assert_eq!(units.tiles(3.0), 48.0);
+/// assert_eq!(units.frames(6.0), 0.1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TnuaDesignerUnits {
+    /// The size, in world units, of a single tile.
+    pub tile_size: Float,
+
+    /// The number of frames in a second that frame counts refer to.
+    ///
+    /// This does not have to match the actual frame rate of the game - it is only used to convert
+    /// frame counts to seconds.
+    pub frame_rate: Float,
+}
+
+impl Default for TnuaDesignerUnits {
+    fn default() -> Self {
+        Self {
+            tile_size: 1.0,
+            frame_rate: 60.0,
+        }
+    }
+}
+
+impl TnuaDesignerUnits {
+    /// Convert a distance in tiles to world units.
+    pub fn tiles(&self, tiles: Float) -> Float {
+        tiles * self.tile_size
+    }
+
+    /// Convert a speed in tiles per second to world units per second.
+    pub fn tiles_per_second(&self, tiles_per_second: Float) -> Float {
+        tiles_per_second * self.tile_size
+    }
+
+    /// Convert an acceleration in tiles per second squared to world units per second squared.
+    pub fn tiles_per_second_squared(&self, tiles_per_second_squared: Float) -> Float {
+        tiles_per_second_squared * self.tile_size
+    }
+
+    /// Convert a duration in frames to seconds.
+    pub fn frames(&self, frames: Float) -> Float {
+        frames / self.frame_rate
+    }
+}
+
+/// The movement of a character, specified in [designer units](TnuaDesignerUnits).
+///
+/// Use [`walk`](Self::walk) and [`jump`](Self::jump) to get the configuration of the builtin basis
+/// and action. The distances, speeds and extra gravities the builtin defaults use are treated as
+/// if they were in tiles, so that they scale with [`tile_size`](TnuaDesignerUnits::tile_size).
+/// Fields that are not listed here keep their default values, and since the returned values are
+/// regular config structs they can be overridden like any other configuration:
+///
+/// ```
+/// # use bevy_tnua::control_helpers::{TnuaDesignerMovement, TnuaDesignerUnits};
+/// # use bevy_tnua::builtins::TnuaBuiltinWalk;
+/// # use bevy_tnua::math::Vector3;
+/// let units = TnuaDesignerUnits {
+///     tile_size: 16.0,
+///     ..Default::default()
+/// };
+/// let movement = TnuaDesignerMovement {
+///     float_height: 1.0,
+///     walk_speed: 8.0,
+///     jump_height: 3.5,
+///     ..Default::default()
+/// };
+/// assert_eq!(movement.jump(&units).height, 56.0);
+///
+/// let walk = TnuaBuiltinWalk {
+///     desired_velocity: Vector3::X * movement.walk_speed(&units),
+///     ..movement.walk(&units)
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct TnuaDesignerMovement {
+    /// The [`float_height`](TnuaBuiltinWalk::float_height) of the walk basis, in tiles.
+    pub float_height: Float,
+
+    /// The speed of the character when walking at full speed, in tiles per second.
+    ///
+    /// This is not used directly by the walk basis (since it depends on the input) - use
+    /// [`walk_speed`](Self::walk_speed) to multiply the input direction by it.
+    pub walk_speed: Float,
+
+    /// The number of frames it takes the character to reach full speed from a standstill on the
+    /// ground.
+    pub acceleration_frames: Float,
+
+    /// The number of frames it takes the character to reach full speed from a standstill in the
+    /// air.
+    pub air_acceleration_frames: Float,
+
+    /// The [`height`](TnuaBuiltinJump::height) of a full jump, in tiles.
+    pub jump_height: Float,
+
+    /// The [`coyote_time`](TnuaBuiltinWalk::coyote_time), in frames.
+    pub coyote_time_frames: Float,
+
+    /// The [`input_buffer_time`](TnuaBuiltinJump::input_buffer_time) of the jump, in frames.
+    pub jump_input_buffer_frames: Float,
+}
+
+impl Default for TnuaDesignerMovement {
+    fn default() -> Self {
+        Self {
+            float_height: 0.0,
+            walk_speed: 8.0,
+            acceleration_frames: 6.0,
+            air_acceleration_frames: 12.0,
+            jump_height: 3.0,
+            coyote_time_frames: 9.0,
+            jump_input_buffer_frames: 12.0,
+        }
+    }
+}
+
+impl TnuaDesignerMovement {
+    /// The walk speed, in world units per second.
+    pub fn walk_speed(&self, units: &TnuaDesignerUnits) -> Float {
+        units.tiles_per_second(self.walk_speed)
+    }
+
+    /// The walk basis, with zero [`desired_velocity`](TnuaBuiltinWalk::desired_velocity).
+    pub fn walk(&self, units: &TnuaDesignerUnits) -> TnuaBuiltinWalk {
+        let walk_speed = self.walk_speed(units);
+        let acceleration_from_frames = |frames: Float| {
+            let duration = units.frames(frames);
+            if 0.0 < duration {
+                walk_speed / duration
+            } else {
+                Float::INFINITY
+            }
+        };
+        let default = TnuaBuiltinWalk::default();
+        TnuaBuiltinWalk {
+            float_height: units.tiles(self.float_height),
+            cling_distance: units.tiles(default.cling_distance),
+            snap_to_ground_distance: units.tiles(default.snap_to_ground_distance),
+            short_hop_max_distance: units.tiles(default.short_hop_max_distance),
+            acceleration: acceleration_from_frames(self.acceleration_frames),
+            air_acceleration: acceleration_from_frames(self.air_acceleration_frames),
+            coyote_time: units.frames(self.coyote_time_frames),
+            free_fall_extra_gravity: units
+                .tiles_per_second_squared(default.free_fall_extra_gravity),
+            ..default
+        }
+    }
+
+    /// The jump action, at full height.
+    pub fn jump(&self, units: &TnuaDesignerUnits) -> TnuaBuiltinJump {
+        let default = TnuaBuiltinJump::default();
+        TnuaBuiltinJump {
+            height: units.tiles(self.jump_height),
+            input_buffer_time: units.frames(self.jump_input_buffer_frames),
+            upslope_extra_gravity: units.tiles_per_second_squared(default.upslope_extra_gravity),
+            takeoff_extra_gravity: units.tiles_per_second_squared(default.takeoff_extra_gravity),
+            takeoff_above_velocity: units.tiles_per_second(default.takeoff_above_velocity),
+            fall_extra_gravity: units.tiles_per_second_squared(default.fall_extra_gravity),
+            shorten_extra_gravity: units.tiles_per_second_squared(default.shorten_extra_gravity),
+            peak_prevention_at_upward_velocity: units
+                .tiles_per_second(default.peak_prevention_at_upward_velocity),
+            peak_prevention_extra_gravity: units
+                .tiles_per_second_squared(default.peak_prevention_extra_gravity),
+            ..default
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::{TnuaPipelineStages, TnuaRigidBodyTracker, TnuaToggle};
+
+use super::subservient_probe::{ProbeSensorsQuery, SubservientProbe};
+
+/// A plugin required for making [`TnuaFeetSensors`] work.
+pub struct TnuaFeetSensorsPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaFeetSensorsPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaFeetSensorsPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaFeetSensorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            update_feet_sensors.in_set(TnuaPipelineStages::SubservientSensors),
+        );
+    }
+}
+
+/// The configuration of a single foot probe of [`TnuaFeetSensors`].
+#[derive(Clone, Debug)]
+pub struct TnuaFootProbe {
+    /// The origin of the probe, in the character's coord system.
+    ///
+    /// Should typically be placed horizontally above the foot, around the height of the hips, so
+    /// that the probe can find steps that are higher than the foot's resting position.
+    pub origin: Vector3,
+
+    /// The range of the probe, downward from its origin.
+    pub range: Float,
+}
+
+/// The ground found below a foot by [`TnuaFeetSensors`].
+#[derive(Clone, Copy, Debug)]
+pub struct TnuaFootGround {
+    /// The entity of the ground.
+    pub entity: Entity,
+
+    /// The point on the ground below the foot, in world coordinates.
+    pub point: Vector3,
+
+    /// The normal of the ground at that point.
+    pub normal: Dir3,
+}
+
+/// Finds the ground below each of the character's feet, for placing them with foot IK on stairs
+/// and slopes.
+///
+/// Each [foot probe](TnuaFootProbe) is a proximity sensor on a child entity, cast downward, and
+/// its result is available from [`ground`](Self::ground) in the same frame the character's main
+/// proximity sensor is updated.
+///
+/// Using it requires:
+///
+/// 1. Adding the plugin [`TnuaFeetSensorsPlugin`].
+/// 2. Adding [`TnuaFeetSensors`] as a component to the character entity.
+/// 3. Reading the ground points in the IK system, which should run after
+///    [`TnuaPipelineStages::SubservientSensors`]:
+///     ```no_run
+///     # use bevy_tnua::control_helpers::{TnuaFeetSensors, TnuaFootProbe};
+///     # use bevy_tnua::math::Vector3;
+///     let feet_sensors = TnuaFeetSensors::new([
+///         TnuaFootProbe {
+///             origin: Vector3::new(-0.2, 0.0, 0.0),
+///             range: 1.5,
+///         },
+///         TnuaFootProbe {
+///             origin: Vector3::new(0.2, 0.0, 0.0),
+///             range: 1.5,
+///         },
+///     ]);
+///     // ...
+///     if let Some(left_foot_ground) = feet_sensors.ground(0) {
+///         // Place the left foot on `left_foot_ground.point`
+///     }
+///     ```
+///
+/// Like with [`TnuaCrouchEnforcer`](crate::control_helpers::TnuaCrouchEnforcer), the sensor
+/// entities can be modified to cast a shape instead of a ray (see
+/// [`with_probe_modifier`](Self::with_probe_modifier)).
+#[derive(Component)]
+pub struct TnuaFeetSensors {
+    probes: Vec<(TnuaFootProbe, SubservientProbe, Option<TnuaFootGround>)>,
+    modify_probes: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+}
+
+impl TnuaFeetSensors {
+    /// Create the sensors with a probe for each foot. The feet are indexed in the order of the
+    /// probes.
+    pub fn new(probes: impl IntoIterator<Item = TnuaFootProbe>) -> Self {
+        Self {
+            probes: probes
+                .into_iter()
+                .map(|probe| (probe, Default::default(), None))
+                .collect(),
+            modify_probes: Box::new(|_| {}),
+        }
+    }
+
+    /// Set a function that will be called with the commands that create the probes. This function
+    /// has the opportunity to add things to the probe entities - mostly cast-shape components.
+    pub fn with_probe_modifier(
+        mut self,
+        modify_probes: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        self.modify_probes = Box::new(modify_probes);
+        self
+    }
+
+    /// The configuration of a foot's probe, for changing it at runtime.
+    pub fn probe_mut(&mut self, foot: usize) -> Option<&mut TnuaFootProbe> {
+        Some(&mut self.probes.get_mut(foot)?.0)
+    }
+
+    /// The ground below a foot, if the probe found any.
+    pub fn ground(&self, foot: usize) -> Option<TnuaFootGround> {
+        self.probes.get(foot)?.2
+    }
+
+    /// The ground below all the feet, in the order of the probes.
+    pub fn grounds(&self) -> impl '_ + Iterator<Item = Option<TnuaFootGround>> {
+        self.probes.iter().map(|(_, _, ground)| *ground)
+    }
+}
+
+fn update_feet_sensors(
+    mut query: Query<(
+        Entity,
+        &mut TnuaFeetSensors,
+        &TnuaRigidBodyTracker,
+        Option<&TnuaToggle>,
+    )>,
+    mut sensors_query: ProbeSensorsQuery,
+    mut commands: Commands,
+) {
+    for (owner_entity, mut feet_sensors, tracker, tnua_toggle) in query.iter_mut() {
+        let feet_sensors = feet_sensors.as_mut();
+        let disabled = tnua_toggle.copied().unwrap_or_default() == TnuaToggle::Disabled;
+        let up_direction = Dir3::new(-tracker.gravity.f32());
+        let (Ok(up_direction), false) = (up_direction, disabled) else {
+            for (_, subservient_probe, ground) in feet_sensors.probes.iter_mut() {
+                *ground = None;
+                subservient_probe.turn_off(&mut sensors_query);
+            }
+            continue;
+        };
+        let up = up_direction.adjust_precision();
+
+        for (probe, subservient_probe, ground) in feet_sensors.probes.iter_mut() {
+            let origin = tracker.translation + tracker.rotation * probe.origin;
+            *ground = subservient_probe
+                .output(&sensors_query)
+                .map(|hit| TnuaFootGround {
+                    entity: hit.entity,
+                    point: origin - hit.proximity * up,
+                    normal: hit.normal,
+                });
+            subservient_probe.cast(
+                owner_entity,
+                &mut sensors_query,
+                &mut commands,
+                probe.origin,
+                -up_direction,
+                probe.range,
+                &feet_sensors.modify_probes,
+            );
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::time::Stopwatch;
+
+use crate::controller::TnuaController;
+use crate::math::Float;
+use crate::{TnuaActionInitiationDirective, TnuaControllerEvent};
+
+/// Decide whether an action that is being fed should start, using input buffering.
+///
+/// Returns [`Allow`](TnuaActionInitiationDirective::Allow) if the action `can_start` right now,
+/// [`Delay`](TnuaActionInitiationDirective::Delay) if it cannot but has been fed for less than
+/// `input_buffer_time` seconds (so it will start as soon as it can), and
+/// [`Reject`](TnuaActionInitiationDirective::Reject) otherwise.
+///
+/// This is the logic [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump) and
+/// [`TnuaBuiltinDash`](crate::builtins::TnuaBuiltinDash) use for their `input_buffer_time`, and it
+/// can be used in the [`initiation_decision`](crate::TnuaAction::initiation_decision) of custom
+/// actions:
+///
+/// ```no_run
+/// # use bevy::time::Stopwatch;
+/// # use bevy_tnua::control_helpers::buffered_initiation_decision;
+/// # use bevy_tnua::{TnuaActionContext, TnuaActionInitiationDirective};
+/// # struct MyAction { input_buffer_time: f32 }
+/// # impl MyAction {
+/// fn initiation_decision(
+///     &self,
+///     ctx: TnuaActionContext,
+///     being_fed_for: &Stopwatch,
+/// ) -> TnuaActionInitiationDirective {
+///     buffered_initiation_decision(
+///         !ctx.basis.is_airborne(),
+///         being_fed_for,
+///         self.input_buffer_time,
+///     )
+/// }
+/// # }
+/// ```
+pub fn buffered_initiation_decision(
+    can_start: bool,
+    being_fed_for: &Stopwatch,
+    input_buffer_time: Float,
+) -> TnuaActionInitiationDirective {
+    if can_start {
+        TnuaActionInitiationDirective::Allow
+    } else if (being_fed_for.elapsed().as_secs_f64() as Float) < input_buffer_time {
+        TnuaActionInitiationDirective::Delay
+    } else {
+        TnuaActionInitiationDirective::Reject
+    }
+}
+
+/// Remembers an input for a short time, so that pressing a button slightly before the move it
+/// triggers becomes possible still performs the move.
+///
+/// Tnua's actions get this from [`buffered_initiation_decision`], because they are fed every
+/// frame the button is held. This helper is for moves the game performs by itself (e.g. attacks)
+/// or for actions that are triggered by a single press rather than by holding the button:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaBufferedInput;
+/// # use bevy_tnua::math::Float;
+/// # let keyboard: ButtonInput<KeyCode> = panic!();
+/// # let time: Time = panic!();
+/// # let can_attack = true;
+/// # fn attack(_: u8) {}
+/// # let mut buffered_attack = TnuaBufferedInput::<u8>::new(0.2);
+/// // Every frame:
+/// buffered_attack.update(time.delta_seconds() as Float);
+/// if keyboard.just_pressed(KeyCode::KeyX) {
+///     buffered_attack.press(1); // the combo step to perform
+/// }
+/// if can_attack {
+///     if let Some(combo_step) = buffered_attack.take() {
+///         attack(combo_step);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TnuaBufferedInput<T> {
+    /// The time, in seconds, an input is remembered after being pressed.
+    pub buffer_time: Float,
+
+    pending: Option<(T, Float)>,
+}
+
+impl<T> Default for TnuaBufferedInput<T> {
+    fn default() -> Self {
+        Self::new(0.2)
+    }
+}
+
+impl<T> TnuaBufferedInput<T> {
+    /// Create a buffer that remembers inputs for `buffer_time` seconds.
+    pub fn new(buffer_time: Float) -> Self {
+        Self {
+            buffer_time,
+            pending: None,
+        }
+    }
+
+    /// Advance the time of the buffered input, and forget it if it expired.
+    ///
+    /// Call this every frame, before [`press`](Self::press).
+    pub fn update(&mut self, frame_duration: Float) {
+        if let Some((_, age)) = self.pending.as_mut() {
+            *age += frame_duration;
+            if self.buffer_time < *age {
+                self.pending = None;
+            }
+        }
+    }
+
+    /// Buffer an input, replacing the previously buffered one.
+    pub fn press(&mut self, input: T) {
+        self.pending = Some((input, 0.0));
+    }
+
+    /// The buffered input, if there is one that did not expire.
+    pub fn pending(&self) -> Option<&T> {
+        Some(&self.pending.as_ref()?.0)
+    }
+
+    /// The time, in seconds, since the buffered input was pressed.
+    pub fn age(&self) -> Option<Float> {
+        Some(self.pending.as_ref()?.1)
+    }
+
+    /// Remove the buffered input, returning it. Call this when performing the move it triggers.
+    pub fn take(&mut self) -> Option<T> {
+        Some(self.pending.take()?.0)
+    }
+
+    /// Forget the buffered input.
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// Lets the character perform ground moves for a short time after it left the ground.
+///
+/// [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) already delays considering the character
+/// airborne by its [`coyote_time`](crate::builtins::TnuaBuiltinWalk::coyote_time), which is what
+/// the builtin jump uses. This helper provides the same grace time for other moves - e.g. letting
+/// a dash or an attack start as if from the ground after running off a ledge - with a duration
+/// that can be different for each of them:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaCoyoteTracker;
+/// # use bevy_tnua::math::Float;
+/// # let controller = TnuaController::default();
+/// # let time: Time = panic!();
+/// # let attack_pressed = true;
+/// # fn ground_attack() {}
+/// let mut ground_attack_coyote = TnuaCoyoteTracker::new(0.1);
+/// // Every frame:
+/// ground_attack_coyote.update_from_controller(&controller, time.delta_seconds() as Float);
+/// if attack_pressed && ground_attack_coyote.can_act_as_grounded() {
+///     ground_attack();
+///     // Do not allow another ground attack until the character lands:
+///     ground_attack_coyote.violate();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TnuaCoyoteTracker {
+    /// The time, in seconds, after leaving the ground, during which the character can still act
+    /// as if it is grounded.
+    pub coyote_time: Float,
+
+    airborne_for: Option<Float>,
+    violated: bool,
+}
+
+impl Default for TnuaCoyoteTracker {
+    fn default() -> Self {
+        Self::new(0.15)
+    }
+}
+
+impl TnuaCoyoteTracker {
+    /// Create a tracker with the given coyote time.
+    pub fn new(coyote_time: Float) -> Self {
+        Self {
+            coyote_time,
+            airborne_for: Some(Float::INFINITY),
+            violated: false,
+        }
+    }
+
+    /// Track whether the character is grounded. Call this every frame.
+    pub fn update(&mut self, grounded: bool, frame_duration: Float) {
+        if grounded {
+            self.airborne_for = None;
+            self.violated = false;
+        } else if let Some(airborne_for) = self.airborne_for.as_mut() {
+            *airborne_for += frame_duration;
+        } else {
+            self.airborne_for = Some(0.0);
+        }
+    }
+
+    /// Track whether the character is grounded, according to the controller. Call this every
+    /// frame.
+    ///
+    /// The character is considered grounded when its basis reports a
+    /// [float offset](TnuaController::float_offset) - which, unlike
+    /// [`is_airborne`](TnuaController::is_airborne), does not include the basis' own coyote time.
+    /// After a [`teleport_reset`](TnuaController::teleport_reset), the tracker starts over as if
+    /// the character was never on the ground.
+    pub fn update_from_controller(&mut self, controller: &TnuaController, frame_duration: Float) {
+        if controller
+            .events()
+            .any(|event| matches!(event, TnuaControllerEvent::TeleportReset))
+        {
+            self.airborne_for = Some(Float::INFINITY);
+            self.violated = false;
+        }
+        self.update(controller.float_offset().is_some(), frame_duration);
+    }
+
+    /// Whether the character is on the ground.
+    pub fn is_grounded(&self) -> bool {
+        self.airborne_for.is_none()
+    }
+
+    /// The time, in seconds, since the character left the ground, or `None` if it is grounded.
+    pub fn airborne_for(&self) -> Option<Float> {
+        self.airborne_for
+    }
+
+    /// Whether the character can act as if it is grounded - either because it is, or because it
+    /// left the ground less than [`coyote_time`](Self::coyote_time) ago and the coyote time was
+    /// not [violated](Self::violate).
+    pub fn can_act_as_grounded(&self) -> bool {
+        match self.airborne_for {
+            None => true,
+            Some(airborne_for) => !self.violated && airborne_for < self.coyote_time,
+        }
+    }
+
+    /// End the coyote time until the character lands again - typically because the character
+    /// performed the move it allows.
+    pub fn violate(&mut self) {
+        self.violated = true;
+    }
+}
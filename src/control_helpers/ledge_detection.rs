@@ -0,0 +1,280 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::builtins::{TnuaBuiltinLedgeGrab, TnuaBuiltinLedgeGrabState, TnuaLedge};
+use crate::controller::TnuaController;
+use crate::{TnuaPipelineStages, TnuaRigidBodyTracker};
+
+use super::subservient_probe::{ProbeSensorsQuery, SubservientProbe};
+
+/// A plugin required for making [`TnuaLedgeDetector`] work.
+pub struct TnuaLedgeDetectorPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaLedgeDetectorPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaLedgeDetectorPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaLedgeDetectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            update_ledge_detector.in_set(TnuaPipelineStages::SubservientSensors),
+        );
+    }
+}
+
+/// Detects ledges in front of the character, for use with
+/// [`TnuaBuiltinLedgeGrab`].
+///
+/// The detector uses three proximity sensors on child entities:
+///
+/// * A wall probe, cast forward from [`wall_probe_origin`](Self::wall_probe_origin), that needs to
+///   find a wall.
+/// * A clearance probe, cast forward from [`clearance_probe_origin`](Self::clearance_probe_origin),
+///   that needs to find empty space above that wall.
+/// * A top probe, cast downward from above the wall, that finds the top surface of the ledge.
+///
+/// "Forward" is the negative Z axis of the character model - the same one
+/// [`TnuaBuiltinWalk::desired_forward`](crate::builtins::TnuaBuiltinWalk::desired_forward) sets.
+///
+/// Using it requires:
+///
+/// 1. Adding the plugin [`TnuaLedgeDetectorPlugin`].
+/// 2. Adding [`TnuaLedgeDetector`] as a component to the character entity.
+/// 3. Passing the detected ledge to the action:
+///     ```no_run
+///     # use bevy_tnua::prelude::*;
+///     # use bevy_tnua::builtins::TnuaBuiltinLedgeGrab;
+///     # use bevy_tnua::control_helpers::TnuaLedgeDetector;
+///     # let mut controller = TnuaController::default();
+///     # let ledge_detector = TnuaLedgeDetector::default();
+///     # let (climb_pressed, drop_pressed, jump_pressed) = (false, false, false);
+///     if let Some(ledge) = ledge_detector.ledge() {
+///         controller.action(TnuaBuiltinLedgeGrab {
+///             ledge: Some(ledge),
+///             climb: climb_pressed,
+///             drop: drop_pressed,
+///             jump_off: jump_pressed,
+///             cooling_down_ledge: ledge_detector.cooling_down_ledge(),
+///             ..Default::default()
+///         });
+///     }
+///     ```
+///
+/// While the character performs [`TnuaBuiltinLedgeGrab`], the detector turns its probes off and
+/// keeps reporting the ledge that was detected when the action started. If the character drops
+/// from the ledge because it ran out of
+/// [`max_hang_duration`](TnuaBuiltinLedgeGrab::max_hang_duration), the detector reports that ledge
+/// as [`cooling_down_ledge`](Self::cooling_down_ledge) for
+/// [`regrab_cooldown`](TnuaBuiltinLedgeGrab::regrab_cooldown) seconds.
+#[derive(Component)]
+pub struct TnuaLedgeDetector {
+    /// The origin of the wall probe, in the character's coord system.
+    ///
+    /// Should typically be around the top of the collider.
+    pub wall_probe_origin: Vector3,
+
+    /// The origin of the clearance probe, in the character's coord system.
+    ///
+    /// Should be placed above [`wall_probe_origin`](Self::wall_probe_origin). The ledge is
+    /// detected when it is between the two.
+    pub clearance_probe_origin: Vector3,
+
+    /// The range of the wall and clearance probes.
+    pub probe_range: Float,
+
+    /// How far past the wall the top probe is cast down from.
+    pub top_probe_inset: Float,
+
+    modify_probes: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+    wall_probe: SubservientProbe,
+    clearance_probe: SubservientProbe,
+    top_probe: SubservientProbe,
+    ledge: Option<TnuaLedge>,
+    cooling_down: Option<(Entity, Float)>,
+}
+
+impl Default for TnuaLedgeDetector {
+    fn default() -> Self {
+        Self {
+            wall_probe_origin: 0.5 * Vector3::Y,
+            clearance_probe_origin: 1.0 * Vector3::Y,
+            probe_range: 1.0,
+            top_probe_inset: 0.1,
+            modify_probes: Box::new(|_| {}),
+            wall_probe: Default::default(),
+            clearance_probe: Default::default(),
+            top_probe: Default::default(),
+            ledge: None,
+            cooling_down: None,
+        }
+    }
+}
+
+impl TnuaLedgeDetector {
+    /// Set a function that will be called with the commands that create the probes. This function
+    /// has the opportunity to add things to the probe entities - mostly cast-shape components.
+    pub fn with_probe_modifier(
+        mut self,
+        modify_probes: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        self.modify_probes = Box::new(modify_probes);
+        self
+    }
+
+    /// The currently detected ledge.
+    pub fn ledge(&self) -> Option<TnuaLedge> {
+        self.ledge
+    }
+
+    /// The entity of the ledge the character has recently dropped from after running out of
+    /// [`max_hang_duration`](TnuaBuiltinLedgeGrab::max_hang_duration), for passing to
+    /// [`TnuaBuiltinLedgeGrab::cooling_down_ledge`].
+    pub fn cooling_down_ledge(&self) -> Option<Entity> {
+        Some(self.cooling_down?.0)
+    }
+}
+
+fn update_ledge_detector(
+    mut query: Query<(
+        Entity,
+        &TnuaController,
+        &mut TnuaLedgeDetector,
+        &TnuaRigidBodyTracker,
+    )>,
+    mut sensors_query: ProbeSensorsQuery,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    for (owner_entity, controller, mut detector, tracker) in query.iter_mut() {
+        let detector = detector.as_mut();
+        if let Some((_, remaining)) = detector.cooling_down.as_mut() {
+            *remaining -= frame_duration;
+            if *remaining <= 0.0 {
+                detector.cooling_down = None;
+            }
+        }
+        if let Some((action, state)) = controller.concrete_action::<TnuaBuiltinLedgeGrab>() {
+            if let TnuaBuiltinLedgeGrabState::TimedOut {
+                ledge:
+                    TnuaLedge {
+                        entity: Some(ledge_entity),
+                        ..
+                    },
+            } = state
+            {
+                detector.cooling_down = Some((*ledge_entity, action.regrab_cooldown));
+            }
+            for probe in [
+                &detector.wall_probe,
+                &detector.clearance_probe,
+                &detector.top_probe,
+            ] {
+                probe.turn_off(&mut sensors_query);
+            }
+            continue;
+        }
+
+        let Ok(up_direction) = Dir3::new(-tracker.gravity.f32()) else {
+            continue;
+        };
+        let up = up_direction.adjust_precision();
+        let Ok(forward) = Dir3::new(
+            tracker
+                .rotation
+                .mul_vec3(Vector3::NEG_Z)
+                .reject_from(up)
+                .f32(),
+        ) else {
+            continue;
+        };
+        let to_world =
+            |local_origin: Vector3| tracker.translation + tracker.rotation * local_origin;
+
+        let wall_hit = detector.wall_probe.output(&sensors_query).cloned();
+        let clearance_hit = detector.clearance_probe.output(&sensors_query).is_some();
+        let top_hit = detector.top_probe.output(&sensors_query).cloned();
+
+        detector.ledge = None;
+        if let (Some(wall_hit), false, Some(top_hit)) = (&wall_hit, clearance_hit, top_hit) {
+            let wall_normal = Dir3::new(wall_hit.normal.adjust_precision().reject_from(up).f32());
+            if let (Ok(wall_normal), true) = (wall_normal, 0.0 < top_hit.normal.dot(*up_direction))
+            {
+                let wall_point = to_world(detector.wall_probe_origin)
+                    + wall_hit.proximity * forward.adjust_precision();
+                let top_point =
+                    to_world(detector.top_probe_origin(wall_hit.proximity, forward, tracker))
+                        - top_hit.proximity * up;
+                detector.ledge = Some(TnuaLedge {
+                    point: wall_point + (top_point - wall_point).dot(up) * up,
+                    wall_normal,
+                    entity: Some(wall_hit.entity),
+                });
+            }
+        }
+
+        detector.wall_probe.cast(
+            owner_entity,
+            &mut sensors_query,
+            &mut commands,
+            detector.wall_probe_origin,
+            forward,
+            detector.probe_range,
+            &detector.modify_probes,
+        );
+        detector.clearance_probe.cast(
+            owner_entity,
+            &mut sensors_query,
+            &mut commands,
+            detector.clearance_probe_origin,
+            forward,
+            detector.probe_range,
+            &detector.modify_probes,
+        );
+        if let Some(wall_hit) = wall_hit {
+            detector.top_probe.cast(
+                owner_entity,
+                &mut sensors_query,
+                &mut commands,
+                detector.top_probe_origin(wall_hit.proximity, forward, tracker),
+                -up_direction,
+                detector
+                    .clearance_probe_origin
+                    .distance(detector.wall_probe_origin),
+                &detector.modify_probes,
+            );
+        } else {
+            detector.top_probe.turn_off(&mut sensors_query);
+        }
+    }
+}
+
+impl TnuaLedgeDetector {
+    fn top_probe_origin(
+        &self,
+        wall_proximity: Float,
+        forward: Dir3,
+        tracker: &TnuaRigidBodyTracker,
+    ) -> Vector3 {
+        // The forward probes are cast in world coordinates, but the origin is in the character's
+        // coordinates.
+        self.clearance_probe_origin
+            + tracker.rotation.inverse()
+                * ((wall_proximity + self.top_probe_inset) * forward.adjust_precision())
+    }
+}
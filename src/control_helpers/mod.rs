@@ -6,10 +6,55 @@
 //! customizability as it can provide. This, however, means that some of the advanced features can
 //! be complex to use. This module provides helpers that allow using these features in an easier
 //! although less flexible way.
+mod action_gate;
+#[cfg(feature = "ai")]
+mod ai_adapter;
 mod air_actions_tracking;
+#[cfg(feature = "bevy_animation")]
+mod animation_driver;
+mod archetype;
+mod controls_recorder;
+mod controls_source;
+mod crawl_enforcer;
 mod crouch_enforcer;
+mod designer_units;
+mod feet_sensors;
+mod input_buffering;
+mod ledge_detection;
+mod obstruction_detection;
+mod player_input_routing;
+mod presets;
 mod simple_fall_through_platforms;
+mod subservient_probe;
+#[cfg(feature = "gizmos")]
+mod trajectory_gizmos;
+mod traversal_sensors;
+mod visual_smoothing;
+#[cfg(feature = "ai")]
+mod wander_driver;
 
+pub use action_gate::*;
+#[cfg(feature = "ai")]
+pub use ai_adapter::*;
 pub use air_actions_tracking::*;
+#[cfg(feature = "bevy_animation")]
+pub use animation_driver::*;
+pub use archetype::*;
+pub use controls_recorder::*;
+pub use controls_source::*;
+pub use crawl_enforcer::*;
 pub use crouch_enforcer::*;
+pub use designer_units::*;
+pub use feet_sensors::*;
+pub use input_buffering::*;
+pub use ledge_detection::*;
+pub use obstruction_detection::*;
+pub use player_input_routing::*;
+pub use presets::*;
 pub use simple_fall_through_platforms::*;
+#[cfg(feature = "gizmos")]
+pub use trajectory_gizmos::*;
+pub use traversal_sensors::*;
+pub use visual_smoothing::*;
+#[cfg(feature = "ai")]
+pub use wander_driver::*;
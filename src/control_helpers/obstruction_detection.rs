@@ -0,0 +1,199 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::builtins::{TnuaBuiltinLunge, TnuaBuiltinLungeState, TnuaBuiltinWalk};
+use crate::controller::TnuaController;
+use crate::{TnuaPipelineStages, TnuaRigidBodyTracker};
+
+use super::subservient_probe::{ProbeSensorsQuery, SubservientProbe};
+
+/// A plugin required for making [`TnuaObstructionDetector`] work.
+pub struct TnuaObstructionDetectorPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaObstructionDetectorPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaObstructionDetectorPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaObstructionDetectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            update_obstruction_detector.in_set(TnuaPipelineStages::SubservientSensors),
+        );
+    }
+}
+
+/// Detects when the movement [`TnuaBuiltinWalk`] tries to make is blocked by a wall - e.g. for
+/// playing a pushing animation or a wall-slide sound.
+///
+/// The detector uses a proximity sensor on a child entity, cast from
+/// [`probe_origin`](Self::probe_origin) in the horizontal direction of
+/// [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) - or toward the
+/// [target](TnuaBuiltinLunge::target) while a [`TnuaBuiltinLunge`] is lunging, so that it can be
+/// passed as the lunge's [`obstruction`](TnuaBuiltinLunge::obstruction). When it hits a surface
+/// steep enough to be considered a wall (see [`min_wall_angle`](Self::min_wall_angle)) that faces
+/// against the movement, [`obstruction`](Self::obstruction) reports it.
+///
+/// Using it requires:
+///
+/// 1. Adding the plugin [`TnuaObstructionDetectorPlugin`].
+/// 2. Adding [`TnuaObstructionDetector`] as a component to the character entity.
+/// 3. Reading the obstruction, e.g. in the animating system:
+///     ```no_run
+///     # use bevy_tnua::control_helpers::TnuaObstructionDetector;
+///     # let obstruction_detector = TnuaObstructionDetector::default();
+///     if let Some(obstruction) = obstruction_detector.obstruction() {
+///         if 0.2 < obstruction.duration {
+///             // Play the pushing animation
+///         }
+///     }
+///     ```
+///
+/// Like with [`TnuaCrouchEnforcer`](crate::control_helpers::TnuaCrouchEnforcer), the sensor
+/// entity can be modified to cast a shape instead of a ray (see
+/// [`with_probe_modifier`](Self::with_probe_modifier)).
+#[derive(Component)]
+pub struct TnuaObstructionDetector {
+    /// The origin of the probe, in the character's coord system.
+    ///
+    /// Should typically be placed a little above the bottom of the collider, so that the probe
+    /// will not detect the ground itself.
+    pub probe_origin: Vector3,
+
+    /// The range of the probe.
+    ///
+    /// Should be a little more than the horizontal distance from the probe origin to the edge of
+    /// the collider.
+    pub probe_range: Float,
+
+    /// The minimum angle, in radians, between a surface's normal and the up direction for the
+    /// surface to be considered a wall. Less steep surfaces are slopes the character can walk on.
+    pub min_wall_angle: Float,
+
+    modify_probe: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+    probe: SubservientProbe,
+    obstruction: Option<TnuaObstruction>,
+}
+
+impl Default for TnuaObstructionDetector {
+    fn default() -> Self {
+        Self {
+            probe_origin: Vector3::ZERO,
+            probe_range: 0.6,
+            min_wall_angle: 1.0,
+            modify_probe: Box::new(|_| {}),
+            probe: Default::default(),
+            obstruction: None,
+        }
+    }
+}
+
+/// A wall that blocks the desired movement of the character. See [`TnuaObstructionDetector`].
+#[derive(Clone, Copy, Debug)]
+pub struct TnuaObstruction {
+    /// The entity of the wall.
+    pub entity: Entity,
+
+    /// The normal of the wall, pointing toward the character.
+    pub normal: Dir3,
+
+    /// The time, in seconds, the character has been pressing against this entity.
+    pub duration: Float,
+}
+
+impl TnuaObstructionDetector {
+    /// Set a function that will be called with the command that creates the probe. This function
+    /// has the opportunity to add things to the probe entity - mostly cast-shape components.
+    pub fn with_probe_modifier(
+        mut self,
+        modify_probe: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        self.modify_probe = Box::new(modify_probe);
+        self
+    }
+
+    /// The wall the character is currently pressing against, if any.
+    pub fn obstruction(&self) -> Option<TnuaObstruction> {
+        self.obstruction
+    }
+}
+
+fn update_obstruction_detector(
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &TnuaController,
+        &mut TnuaObstructionDetector,
+        &TnuaRigidBodyTracker,
+    )>,
+    mut sensors_query: ProbeSensorsQuery,
+    mut commands: Commands,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    for (owner_entity, controller, mut detector, tracker) in query.iter_mut() {
+        let detector = detector.as_mut();
+
+        let Ok(up_direction) = Dir3::new(-tracker.gravity.f32()) else {
+            continue;
+        };
+        let up = up_direction.adjust_precision();
+        let desired_movement = match controller.concrete_action::<TnuaBuiltinLunge>() {
+            Some((lunge, TnuaBuiltinLungeState::Lunging { .. })) => {
+                Some(lunge.target - tracker.translation)
+            }
+            _ => controller
+                .concrete_basis::<TnuaBuiltinWalk>()
+                .map(|(walk, _)| walk.desired_velocity),
+        };
+        let direction = desired_movement
+            .and_then(|desired_movement| Dir3::new(desired_movement.reject_from(up).f32()).ok());
+        let Some(direction) = direction else {
+            detector.obstruction = None;
+            detector.probe.turn_off(&mut sensors_query);
+            continue;
+        };
+
+        let previous_obstruction = detector.obstruction.take();
+        if let Some(hit) = detector.probe.output(&sensors_query) {
+            let is_wall = detector.min_wall_angle
+                <= hit.normal.angle_between(*up_direction).adjust_precision();
+            let blocks_movement = hit.normal.dot(*direction) < 0.0;
+            if is_wall && blocks_movement {
+                detector.obstruction = Some(TnuaObstruction {
+                    entity: hit.entity,
+                    normal: hit.normal,
+                    duration: match previous_obstruction {
+                        Some(previous) if previous.entity == hit.entity => {
+                            previous.duration + frame_duration
+                        }
+                        _ => 0.0,
+                    },
+                });
+            }
+        }
+
+        detector.probe.cast(
+            owner_entity,
+            &mut sensors_query,
+            &mut commands,
+            detector.probe_origin,
+            direction,
+            detector.probe_range,
+            &detector.modify_probe,
+        );
+    }
+}
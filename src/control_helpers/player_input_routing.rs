@@ -0,0 +1,312 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::input::gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, Gamepads};
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::{TnuaRigidBodyTracker, TnuaUserControlsSystemSet};
+
+/// A plugin required for making [`TnuaPlayerInput`] work.
+///
+/// Requires Bevy's `InputPlugin` (which is part of `DefaultPlugins`).
+pub struct TnuaPlayerInputPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaPlayerInputPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaPlayerInputPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaPlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TnuaGamepadAssignment>();
+        app.add_systems(
+            self.schedule,
+            (assign_gamepads_system, route_player_input_system)
+                .chain()
+                .before(TnuaUserControlsSystemSet),
+        );
+    }
+}
+
+/// Assignment of gamepads to player slots, for local multiplayer games.
+///
+/// Gamepads are assigned automatically, in the order they connect, to the first free slot. When a
+/// gamepad disconnects its slot is freed, but the other players keep their slots - so a player
+/// whose controller ran out of battery will not make the other players swap characters.
+///
+/// Games that want a "press start to join" flow can modify this resource directly, and set
+/// [`auto_assign`](Self::auto_assign) to `false`.
+#[derive(Resource)]
+pub struct TnuaGamepadAssignment {
+    /// Assign newly connected gamepads to free slots automatically.
+    pub auto_assign: bool,
+
+    /// The gamepad assigned to each player slot.
+    pub slots: Vec<Option<Gamepad>>,
+}
+
+impl Default for TnuaGamepadAssignment {
+    fn default() -> Self {
+        Self {
+            auto_assign: true,
+            slots: Vec::new(),
+        }
+    }
+}
+
+impl TnuaGamepadAssignment {
+    /// The gamepad assigned to a player slot.
+    pub fn gamepad_of(&self, player: usize) -> Option<Gamepad> {
+        self.slots.get(player).copied().flatten()
+    }
+
+    /// The player slot a gamepad is assigned to.
+    pub fn player_of(&self, gamepad: Gamepad) -> Option<usize> {
+        self.slots.iter().position(|slot| *slot == Some(gamepad))
+    }
+}
+
+/// Keyboard keys for moving a character, for players that don't use a gamepad.
+#[derive(Clone, Debug)]
+pub struct TnuaKeyboardMovementKeys {
+    pub forward: Vec<KeyCode>,
+    pub backward: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+}
+
+impl TnuaKeyboardMovementKeys {
+    /// Move with the W, A, S and D keys.
+    pub fn wasd() -> Self {
+        Self {
+            forward: vec![KeyCode::KeyW],
+            backward: vec![KeyCode::KeyS],
+            left: vec![KeyCode::KeyA],
+            right: vec![KeyCode::KeyD],
+        }
+    }
+
+    /// Move with the arrow keys.
+    pub fn arrows() -> Self {
+        Self {
+            forward: vec![KeyCode::ArrowUp],
+            backward: vec![KeyCode::ArrowDown],
+            left: vec![KeyCode::ArrowLeft],
+            right: vec![KeyCode::ArrowRight],
+        }
+    }
+}
+
+/// Routes the input of one local player to the character entity it is added to.
+///
+/// Each frame, before [`TnuaUserControlsSystemSet`], the movement input of the player's gamepad
+/// (and keyboard keys, if set) is converted to a world-space
+/// [`direction`](Self::direction) relative to the player's camera. The control system - which
+/// should run in [`TnuaUserControlsSystemSet`] - can then feed it to the controller without
+/// having to know which player controls which character:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaPlayerInput;
+/// fn apply_controls(
+///     buttons: Res<ButtonInput<GamepadButton>>,
+///     mut query: Query<(&TnuaPlayerInput, &mut TnuaController)>,
+/// ) {
+///     for (player_input, mut controller) in query.iter_mut() {
+///         controller.basis(TnuaBuiltinWalk {
+///             desired_velocity: player_input.direction() * 10.0,
+///             float_height: 1.5,
+///             ..Default::default()
+///         });
+///         if player_input.gamepad_button_pressed(&buttons, GamepadButtonType::South) {
+///             controller.action(TnuaBuiltinJump {
+///                 height: 4.0,
+///                 ..Default::default()
+///             });
+///         }
+///     }
+/// }
+/// ```
+///
+/// The gamepad is taken from the player's slot in [`TnuaGamepadAssignment`].
+#[derive(Component, Clone, Debug)]
+pub struct TnuaPlayerInput {
+    /// The player slot in [`TnuaGamepadAssignment`].
+    pub player: usize,
+
+    /// Keyboard keys this player can also use for moving.
+    pub keyboard: Option<TnuaKeyboardMovementKeys>,
+
+    /// The camera of that player (e.g. its own split-screen viewport camera).
+    ///
+    /// The [`direction`](Self::direction) will be relative to that camera's horizontal
+    /// orientation. When `None`, forward is the negative Z axis.
+    pub camera: Option<Entity>,
+
+    /// The gamepad stick is considered centered when its deflection is smaller than this.
+    pub dead_zone: Float,
+
+    direction: Vector3,
+    gamepad: Option<Gamepad>,
+}
+
+impl TnuaPlayerInput {
+    pub fn new(player: usize) -> Self {
+        Self {
+            player,
+            keyboard: None,
+            camera: None,
+            dead_zone: 0.1,
+            direction: Vector3::ZERO,
+            gamepad: None,
+        }
+    }
+
+    /// Allow the player to move with the keyboard as well.
+    pub fn with_keyboard(mut self, keys: TnuaKeyboardMovementKeys) -> Self {
+        self.keyboard = Some(keys);
+        self
+    }
+
+    /// Make the movement relative to a camera.
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// The direction the player wants to move in, in world coordinates.
+    ///
+    /// The direction is orthogonal to the up direction, and its length is between `0.0` (stick
+    /// centered) and `1.0` (stick fully deflected).
+    pub fn direction(&self) -> Vector3 {
+        self.direction
+    }
+
+    /// The gamepad currently assigned to this player.
+    pub fn gamepad(&self) -> Option<Gamepad> {
+        self.gamepad
+    }
+
+    /// Check if a button is pressed on the player's gamepad.
+    pub fn gamepad_button_pressed(
+        &self,
+        buttons: &ButtonInput<GamepadButton>,
+        button_type: GamepadButtonType,
+    ) -> bool {
+        self.gamepad
+            .is_some_and(|gamepad| buttons.pressed(GamepadButton::new(gamepad, button_type)))
+    }
+
+    /// Check if a button was just pressed on the player's gamepad.
+    pub fn gamepad_button_just_pressed(
+        &self,
+        buttons: &ButtonInput<GamepadButton>,
+        button_type: GamepadButtonType,
+    ) -> bool {
+        self.gamepad
+            .is_some_and(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    }
+}
+
+fn assign_gamepads_system(gamepads: Res<Gamepads>, mut assignment: ResMut<TnuaGamepadAssignment>) {
+    for slot in assignment.slots.iter_mut() {
+        if slot.is_some_and(|gamepad| !gamepads.contains(gamepad)) {
+            *slot = None;
+        }
+    }
+    if !assignment.auto_assign {
+        return;
+    }
+    for gamepad in gamepads.iter() {
+        if assignment.player_of(gamepad).is_some() {
+            continue;
+        }
+        if let Some(free_slot) = assignment.slots.iter_mut().find(|slot| slot.is_none()) {
+            *free_slot = Some(gamepad);
+        } else {
+            assignment.slots.push(Some(gamepad));
+        }
+    }
+}
+
+fn route_player_input_system(
+    assignment: Res<TnuaGamepadAssignment>,
+    axes: Res<Axis<GamepadAxis>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    cameras_query: Query<&GlobalTransform>,
+    mut query: Query<(&mut TnuaPlayerInput, Option<&TnuaRigidBodyTracker>)>,
+) {
+    for (mut player_input, tracker) in query.iter_mut() {
+        let player_input = player_input.as_mut();
+        player_input.gamepad = assignment.gamepad_of(player_input.player);
+
+        // X is right, Y is forward.
+        let mut stick = Vec2::ZERO;
+        if let Some(gamepad) = player_input.gamepad {
+            let axis = |axis_type| {
+                axes.get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or(0.0)
+            };
+            stick = Vec2::new(
+                axis(GamepadAxisType::LeftStickX),
+                axis(GamepadAxisType::LeftStickY),
+            );
+            if stick.length() < player_input.dead_zone.f32() {
+                stick = Vec2::ZERO;
+            }
+        }
+        if let Some(keys) = player_input.keyboard.as_ref() {
+            let any_pressed = |keys: &[KeyCode]| keyboard.any_pressed(keys.iter().copied());
+            if any_pressed(&keys.forward) {
+                stick.y += 1.0;
+            }
+            if any_pressed(&keys.backward) {
+                stick.y -= 1.0;
+            }
+            if any_pressed(&keys.left) {
+                stick.x -= 1.0;
+            }
+            if any_pressed(&keys.right) {
+                stick.x += 1.0;
+            }
+        }
+        let stick = stick.clamp_length_max(1.0).adjust_precision();
+
+        let up = tracker
+            .and_then(|tracker| Dir3::new(-tracker.gravity.f32()).ok())
+            .unwrap_or(Dir3::Y)
+            .adjust_precision();
+        let camera_transform = player_input
+            .camera
+            .and_then(|camera| cameras_query.get(camera).ok());
+        let (camera_forward, camera_up) =
+            camera_transform.map_or((Vector3::NEG_Z, Vector3::Y), |transform| {
+                (
+                    transform.forward().adjust_precision(),
+                    transform.up().adjust_precision(),
+                )
+            });
+        // When the camera looks straight down (like in top-down games), the top of the screen is
+        // the forward direction.
+        let Ok(forward) = Dir3::new(camera_forward.reject_from(up).f32())
+            .or_else(|_| Dir3::new(camera_up.reject_from(up).f32()))
+        else {
+            continue;
+        };
+        let forward = forward.adjust_precision();
+        let right = forward.cross(up);
+
+        player_input.direction = stick.x * right + stick.y * forward;
+    }
+}
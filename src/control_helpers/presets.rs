@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+
+use crate::builtins::{TnuaBuiltinCrouch, TnuaBuiltinDash, TnuaBuiltinJump, TnuaBuiltinWalk};
+use crate::math::{float_consts, AsF32, Float, Vector3};
+
+/// The few inputs that [`TnuaPreset`] scales its configurations by.
+///
+/// The defaults describe the character of the examples - a capsule two units tall that walks at
+/// `10.0` units per second and jumps `4.0` units high.
+#[derive(Clone, Debug)]
+pub struct TnuaPresetParams {
+    /// The full height of the character's collider, from the bottom to the top.
+    pub character_height: Float,
+
+    /// The speed of the character when walking at full speed.
+    pub speed: Float,
+
+    /// The height of a full jump.
+    pub jump_height: Float,
+}
+
+impl Default for TnuaPresetParams {
+    fn default() -> Self {
+        Self {
+            character_height: 2.0,
+            speed: 10.0,
+            jump_height: 4.0,
+        }
+    }
+}
+
+/// Configurations of the builtin basis and actions, tuned for common game genres.
+///
+/// Each preset sets the size, speed and jump related fields of the walk basis and of the jump,
+/// crouch and dash actions from a [`TnuaPresetParams`]:
+///
+/// * The float height is `0.75` of the character's height (assuming the collider's center is
+///   halfway up - if it is not, override [`float_height`](TnuaBuiltinWalk::float_height)), and
+///   the cling distance of the walk basis and the crouch offset scale with it.
+/// * The accelerations of the walk basis and the speed, brake speed and accelerations of the dash
+///   scale with the walking speed, so the character reaches its full speed in the same time
+///   regardless of how fast it is.
+/// * The jump height, the extra gravities of the jump and the free fall extra gravity of the walk
+///   basis scale with the jump height, so jumps of every height take the same time.
+/// * Depending on the genre, the preset also sets the coyote time, the turning speed, the max
+///   slope, the jump input buffering and the jump shortening.
+///
+/// All the other fields keep their `Default` values - e.g. the spring of the walk basis, the fall
+/// speed limits, the jump's landing assist and hold extension, and whether jumps and dashes are
+/// allowed in the air. The caller still has to provide the per-frame inputs: the
+/// [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) and
+/// [`desired_forward`](TnuaBuiltinWalk::desired_forward) of the walk basis (see
+/// [`walk_in_direction`](Self::walk_in_direction)) and the
+/// [`displacement`](TnuaBuiltinDash::displacement) of the dash.
+///
+/// The configurations are regular config structs, so any field can be overridden:
+///
+/// ```
+/// # use bevy_tnua::control_helpers::{TnuaPreset, TnuaPresetParams};
+/// # use bevy_tnua::builtins::TnuaBuiltinJump;
+/// let preset = TnuaPreset::preset_2d_platformer(TnuaPresetParams {
+///     character_height: 1.0,
+///     speed: 5.0,
+///     jump_height: 2.0,
+/// });
+/// assert_eq!(preset.walk.float_height, 0.75);
+/// let reference = TnuaPreset::preset_2d_platformer(Default::default());
+/// assert_eq!(2.0 * preset.walk.acceleration, reference.walk.acceleration);
+///
+/// let jump = TnuaBuiltinJump {
+///     shorten_extra_gravity: 0.0,
+///     ..preset.jump.clone()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct TnuaPreset {
+    /// The walk basis, with zero [`desired_velocity`](TnuaBuiltinWalk::desired_velocity).
+    pub walk: TnuaBuiltinWalk,
+
+    /// The jump action, at full height.
+    pub jump: TnuaBuiltinJump,
+
+    /// The crouch action.
+    pub crouch: TnuaBuiltinCrouch,
+
+    /// The dash action, with zero [`displacement`](TnuaBuiltinDash::displacement).
+    pub dash: TnuaBuiltinDash,
+
+    /// The [`speed`](TnuaPresetParams::speed) the preset was made for.
+    ///
+    /// This is not used directly by the configs (since it depends on the input) - it is provided
+    /// so that the control system can multiply the input direction by it.
+    pub speed: Float,
+}
+
+/// Ratios between the params of a preset and the default params.
+struct PresetScale {
+    length: Float,
+    speed: Float,
+    jump: Float,
+}
+
+impl PresetScale {
+    fn new(params: &TnuaPresetParams) -> Self {
+        let reference = TnuaPresetParams::default();
+        Self {
+            length: params.character_height / reference.character_height,
+            speed: params.speed / reference.speed,
+            jump: params.jump_height / reference.jump_height,
+        }
+    }
+}
+
+impl TnuaPreset {
+    fn scaled(params: &TnuaPresetParams, scale: &PresetScale) -> Self {
+        let walk = TnuaBuiltinWalk::default();
+        let jump = TnuaBuiltinJump::default();
+        let dash = TnuaBuiltinDash::default();
+        Self {
+            walk: TnuaBuiltinWalk {
+                float_height: 0.75 * params.character_height,
+                cling_distance: walk.cling_distance * scale.length,
+                acceleration: walk.acceleration * scale.speed,
+                air_acceleration: walk.air_acceleration * scale.speed,
+                free_fall_extra_gravity: walk.free_fall_extra_gravity * scale.jump,
+                ..walk
+            },
+            // For a jump that takes the same time, both the velocities and the accelerations are
+            // proportional to the height.
+            jump: TnuaBuiltinJump {
+                height: params.jump_height,
+                upslope_extra_gravity: jump.upslope_extra_gravity * scale.jump,
+                takeoff_extra_gravity: jump.takeoff_extra_gravity * scale.jump,
+                takeoff_above_velocity: jump.takeoff_above_velocity * scale.jump,
+                fall_extra_gravity: jump.fall_extra_gravity * scale.jump,
+                shorten_extra_gravity: jump.shorten_extra_gravity * scale.jump,
+                peak_prevention_at_upward_velocity: jump.peak_prevention_at_upward_velocity
+                    * scale.jump,
+                peak_prevention_extra_gravity: jump.peak_prevention_extra_gravity * scale.jump,
+                ..jump
+            },
+            crouch: TnuaBuiltinCrouch {
+                float_offset: -0.45 * params.character_height,
+                ..Default::default()
+            },
+            dash: TnuaBuiltinDash {
+                speed: dash.speed * scale.speed,
+                brake_to_speed: 2.0 * params.speed,
+                acceleration: dash.acceleration * scale.speed,
+                brake_acceleration: dash.brake_acceleration * scale.speed,
+                ..dash
+            },
+            speed: params.speed,
+        }
+    }
+
+    /// A side-scrolling platformer - snappy acceleration, strong air control, variable jump
+    /// height and generous timing windows.
+    pub fn preset_2d_platformer(params: TnuaPresetParams) -> Self {
+        let scale = PresetScale::new(&params);
+        let mut preset = Self::scaled(&params, &scale);
+        preset.walk.acceleration = 90.0 * scale.speed;
+        preset.walk.air_acceleration = 60.0 * scale.speed;
+        preset.walk.coyote_time = 0.15;
+        preset.walk.max_slope = float_consts::FRAC_PI_4;
+        preset.jump.input_buffer_time = 0.2;
+        preset
+    }
+
+    /// A 3D game with a third person camera - softer acceleration and limited air control, with
+    /// the character turning toward its movement direction.
+    pub fn preset_3d_third_person(params: TnuaPresetParams) -> Self {
+        let scale = PresetScale::new(&params);
+        let mut preset = Self::scaled(&params, &scale);
+        preset.walk.acceleration = 60.0 * scale.speed;
+        preset.walk.air_acceleration = 20.0 * scale.speed;
+        preset.walk.coyote_time = 0.15;
+        preset.walk.turning_angvel = 10.0;
+        preset.walk.max_slope = float_consts::FRAC_PI_3;
+        preset
+    }
+
+    /// A top-down game - near instant acceleration and fast turning, with fixed height jumps and a
+    /// short dash.
+    pub fn preset_top_down(params: TnuaPresetParams) -> Self {
+        let scale = PresetScale::new(&params);
+        let mut preset = Self::scaled(&params, &scale);
+        preset.walk.acceleration = 120.0 * scale.speed;
+        preset.walk.air_acceleration = 120.0 * scale.speed;
+        preset.walk.coyote_time = 0.1;
+        preset.walk.turning_angvel = 20.0;
+        preset.walk.max_slope = float_consts::FRAC_PI_4;
+        preset.jump.shorten_extra_gravity = 0.0;
+        preset.dash.speed = 4.0 * params.speed;
+        preset
+    }
+
+    /// The walk basis, moving at [`speed`](Self::speed) times `direction` and facing it.
+    ///
+    /// The length of `direction` should be between zero and one.
+    pub fn walk_in_direction(&self, direction: Vector3) -> TnuaBuiltinWalk {
+        TnuaBuiltinWalk {
+            desired_velocity: self.speed * direction,
+            desired_forward: Dir3::new(direction.f32()).ok(),
+            ..self.walk.clone()
+        }
+    }
+}
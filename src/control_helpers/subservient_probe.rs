@@ -0,0 +1,74 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::data_for_backends::TnuaProximitySensorOutput;
+
+use crate::math::{Float, Vector3};
+use crate::subservient_sensors::TnuaSubservientSensor;
+use crate::TnuaProximitySensor;
+
+pub(crate) type ProbeSensorsQuery<'w, 's> =
+    Query<'w, 's, (&'static mut TnuaProximitySensor, Has<TnuaSubservientSensor>)>;
+
+/// A [`TnuaSubservientSensor`] on a child entity, spawned lazily when it first needs to cast.
+#[derive(Default)]
+pub(crate) struct SubservientProbe {
+    entity: Option<Entity>,
+}
+
+impl SubservientProbe {
+    pub(crate) fn output<'a>(
+        &self,
+        sensors_query: &'a ProbeSensorsQuery,
+    ) -> Option<&'a TnuaProximitySensorOutput> {
+        let (sensor, is_subservient) = sensors_query.get(self.entity?).ok()?;
+        if is_subservient {
+            sensor.output.as_ref()
+        } else {
+            None
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn cast(
+        &mut self,
+        owner_entity: Entity,
+        sensors_query: &mut ProbeSensorsQuery,
+        commands: &mut Commands,
+        cast_origin: Vector3,
+        cast_direction: Dir3,
+        cast_range: Float,
+        modify_sensor: &dyn Fn(&mut EntityCommands),
+    ) {
+        if let Some((mut sensor, true)) = self
+            .entity
+            .and_then(|entity| sensors_query.get_mut(entity).ok())
+        {
+            sensor.cast_origin = cast_origin;
+            sensor.cast_direction = cast_direction;
+            sensor.cast_range = cast_range;
+        } else {
+            let mut cmd = commands.spawn((
+                TransformBundle::default(),
+                TnuaSubservientSensor { owner_entity },
+                TnuaProximitySensor {
+                    cast_origin,
+                    cast_direction,
+                    cast_range,
+                    ..Default::default()
+                },
+            ));
+            cmd.set_parent(owner_entity);
+            modify_sensor(&mut cmd);
+            self.entity = Some(cmd.id());
+        }
+    }
+
+    pub(crate) fn turn_off(&self, sensors_query: &mut ProbeSensorsQuery) {
+        if let Some((mut sensor, true)) = self
+            .entity
+            .and_then(|entity| sensors_query.get_mut(entity).ok())
+        {
+            sensor.cast_range = 0.0;
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::color::palettes::css;
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::builtins::{TnuaBuiltinJump, TnuaBuiltinWalk};
+use crate::controller::TnuaController;
+use crate::motion_math;
+use crate::{TnuaBasis, TnuaPipelineStages, TnuaRigidBodyTracker};
+
+/// A plugin required for making [`TnuaTrajectoryGizmos`] work.
+///
+/// Only available with the `gizmos` feature.
+pub struct TnuaTrajectoryGizmosPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaTrajectoryGizmosPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaTrajectoryGizmosPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaTrajectoryGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            draw_trajectory_gizmos.after(TnuaPipelineStages::Logic),
+        );
+    }
+}
+
+/// Draws the theoretical movement of a character according to its current configuration, to make
+/// tuning it easier.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) that uses
+/// [`TnuaBuiltinWalk`] as its basis. It draws, with gizmos:
+///
+/// * The arc a full height [`TnuaBuiltinJump`] would follow if the character jumped while moving
+///   at its [`desired_velocity`](TnuaBuiltinWalk::desired_velocity). While the character is in the
+///   air, the arc from the position it left the ground at is kept - so that it can be compared
+///   with the actual jump.
+/// * The point where the character would stop if the player stopped moving it now.
+///
+/// Since the configuration is read every frame, the gizmos update live when tuning the
+/// parameters. The simulation ignores obstacles and air control.
+///
+/// Only available with the `gizmos` feature.
+#[derive(Component)]
+pub struct TnuaTrajectoryGizmos {
+    /// The jump to draw the arc of. When `None`, the arc of the last jump the character performed
+    /// is drawn (and nothing is drawn before the first jump)
+    pub jump: Option<TnuaBuiltinJump>,
+
+    /// The color of the jump arc.
+    pub jump_arc_color: Color,
+
+    /// The color of the stopping distance line.
+    pub stopping_distance_color: Color,
+
+    /// The duration, in seconds, of each step of the jump simulation.
+    ///
+    /// Should typically be the duration of the frame (or the physics step).
+    pub step_duration: Float,
+
+    last_jump: Option<TnuaBuiltinJump>,
+    takeoff: Option<(Vector3, Vector3)>,
+}
+
+impl Default for TnuaTrajectoryGizmos {
+    fn default() -> Self {
+        Self {
+            jump: None,
+            jump_arc_color: css::YELLOW.into(),
+            stopping_distance_color: css::RED.into(),
+            step_duration: 1.0 / 60.0,
+            last_jump: None,
+            takeoff: None,
+        }
+    }
+}
+
+/// Stop simulating jumps that would never land (e.g. because of zero gravity)
+const MAX_SIMULATED_JUMP_DURATION: Float = 10.0;
+
+fn draw_trajectory_gizmos(
+    mut query: Query<(
+        &TnuaController,
+        &TnuaRigidBodyTracker,
+        &mut TnuaTrajectoryGizmos,
+    )>,
+    mut gizmos: Gizmos,
+) {
+    for (controller, tracker, mut trajectory_gizmos) in query.iter_mut() {
+        let trajectory_gizmos = trajectory_gizmos.as_mut();
+        if let Some((jump, _)) = controller.concrete_action::<TnuaBuiltinJump>() {
+            trajectory_gizmos.last_jump = Some(jump.clone());
+        }
+        let Some((walk, walk_state)) = controller.concrete_basis::<TnuaBuiltinWalk>() else {
+            continue;
+        };
+        let Ok(up_direction) = Dir3::new(-tracker.gravity.f32()) else {
+            continue;
+        };
+        let up = up_direction.adjust_precision();
+
+        if let Some(displacement) = walk.displacement(walk_state) {
+            let ground_position = tracker.translation - displacement;
+            trajectory_gizmos.takeoff = Some((ground_position, walk.desired_velocity));
+
+            let running_velocity = walk_state.running_velocity;
+            let speed = running_velocity.length();
+            if 0.0 < speed {
+                let stopping_distance = motion_math::stopping_distance(
+                    speed,
+                    walk.acceleration * walk.acceleration_curve.multiplier(0.0),
+                );
+                let stop_at = ground_position + running_velocity * (stopping_distance / speed);
+                gizmos.line(
+                    ground_position.f32(),
+                    stop_at.f32(),
+                    trajectory_gizmos.stopping_distance_color,
+                );
+                gizmos.circle(
+                    stop_at.f32(),
+                    up_direction,
+                    0.25,
+                    trajectory_gizmos.stopping_distance_color,
+                );
+            }
+        }
+
+        let Some(jump) = trajectory_gizmos
+            .jump
+            .as_ref()
+            .or(trajectory_gizmos.last_jump.as_ref())
+        else {
+            continue;
+        };
+        let Some((takeoff_position, horizontal_velocity)) = trajectory_gizmos.takeoff else {
+            continue;
+        };
+        let step_duration = trajectory_gizmos.step_duration;
+        let max_steps = (MAX_SIMULATED_JUMP_DURATION / step_duration) as usize;
+        let arc = motion_math::simulate_jump(jump, tracker.gravity.length(), step_duration)
+            .take(max_steps)
+            .enumerate()
+            .map(|(step, (height, _))| {
+                let time = (step + 1) as Float * step_duration;
+                (takeoff_position + time * horizontal_velocity + height * up).f32()
+            });
+        gizmos.linestrip(
+            std::iter::once(takeoff_position.f32()).chain(arc),
+            trajectory_gizmos.jump_arc_color,
+        );
+    }
+}
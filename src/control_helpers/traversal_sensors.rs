@@ -0,0 +1,73 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{Float, Vector3};
+
+use super::{TnuaLedgeDetector, TnuaObstructionDetector};
+
+/// The sensors used for traversal moves - mantling, vaulting and wall interactions - placed
+/// according to the size of the character's collider.
+///
+/// This bundle contains:
+///
+/// * A [`TnuaObstructionDetector`] with a probe cast forward low on the collider, for detecting
+///   walls and low obstacles.
+/// * A [`TnuaLedgeDetector`], with its wall probe cast forward near the top of the collider, its
+///   clearance probe cast forward above the collider, and its top probe cast down onto the ledge.
+///
+/// The probes are only placed by the bundle - the detectors still require their plugins,
+/// [`TnuaObstructionDetectorPlugin`](super::TnuaObstructionDetectorPlugin) and
+/// [`TnuaLedgeDetectorPlugin`](super::TnuaLedgeDetectorPlugin).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaTraversalSensorsBundle;
+/// # let mut commands: Commands = panic!();
+/// # let mut cmd = commands.spawn(());
+/// // For a capsule collider with a half height of 0.5 and a radius of 0.5, centered on the
+/// // character entity:
+/// cmd.insert(TnuaTraversalSensorsBundle::for_collider(2.0, 0.5));
+/// ```
+#[derive(Bundle, Default)]
+pub struct TnuaTraversalSensorsBundle {
+    pub obstruction_detector: TnuaObstructionDetector,
+    pub ledge_detector: TnuaLedgeDetector,
+}
+
+impl TnuaTraversalSensorsBundle {
+    /// Place the probes for a collider of the given total height and horizontal radius, centered
+    /// on the character entity.
+    pub fn for_collider(height: Float, radius: Float) -> Self {
+        let half_height = 0.5 * height;
+
+        let mut obstruction_detector = TnuaObstructionDetector::default();
+        // High enough to not hit the ground, low enough to hit obstacles worth vaulting over.
+        obstruction_detector.probe_origin = -0.6 * half_height * Vector3::Y;
+        obstruction_detector.probe_range = radius + 0.1;
+
+        let mut ledge_detector = TnuaLedgeDetector::default();
+        ledge_detector.wall_probe_origin = 0.7 * half_height * Vector3::Y;
+        ledge_detector.clearance_probe_origin = 1.2 * half_height * Vector3::Y;
+        ledge_detector.probe_range = radius + 0.5;
+        ledge_detector.top_probe_inset = 0.5 * radius;
+
+        Self {
+            obstruction_detector,
+            ledge_detector,
+        }
+    }
+
+    /// Set a function that will be called with the commands that create the probes of all the
+    /// sensors. This function has the opportunity to add things to the probe entities - mostly
+    /// cast-shape components.
+    pub fn with_probe_modifier(
+        self,
+        modify_probes: impl 'static + Send + Sync + Clone + Fn(&mut EntityCommands),
+    ) -> Self {
+        Self {
+            obstruction_detector: self
+                .obstruction_detector
+                .with_probe_modifier(modify_probes.clone()),
+            ledge_detector: self.ledge_detector.with_probe_modifier(modify_probes),
+        }
+    }
+}
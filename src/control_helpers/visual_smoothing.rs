@@ -0,0 +1,205 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::controller::TnuaController;
+use crate::{TnuaControllerEvent, TnuaRigidBodyTracker};
+
+/// A plugin required for making [`TnuaVisualSmoothing`] work.
+///
+/// Unlike most Tnua plugins, this one runs at the render rate and not with the physics - so its
+/// default schedule is `PostUpdate` even when the physics runs in `FixedUpdate`.
+pub struct TnuaVisualSmoothingPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaVisualSmoothingPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaVisualSmoothingPlugin {
+    fn default() -> Self {
+        Self::new(PostUpdate)
+    }
+}
+
+impl Plugin for TnuaVisualSmoothingPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            self.schedule,
+            TnuaVisualSmoothingSystemSet.before(TransformSystem::TransformPropagate),
+        );
+        app.add_systems(
+            self.schedule,
+            apply_visual_smoothing_system.in_set(TnuaVisualSmoothingSystemSet),
+        );
+    }
+}
+
+/// The system set of [`TnuaVisualSmoothingPlugin`].
+///
+/// The physics backends write the (possibly interpolated) transforms in `PostUpdate`, so this set
+/// should be ordered after them - e.g. for Rapier:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaVisualSmoothingSystemSet;
+/// # #[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+/// # enum PhysicsSet { Writeback }
+/// # let mut app = App::new();
+/// app.configure_sets(
+///     PostUpdate,
+///     TnuaVisualSmoothingSystemSet.after(PhysicsSet::Writeback),
+/// );
+/// ```
+///
+/// Otherwise, the smoothing may see the position of the character one frame late.
+#[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct TnuaVisualSmoothingSystemSet;
+
+/// Smooths the visual representation of a character when the floating spring moves it abruptly -
+/// e.g. when it steps up a stair.
+///
+/// Tnua's motors change the velocity (and not the position) of the character, so they already
+/// work with the transform interpolation of the physics backends. But when the ground under the
+/// character changes height at once, the spring snaps the character to its new float height within
+/// a few physics frames, and that looks like a stutter no matter how well these frames are
+/// interpolated.
+///
+/// This component tracks the vertical motion of the character that is not matched by the motion
+/// of the ground it floats above, and keeps an opposite [visual offset](Self::offset) that decays
+/// smoothly back to zero. Walking on slopes, jumping and falling are not affected - only the
+/// spring's corrections while standing on the ground are.
+///
+/// Add it to the character entity, and either apply [`offset`](Self::offset) to the render
+/// transform manually or set [`visual_entity`](Self::visual_entity) to a child entity that holds
+/// the model. Requires [`TnuaVisualSmoothingPlugin`].
+#[derive(Component)]
+pub struct TnuaVisualSmoothing {
+    /// A child of the character entity (usually the one with the model) to move by the offset.
+    ///
+    /// The translation the child has when the smoothing starts is considered its rest position.
+    pub visual_entity: Option<Entity>,
+
+    /// How fast the offset decays back to zero. The offset is multiplied by `e` to the power of
+    /// minus this every second.
+    ///
+    /// Higher values make the character follow the spring more closely.
+    pub smoothing_rate: Float,
+
+    /// The maximum distance between the visual representation and the actual position of the
+    /// character.
+    pub max_offset: Float,
+
+    offset: Float,
+    up_direction: Vector3,
+    previous: Option<PreviousHeights>,
+    visual_rest_translation: Option<Vec3>,
+}
+
+struct PreviousHeights {
+    character: Float,
+    ground: Option<Float>,
+}
+
+impl Default for TnuaVisualSmoothing {
+    fn default() -> Self {
+        Self {
+            visual_entity: None,
+            smoothing_rate: 15.0,
+            max_offset: 0.5,
+            offset: 0.0,
+            up_direction: Vector3::Y,
+            previous: None,
+            visual_rest_translation: None,
+        }
+    }
+}
+
+impl TnuaVisualSmoothing {
+    /// Smooth the motion of a child entity of the character.
+    pub fn for_visual_entity(visual_entity: Entity) -> Self {
+        Self {
+            visual_entity: Some(visual_entity),
+            ..Default::default()
+        }
+    }
+
+    /// The offset, in world space, to add to the render position of the character.
+    pub fn offset(&self) -> Vector3 {
+        self.offset * self.up_direction
+    }
+}
+
+fn apply_visual_smoothing_system(
+    time: Res<Time>,
+    mut query: Query<(
+        &Transform,
+        &TnuaController,
+        &TnuaRigidBodyTracker,
+        &mut TnuaVisualSmoothing,
+    )>,
+    mut visuals_query: Query<&mut Transform, Without<TnuaVisualSmoothing>>,
+) {
+    let frame_duration = time.delta_seconds().adjust_precision();
+    for (transform, controller, tracker, mut smoothing) in query.iter_mut() {
+        let smoothing = smoothing.as_mut();
+        if controller
+            .events()
+            .any(|event| matches!(event, TnuaControllerEvent::TeleportReset))
+        {
+            // The motion of a teleport is not something the spring did.
+            smoothing.previous = None;
+            smoothing.offset = 0.0;
+        }
+        let up = (-tracker.gravity).try_normalize().unwrap_or(Vector3::Y);
+        smoothing.up_direction = up;
+
+        let character_height = transform.translation.adjust_precision().dot(up);
+        let ground_height = if controller.is_airborne().unwrap_or(true) {
+            None
+        } else {
+            controller
+                .dynamic_basis()
+                .and_then(|basis| basis.displacement())
+                .map(|displacement| character_height - displacement.dot(up))
+        };
+
+        if let Some(previous) = smoothing.previous.as_ref() {
+            if let (Some(ground_height), Some(previous_ground_height)) =
+                (ground_height, previous.ground)
+            {
+                let character_motion = character_height - previous.character;
+                let ground_motion = ground_height - previous_ground_height;
+                // Only the part of the ground motion in the same direction as the character's
+                // motion explains it.
+                let explained_motion =
+                    ground_motion.clamp(character_motion.min(0.0), character_motion.max(0.0));
+                smoothing.offset -= character_motion - explained_motion;
+            }
+        }
+        smoothing.previous = Some(PreviousHeights {
+            character: character_height,
+            ground: ground_height,
+        });
+        smoothing.offset = (smoothing.offset * (-smoothing.smoothing_rate * frame_duration).exp())
+            .clamp(-smoothing.max_offset, smoothing.max_offset);
+
+        let Some(visual_entity) = smoothing.visual_entity else {
+            continue;
+        };
+        let Ok(mut visual_transform) = visuals_query.get_mut(visual_entity) else {
+            continue;
+        };
+        let rest_translation = *smoothing
+            .visual_rest_translation
+            .get_or_insert(visual_transform.translation);
+        visual_transform.translation =
+            rest_translation + transform.rotation.inverse() * smoothing.offset().f32();
+    }
+}
@@ -0,0 +1,296 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{
+    float_consts, AdjustPrecision, AsF32, Float, Vector3,
+};
+
+use crate::controller::TnuaController;
+use crate::prelude::*;
+use crate::TnuaRigidBodyTracker;
+
+use super::ai_adapter::ai_driver_system;
+use super::subservient_probe::{ProbeSensorsQuery, SubservientProbe};
+use super::{TnuaAiCommand, TnuaAiCommandStatus, TnuaAiDriver, TnuaAiQueries};
+
+/// A plugin required for making [`TnuaWanderDriver`] work.
+pub struct TnuaWanderDriverPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaWanderDriverPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaWanderDriverPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaWanderDriverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            wander_driver_system
+                .in_set(TnuaUserControlsSystemSet)
+                .before(ai_driver_system),
+        );
+    }
+}
+
+/// Drives a character without any input, by making it wander around randomly.
+///
+/// The character walks in a random direction for a random duration, then picks a new direction
+/// (or stands still for a while). When an obstacle is detected in front of it, the character will
+/// try to jump over it - and if that does not help, it'll pick a different direction.
+///
+/// This is useful for soak testing, benchmarks, and background demos for menus (AKA "attract
+/// mode"). It is not meant to be used as actual AI for NPCs.
+///
+/// The wander driver does not feed the [`TnuaController`] directly - it issues
+/// [`TnuaAiCommand`]s to a [`TnuaAiDriver`], the same way an AI crate would. The walk, the jump
+/// and the speed are therefore configured on the [`TnuaAiDriver`].
+///
+/// Using it requires:
+///
+/// 1. Adding the plugins [`TnuaWanderDriverPlugin`] and
+///    [`TnuaAiDriverPlugin`](super::TnuaAiDriverPlugin).
+/// 2. Adding [`TnuaWanderDriver`] and [`TnuaAiDriver`] as components to the character entity,
+///    instead of feeding the [`TnuaController`] from a player controls system.
+///
+/// Obstacles are detected with a proximity sensor on a child entity, cast in the walking
+/// direction from [`obstacle_probe_origin`](Self::obstacle_probe_origin). Like with
+/// [`TnuaCrouchEnforcer`](crate::control_helpers::TnuaCrouchEnforcer), the sensor entity can be
+/// modified to cast a shape instead of a ray (see
+/// [`with_probe_modifier`](Self::with_probe_modifier)).
+///
+/// Only available with the `ai` feature.
+#[derive(Component)]
+pub struct TnuaWanderDriver {
+    /// The minimal duration, in seconds, of walking in a single direction.
+    pub min_leg_duration: Float,
+
+    /// The maximal duration, in seconds, of walking in a single direction.
+    pub max_leg_duration: Float,
+
+    /// The probability (between 0.0 and 1.0) that the character will stand still instead of
+    /// walking when picking a new direction.
+    pub idle_probability: Float,
+
+    /// When set, only wander along that axis (e.g. `Dir3::X` for side view 2D games).
+    ///
+    /// When `None`, wander in all the directions orthogonal to the up direction.
+    pub restrict_to_axis: Option<Dir3>,
+
+    /// The origin of the obstacle detection probe, in the character's coord system.
+    ///
+    /// Should typically be placed a little above the bottom of the collider, so that the probe
+    /// will not detect the ground itself.
+    pub obstacle_probe_origin: Vector3,
+
+    /// The range of the obstacle detection probe. Set to `0.0` to disable jumping over obstacles.
+    pub obstacle_probe_range: Float,
+
+    /// How many times to try jumping over an obstacle before picking a new direction.
+    pub jump_attempts: usize,
+
+    modify_probe: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+    probe: SubservientProbe,
+    rng_state: Option<u64>,
+    seed: u64,
+    leg: Option<WanderLeg>,
+}
+
+struct WanderLeg {
+    direction: Option<Dir3>,
+    remaining: Float,
+    jump_attempts: usize,
+}
+
+impl Default for TnuaWanderDriver {
+    fn default() -> Self {
+        Self {
+            min_leg_duration: 0.5,
+            max_leg_duration: 3.0,
+            idle_probability: 0.2,
+            restrict_to_axis: None,
+            obstacle_probe_origin: Vector3::ZERO,
+            obstacle_probe_range: 1.0,
+            jump_attempts: 2,
+            modify_probe: Box::new(|_| {}),
+            probe: Default::default(),
+            rng_state: None,
+            seed: 0x2545_f491_4f6c_dd1d,
+            leg: None,
+        }
+    }
+}
+
+impl TnuaWanderDriver {
+    /// Set a function that will be called with the command that creates the obstacle detection
+    /// probe. This function has the opportunity to add things to the probe entity - mostly
+    /// cast-shape components.
+    pub fn with_probe_modifier(
+        mut self,
+        modify_probe: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        self.modify_probe = Box::new(modify_probe);
+        self
+    }
+
+    /// Set the seed for the random number generator.
+    ///
+    /// The seed is mixed with the entity, so multiple characters created with the same seed will
+    /// still wander differently. A given seed will always produce the same decisions for the same
+    /// entity, which makes it possible to reproduce soak test runs.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng_state = None;
+        self
+    }
+
+    /// The direction the character currently wanders in, or `None` if it's standing still.
+    pub fn current_direction(&self) -> Option<Dir3> {
+        self.leg.as_ref()?.direction
+    }
+
+    fn next_random(&mut self, entity: Entity) -> Float {
+        let state = self.rng_state.get_or_insert_with(|| {
+            // splitmix64, so that similar seeds and entities will not produce similar sequences.
+            let mut z = self.seed ^ entity.to_bits().wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            (z ^ (z >> 31)) | 1
+        });
+        // xorshift64*
+        *state ^= *state >> 12;
+        *state ^= *state << 25;
+        *state ^= *state >> 27;
+        let value = state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (value >> 11) as Float / (1u64 << 53) as Float
+    }
+
+    fn pick_leg(&mut self, entity: Entity, up_direction: Dir3) -> WanderLeg {
+        let duration = self.min_leg_duration
+            + self.next_random(entity) * (self.max_leg_duration - self.min_leg_duration).max(0.0);
+        let direction = if self.next_random(entity) < self.idle_probability {
+            None
+        } else if let Some(axis) = self.restrict_to_axis {
+            Some(if self.next_random(entity) < 0.5 {
+                axis
+            } else {
+                -axis
+            })
+        } else {
+            let (x_axis, z_axis) = up_direction.any_orthonormal_pair();
+            let angle = self.next_random(entity) * float_consts::TAU;
+            Dir3::new(
+                (x_axis.adjust_precision() * angle.cos() + z_axis.adjust_precision() * angle.sin())
+                    .f32(),
+            )
+            .ok()
+        };
+        WanderLeg {
+            direction,
+            remaining: duration,
+            jump_attempts: 0,
+        }
+    }
+}
+
+fn wander_driver_system(
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &TnuaController,
+        &mut TnuaWanderDriver,
+        &mut TnuaAiDriver,
+        &TnuaRigidBodyTracker,
+    )>,
+    mut sensors_query: ProbeSensorsQuery,
+    mut commands: Commands,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    for (entity, controller, mut driver, mut ai_driver, tracker) in query.iter_mut() {
+        let up_direction = Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y);
+
+        let obstacle_detected = driver.probe.output(&sensors_query).is_some();
+
+        let grounded = TnuaAiQueries::new(controller, tracker).is_grounded();
+
+        // Walking the whole leg with no obstacles should not reach the target before the leg's
+        // duration is over, so the target is only there to give the walk its direction.
+        let walk_command =
+            |direction: Dir3, remaining: Float, speed: Float| TnuaAiCommand::WalkTo {
+                target: tracker.translation
+                    + direction.adjust_precision() * speed * remaining.max(0.0),
+                stop_distance: 0.0,
+            };
+
+        let mut leg = match driver.leg.take() {
+            Some(leg) if 0.0 < leg.remaining => leg,
+            _ => {
+                let leg = driver.pick_leg(entity, up_direction);
+                let command = match leg.direction {
+                    Some(direction) => walk_command(direction, leg.remaining, ai_driver.speed),
+                    None => TnuaAiCommand::Idle,
+                };
+                ai_driver.command(command);
+                leg
+            }
+        };
+        leg.remaining -= frame_duration;
+
+        if let Some(direction) = leg.direction {
+            match (ai_driver.current_command(), ai_driver.status()) {
+                (TnuaAiCommand::JumpOver { .. }, TnuaAiCommandStatus::Running) => {}
+                (TnuaAiCommand::JumpOver { .. }, _) => {
+                    // Whether the jump succeeded or not - resume walking. If the obstacle is
+                    // still there, it'll be detected again.
+                    let command = walk_command(direction, leg.remaining, ai_driver.speed);
+                    ai_driver.command(command);
+                }
+                (_, TnuaAiCommandStatus::Running) if obstacle_detected && grounded => {
+                    if leg.jump_attempts < driver.jump_attempts {
+                        leg.jump_attempts += 1;
+                        ai_driver.command(TnuaAiCommand::JumpOver { direction });
+                    } else {
+                        // Too many attempts - this obstacle is probably too high to jump over.
+                        leg.remaining = 0.0;
+                    }
+                }
+                (_, TnuaAiCommandStatus::Running) => {}
+                // The walk is over before its time (e.g. it was pushed to the target) - so the
+                // leg is over too.
+                (_, _) => {
+                    leg.remaining = 0.0;
+                }
+            }
+        }
+
+        match (leg.direction, driver.obstacle_probe_range) {
+            (Some(direction), cast_range) if 0.0 < cast_range => {
+                let driver = driver.as_mut();
+                driver.probe.cast(
+                    entity,
+                    &mut sensors_query,
+                    &mut commands,
+                    driver.obstacle_probe_origin,
+                    direction,
+                    cast_range,
+                    &driver.modify_probe,
+                );
+            }
+            _ => {
+                driver.probe.turn_off(&mut sensors_query);
+            }
+        }
+
+        driver.leg = Some(leg);
+    }
+}
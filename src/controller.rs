@@ -1,17 +1,35 @@
+use std::time::Duration;
+
 use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
 use bevy::prelude::*;
 use bevy::time::Stopwatch;
 use bevy::utils::{Entry, HashMap};
-use bevy_tnua_physics_integration_layer::math::{AsF32, Float};
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
 
+use crate::action_resolution::{TnuaActionResolution, TnuaActionResolutionPolicy};
+use crate::arena_bounds::apply_arena_bounds_system;
+use crate::assists::apply_assists_system;
 use crate::basis_action_traits::{
     BoxableAction, BoxableBasis, DynamicAction, DynamicBasis, TnuaAction, TnuaActionContext,
     TnuaActionInitiationDirective, TnuaActionLifecycleDirective, TnuaActionLifecycleStatus,
     TnuaBasisContext,
 };
+use crate::carrying::{apply_carried_sensors_system, apply_carried_weight_system};
+use crate::crowd_separation::apply_crowd_separation_system;
+use crate::drop_through::apply_drop_through_platforms_system;
+use crate::event_log::{
+    TnuaActionRejected, TnuaActionRejectionReason, TnuaControllerEvent, TnuaEvent, TnuaEventLog,
+};
+use crate::replication::TnuaReplicationImportance;
+use crate::resource_pool::TnuaResourcePool;
+use crate::root_motion::TnuaRootMotion;
+use crate::stage_tracing::TnuaStageTrace;
+use crate::surface_properties::apply_conveyor_surfaces_system;
+use crate::swept_sensing::{apply_swept_sensors_system, restore_swept_sensors_system};
 use crate::{
-    TnuaBasis, TnuaMotor, TnuaPipelineStages, TnuaProximitySensor, TnuaRigidBodyTracker,
-    TnuaSystemSet, TnuaToggle, TnuaUserControlsSystemSet,
+    TnuaBasis, TnuaExternalForce, TnuaMotor, TnuaPipelineStages, TnuaProximitySensor,
+    TnuaRigidBodyTracker, TnuaSurfaceProperties, TnuaSystemSet, TnuaToggle, TnuaUpdateRate,
+    TnuaUserControlsSystemSet,
 };
 
 /// The main for supporting Tnua character controller.
@@ -38,6 +56,8 @@ impl Default for TnuaControllerPlugin {
 
 impl Plugin for TnuaControllerPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<TnuaActionRejected>();
+        app.add_event::<TnuaEvent>();
         app.configure_sets(
             self.schedule,
             (
@@ -52,7 +72,33 @@ impl Plugin for TnuaControllerPlugin {
         );
         app.add_systems(
             self.schedule,
-            apply_controller_system.in_set(TnuaPipelineStages::Logic),
+            tick_update_rates_system
+                .in_set(TnuaSystemSet)
+                .before(TnuaPipelineStages::Sensors),
+        );
+        app.add_systems(
+            self.schedule,
+            (
+                restore_swept_sensors_system,
+                apply_conveyor_surfaces_system,
+                apply_carried_sensors_system,
+            )
+                .in_set(TnuaPipelineStages::SubservientSensors),
+        );
+        app.add_systems(
+            self.schedule,
+            (
+                apply_drop_through_platforms_system,
+                apply_crowd_separation_system,
+                apply_assists_system,
+                apply_controller_system,
+                apply_carried_weight_system,
+                send_controller_events_system,
+                apply_swept_sensors_system,
+                apply_arena_bounds_system,
+            )
+                .chain()
+                .in_set(TnuaPipelineStages::Logic),
         );
     }
 }
@@ -99,10 +145,22 @@ struct FedEntry {
 #[derive(Component, Default)]
 pub struct TnuaController {
     current_basis: Option<(&'static str, Box<dyn DynamicBasis>)>,
+    previous_basis: Option<(&'static str, Box<dyn DynamicBasis>)>,
+    basis_transition: Option<TnuaBasisTransition>,
     actions_being_fed: HashMap<&'static str, FedEntry>,
     current_action: Option<(&'static str, Box<dyn DynamicAction>)>,
     contender_action: Option<(&'static str, Box<dyn DynamicAction>, Stopwatch)>,
+    contender_is_fresh: bool,
+    simultaneous_contenders: Vec<(&'static str, Box<dyn DynamicAction>, Stopwatch)>,
+    action_resolution: Option<TnuaActionResolution>,
     action_flow_status: TnuaActionFlowStatus,
+    ragdoll_status: TnuaRagdollStatus,
+    last_basis_name: Option<&'static str>,
+    last_is_airborne: Option<bool>,
+    last_is_rising: bool,
+    frame_events: Vec<TnuaControllerEvent>,
+    replication_importance: TnuaReplicationImportance,
+    teleport_reset_pending: bool,
 }
 
 impl TnuaController {
@@ -125,9 +183,70 @@ impl TnuaController {
         {
             *existing_name = name;
             existing_basis.input = basis;
+            existing_basis.input_is_fresh = true;
         } else {
             self.current_basis = Some((name, Box::new(BoxableBasis::new(basis))));
+            self.previous_basis = None;
+            self.basis_transition = None;
+        }
+    }
+
+    /// Feed a basis - the main movement command - with [its default name](TnuaBasis::NAME),
+    /// blending from the previous basis over `duration` seconds if the basis type changes.
+    ///
+    /// See [`named_transition_to_basis`](Self::named_transition_to_basis).
+    pub fn transition_to_basis<B: TnuaBasis>(&mut self, basis: B, duration: Float) {
+        self.named_transition_to_basis(B::NAME, basis, duration);
+    }
+
+    /// Feed a basis - the main movement command - with a custom name, blending from the previous
+    /// basis over `duration` seconds if the basis type changes.
+    ///
+    /// Replacing the basis type with [`named_basis`](Self::named_basis) switches the motor from
+    /// one basis to the other in a single frame, which can cause a visible pop in the velocity
+    /// (e.g. when the floating spring of the walk basis kicks in right after swimming). With this
+    /// method, the previous basis keeps running with its last input during the transition, and
+    /// the motor is blended from its output to the output of the new basis. Actions see the new
+    /// basis as the current one from the first frame.
+    ///
+    /// Like the regular basis feeding, this should be called every frame - feeding the same basis
+    /// type again does not restart the transition. Feeding a different basis type with
+    /// [`named_basis`](Self::named_basis) cancels the transition.
+    ///
+    /// The progress of the transition is available from
+    /// [`basis_transition`](Self::basis_transition), for crossfading the animations.
+    pub fn named_transition_to_basis<B: TnuaBasis>(
+        &mut self,
+        name: &'static str,
+        basis: B,
+        duration: Float,
+    ) {
+        let is_same_type = self
+            .current_basis
+            .as_ref()
+            .map_or(true, |(_, existing_basis)| {
+                existing_basis.as_any().is::<BoxableBasis<B>>()
+            });
+        if is_same_type || duration <= 0.0 {
+            self.named_basis(name, basis);
+            return;
         }
+        self.previous_basis = self.current_basis.take();
+        self.basis_transition =
+            self.previous_basis
+                .as_ref()
+                .map(|(previous_name, _)| TnuaBasisTransition {
+                    from: previous_name,
+                    progress: 0.0,
+                    duration,
+                });
+        self.current_basis = Some((name, Box::new(BoxableBasis::new(basis))));
+    }
+
+    /// The transition from the previous basis, if the basis was replaced with
+    /// [`transition_to_basis`](Self::transition_to_basis) and the transition is not done yet.
+    pub fn basis_transition(&self) -> Option<TnuaBasisTransition> {
+        self.basis_transition
     }
 
     /// Instruct the basis to pretend the user provided no input this frame.
@@ -190,6 +309,7 @@ impl TnuaController {
                             panic!("Multiple action types registered with same name {name:?}");
                         };
                         current_action.input = action;
+                        current_action.input_is_fresh = true;
                     } else {
                         // different action is running - will not override because button was
                         // already pressed.
@@ -205,6 +325,7 @@ impl TnuaController {
                     // already-existing contender that would have taken priority
                     self.contender_action =
                         Some((name, Box::new(BoxableAction::new(action)), Stopwatch::new()));
+                    self.contender_is_fresh = false;
                 } else {
                     // no action is running - will not set because button was already pressed.
                 }
@@ -230,9 +351,21 @@ impl TnuaController {
                     },
                 ) {
                     contender_action.input = action;
+                    contender_action.input_is_fresh = true;
                 } else {
-                    self.contender_action =
-                        Some((name, Box::new(BoxableAction::new(action)), Stopwatch::new()));
+                    let previous_contender = self.contender_action.replace((
+                        name,
+                        Box::new(BoxableAction::new(action)),
+                        Stopwatch::new(),
+                    ));
+                    if let Some(previous_contender) = previous_contender {
+                        if self.contender_is_fresh {
+                            // Pressed together with this action - let the Logic stage decide
+                            // between them.
+                            self.simultaneous_contenders.push(previous_contender);
+                        }
+                    }
+                    self.contender_is_fresh = true;
                 }
             }
         }
@@ -280,6 +413,145 @@ impl TnuaController {
         &self.action_flow_status
     }
 
+    /// The actions that were fed but did not start in the last frame, together with the reason
+    /// they were rejected.
+    ///
+    /// This can be used to give feedback to the player (e.g. play a "denied" sound). The same
+    /// information is also sent as [`TnuaActionRejected`] events.
+    pub fn rejected_actions(
+        &self,
+    ) -> impl '_ + Iterator<Item = (&'static str, TnuaActionRejectionReason)> {
+        self.frame_events.iter().filter_map(|event| {
+            if let TnuaControllerEvent::ActionRejected { action, reason } = event {
+                Some((*action, *reason))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// How the controller decided between the actions that were fed for the first time in the
+    /// same frame, if there were more than one such actions in the last frame.
+    ///
+    /// See [`TnuaActionResolutionPolicy`].
+    pub fn action_resolution(&self) -> Option<&TnuaActionResolution> {
+        self.action_resolution.as_ref()
+    }
+
+    /// The decisions and transitions of the controller in the last frame.
+    ///
+    /// The same information is also sent as [`TnuaEvent`] events.
+    pub fn events(&self) -> impl '_ + Iterator<Item = &TnuaControllerEvent> {
+        self.frame_events.iter()
+    }
+
+    /// How fast the character's state is changing, for prioritizing its replication over the
+    /// network.
+    pub fn replication_importance(&self) -> TnuaReplicationImportance {
+        self.replication_importance
+    }
+
+    /// The input of the basis, if it is of type `B` and was fed since the last time this method
+    /// was called.
+    pub(crate) fn take_fresh_basis_input<B: TnuaBasis>(&mut self) -> Option<&mut B> {
+        let (_, basis) = self.current_basis.as_mut()?;
+        let boxable_basis: &mut BoxableBasis<B> = basis.as_mut_any().downcast_mut()?;
+        if std::mem::take(&mut boxable_basis.input_is_fresh) {
+            Some(&mut boxable_basis.input)
+        } else {
+            None
+        }
+    }
+
+    /// Run `f` on the inputs of the current and contender actions that are of type `A` and were
+    /// fed since the last time this method was called.
+    pub(crate) fn for_each_fresh_action_input<A: TnuaAction>(&mut self, mut f: impl FnMut(&mut A)) {
+        let actions = self
+            .current_action
+            .iter_mut()
+            .map(|(_, action)| action)
+            .chain(
+                self.contender_action
+                    .iter_mut()
+                    .map(|(_, action, _)| action),
+            );
+        for action in actions {
+            let Some(boxable_action) = action.as_mut_any().downcast_mut::<BoxableAction<A>>()
+            else {
+                continue;
+            };
+            if std::mem::take(&mut boxable_action.input_is_fresh) {
+                f(&mut boxable_action.input);
+            }
+        }
+    }
+
+    /// Stop controlling the character, so that the physics engine can make it tumble like a
+    /// ragdoll.
+    ///
+    /// While ragdolling, the motor is zeroed, so Tnua neither floats the character nor keeps it
+    /// upright. The current action is stopped on the next run of [`TnuaControllerPlugin`]'s logic
+    /// (and reported as [`ActionEnded`](TnuaActionFlowStatus::ActionEnded)), and fed actions are
+    /// not started - but the basis and the sensors keep being updated, so that the character can
+    /// recover from wherever it ends up. Note that if the rotation of the rigid body is locked
+    /// (e.g. with `LockedAxes`), it needs to be unlocked for the body to actually tumble.
+    ///
+    /// Use [`recover_from_ragdoll`](Self::recover_from_ragdoll) to let Tnua regain control.
+    pub fn enter_ragdoll(&mut self) {
+        self.ragdoll_status = TnuaRagdollStatus::Ragdolling;
+    }
+
+    /// Regain control of a character that was put into ragdoll mode with
+    /// [`enter_ragdoll`](Self::enter_ragdoll).
+    ///
+    /// The motor is gradually blended in over `duration` seconds, so that the basis smoothly
+    /// brings the character upright and back to its float height from whatever pose it ended in.
+    /// The [`ragdoll_status`](Self::ragdoll_status) reports the progress of the recovery, which
+    /// can be used to blend into the standing animation.
+    ///
+    /// Does nothing if the character is not ragdolling.
+    pub fn recover_from_ragdoll(&mut self, duration: Float) {
+        if self.ragdoll_status != TnuaRagdollStatus::Ragdolling {
+            return;
+        }
+        self.ragdoll_status = if 0.0 < duration {
+            TnuaRagdollStatus::Recovering {
+                progress: 0.0,
+                duration,
+            }
+        } else {
+            TnuaRagdollStatus::NotRagdolling
+        };
+    }
+
+    /// Whether the character is ragdolling or recovering from ragdolling.
+    pub fn ragdoll_status(&self) -> TnuaRagdollStatus {
+        self.ragdoll_status
+    }
+
+    /// Reset the state of the controller after teleporting the character (or when reusing a
+    /// character entity, e.g. from a pool, at a new position).
+    ///
+    /// Without this, the controller continues from its state before the teleport - the basis
+    /// would treat the distance to the new ground as a huge float spring offset (or as stepping
+    /// off a ledge), and the sudden change in the airborne state would be reported as a
+    /// [`Landed`](TnuaControllerEvent::Landed) or [`LeftGround`](TnuaControllerEvent::LeftGround)
+    /// event.
+    ///
+    /// The reset happens on the next run of [`TnuaControllerPlugin`]'s logic. It clears the state
+    /// of the basis (including its coyote time and airborne tracking), stops the current action,
+    /// forgets the actions that were fed and the airborne state used for the events, and sends a
+    /// [`TeleportReset`](TnuaControllerEvent::TeleportReset) event. Since the proximity sensor
+    /// may have been cast from the old position, its output is discarded and the motor is zeroed
+    /// for that frame - and the controller continues from its fresh state on the frame after,
+    /// with the sensors cast from the new position.
+    ///
+    /// The velocity of the rigid body is not changed - setting it depends on the physics backend,
+    /// so the game should do it if the character should not keep its old velocity.
+    pub fn teleport_reset(&mut self) {
+        self.teleport_reset_pending = true;
+    }
+
     /// Checks if the character is currently airborne.
     ///
     /// The check is done based on the basis, and is equivalent to getting the controller's
@@ -291,6 +563,48 @@ impl TnuaController {
             None => Err(TnuaControllerHasNoBasis),
         }
     }
+
+    /// The time, in seconds, since the character was last grounded.
+    ///
+    /// This is `0.0` while the character is grounded, and `None` if there is no basis or if the
+    /// basis does not [track it](TnuaBasis::airborne_duration).
+    pub fn airborne_duration(&self) -> Option<Float> {
+        self.dynamic_basis()?.airborne_duration()
+    }
+
+    /// How far above its float height the character is (negative if it is below it).
+    ///
+    /// This is `None` if the character does not stand on anything, if there is no basis, or if the
+    /// basis does not [track it](TnuaBasis::float_offset).
+    pub fn float_offset(&self) -> Option<Float> {
+        self.dynamic_basis()?.float_offset()
+    }
+
+    /// The speed the character runs at, perpendicular to the up direction and relative to what it
+    /// stands on.
+    ///
+    /// This is `None` if there is no basis, or if the basis does not
+    /// [have such notion](TnuaBasis::running_velocity).
+    pub fn running_speed(&self) -> Option<Float> {
+        Some(self.dynamic_basis()?.running_velocity()?.length())
+    }
+
+    /// The [`running_speed`](Self::running_speed), divided by `max_speed` and clamped to the range
+    /// `0.0..=1.0` - e.g. for blending between idle, walk and run animations.
+    pub fn normalized_running_speed(&self, max_speed: Float) -> Option<Float> {
+        let running_speed = self.running_speed()?;
+        if max_speed <= 0.0 {
+            return Some(0.0);
+        }
+        Some((running_speed / max_speed).clamp(0.0, 1.0))
+    }
+
+    /// The [stage](TnuaAction::stage_name) of the currently running action.
+    ///
+    /// This is `None` if there is no action, or if the action does not have stages.
+    pub fn action_stage(&self) -> Option<&'static str> {
+        self.dynamic_action()?.stage_name()
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -322,6 +636,37 @@ pub enum TnuaActionFlowStatus {
     },
 }
 
+/// The result of [`TnuaController::basis_transition()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TnuaBasisTransition {
+    /// The name of the basis the controller transitions from.
+    pub from: &'static str,
+    /// Goes from 0.0 (just started transitioning) to 1.0 (the transition is done).
+    pub progress: Float,
+    /// The duration, in seconds, of the entire transition.
+    pub duration: Float,
+}
+
+/// The result of [`TnuaController::ragdoll_status()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TnuaRagdollStatus {
+    /// Tnua controls the character normally.
+    #[default]
+    NotRagdolling,
+
+    /// The character was put into ragdoll mode with [`TnuaController::enter_ragdoll`].
+    Ragdolling,
+
+    /// The character is recovering from ragdoll mode, after
+    /// [`TnuaController::recover_from_ragdoll`] was called.
+    Recovering {
+        /// Goes from 0.0 (just started recovering) to 1.0 (the recovery is done).
+        progress: Float,
+        /// The duration, in seconds, of the entire recovery.
+        duration: Float,
+    },
+}
+
 impl TnuaActionFlowStatus {
     /// The name of the ongoing action, if there is an ongoing action.
     ///
@@ -356,261 +701,662 @@ impl TnuaActionFlowStatus {
     }
 }
 
+fn send_controller_events_system(
+    query: Query<(Entity, &TnuaController)>,
+    mut rejected_writer: EventWriter<TnuaActionRejected>,
+    mut events_writer: EventWriter<TnuaEvent>,
+) {
+    for (entity, controller) in query.iter() {
+        for (action, reason) in controller.rejected_actions() {
+            rejected_writer.send(TnuaActionRejected {
+                entity,
+                action,
+                reason,
+            });
+        }
+        for event in controller.events() {
+            events_writer.send(TnuaEvent {
+                entity,
+                event: event.clone(),
+            });
+        }
+    }
+}
+
+fn tick_update_rates_system(
+    time: Res<Time>,
+    mut query: Query<(&mut TnuaUpdateRate, Option<&TnuaToggle>)>,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    for (mut update_rate, tnua_toggle) in query.iter_mut() {
+        if tnua_toggle == Some(&TnuaToggle::Disabled) {
+            // Don't let the time the character was disabled accumulate into its next update.
+            continue;
+        }
+        update_rate.tick(frame_duration);
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn apply_controller_system(
     time: Res<Time>,
     mut query: Query<(
+        Entity,
         &mut TnuaController,
         &TnuaRigidBodyTracker,
         &mut TnuaProximitySensor,
         &mut TnuaMotor,
         Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+        Option<&mut TnuaEventLog>,
+        Option<&mut TnuaExternalForce>,
+        Option<&TnuaActionResolutionPolicy>,
+        Option<&mut TnuaResourcePool>,
+        Option<&mut TnuaRootMotion>,
     )>,
+    surfaces_query: Query<&TnuaSurfaceProperties>,
 ) {
     let frame_duration = time.delta().as_secs_f64() as Float;
     if frame_duration == 0.0 {
         return;
     }
-    for (mut controller, tracker, mut sensor, mut motor, tnua_toggle) in query.iter_mut() {
-        match tnua_toggle.copied().unwrap_or_default() {
-            TnuaToggle::Disabled => continue,
-            TnuaToggle::SenseOnly => {}
-            TnuaToggle::Enabled => {}
-        }
-
-        let controller = controller.as_mut();
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::Logic);
+    query.par_iter_mut().for_each(
+        |(
+            entity,
+            mut controller,
+            tracker,
+            mut sensor,
+            mut motor,
+            tnua_toggle,
+            update_rate,
+            mut event_log,
+            mut external_force,
+            resolution_policy,
+            mut resource_pool,
+            root_motion,
+        )| {
+            let _span = stage_trace.character(entity);
+            if !controller.frame_events.is_empty() {
+                controller.frame_events.clear();
+            }
 
-        match controller.action_flow_status {
-            TnuaActionFlowStatus::NoAction | TnuaActionFlowStatus::ActionOngoing(_) => {}
-            TnuaActionFlowStatus::ActionEnded(_) => {
-                controller.action_flow_status = TnuaActionFlowStatus::NoAction;
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
             }
-            TnuaActionFlowStatus::ActionStarted(action_name)
-            | TnuaActionFlowStatus::Cancelled {
-                old: _,
-                new: action_name,
-            } => {
-                controller.action_flow_status = TnuaActionFlowStatus::ActionOngoing(action_name);
+
+            let frame_duration = match update_rate {
+                Some(update_rate) if !update_rate.should_update() => return,
+                // Compensate for the frames that were skipped since the last update.
+                Some(update_rate) => update_rate.time_since_update(),
+                None => frame_duration,
+            };
+
+            let controller = controller.as_mut();
+            let mut frame_events = std::mem::take(&mut controller.frame_events);
+            let mut record_event = |event: TnuaControllerEvent| frame_events.push(event);
+
+            match controller.action_flow_status {
+                TnuaActionFlowStatus::NoAction | TnuaActionFlowStatus::ActionOngoing(_) => {}
+                TnuaActionFlowStatus::ActionEnded(_) => {
+                    controller.action_flow_status = TnuaActionFlowStatus::NoAction;
+                }
+                TnuaActionFlowStatus::ActionStarted(action_name)
+                | TnuaActionFlowStatus::Cancelled {
+                    old: _,
+                    new: action_name,
+                } => {
+                    controller.action_flow_status =
+                        TnuaActionFlowStatus::ActionOngoing(action_name);
+                }
             }
-        }
 
-        if let Some((_, basis)) = controller.current_basis.as_mut() {
-            let up_direction = Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y);
-            let basis = basis.as_mut();
-            basis.apply(
-                TnuaBasisContext {
-                    frame_duration,
-                    tracker,
-                    proximity_sensor: sensor.as_ref(),
-                    up_direction,
-                },
-                motor.as_mut(),
-            );
-            let sensor_cast_range_for_basis = basis.proximity_sensor_cast_range();
+            if std::mem::take(&mut controller.teleport_reset_pending) {
+                if let Some((_, basis)) = controller.current_basis.as_mut() {
+                    basis.reset_state();
+                }
+                controller.previous_basis = None;
+                controller.basis_transition = None;
+                if let Some((action_name, _)) = controller.current_action.take() {
+                    controller.action_flow_status = TnuaActionFlowStatus::ActionEnded(action_name);
+                    record_event(TnuaControllerEvent::ActionEnded(action_name));
+                    if let Some(resource_pool) = resource_pool.as_mut() {
+                        resource_pool.action_ended();
+                    }
+                }
+                controller.contender_action = None;
+                controller.contender_is_fresh = false;
+                controller.simultaneous_contenders.clear();
+                controller.actions_being_fed.clear();
+                controller.action_resolution = None;
+                controller.last_is_airborne = None;
+                controller.last_is_rising = false;
+                record_event(TnuaControllerEvent::TeleportReset);
 
-            // To streamline TnuaActionContext creation
-            let proximity_sensor = sensor.as_ref();
+                // The sensor may have been cast from where the character was before the teleport,
+                // so nothing can be decided from it this frame.
+                sensor.output = None;
+                *motor = Default::default();
 
-            let has_valid_contender = if let Some((_, contender_action, being_fed_for)) =
-                &mut controller.contender_action
-            {
-                let initiation_decision = contender_action.initiation_decision(
-                    TnuaActionContext {
-                        frame_duration,
-                        tracker,
-                        proximity_sensor,
-                        basis,
-                        up_direction,
-                    },
-                    being_fed_for,
-                );
-                being_fed_for.tick(time.delta());
-                match initiation_decision {
-                    TnuaActionInitiationDirective::Reject => {
-                        controller.contender_action = None;
-                        false
+                if let Some(event_log) = event_log.as_mut() {
+                    for event in frame_events.iter() {
+                        event_log.record(time.elapsed_seconds_f64(), event.clone());
                     }
-                    TnuaActionInitiationDirective::Delay => false,
-                    TnuaActionInitiationDirective::Allow => true,
                 }
-            } else {
-                false
-            };
+                controller.frame_events = frame_events;
+                return;
+            }
 
-            if let Some((name, current_action)) = controller.current_action.as_mut() {
-                let lifecycle_status = if has_valid_contender {
-                    TnuaActionLifecycleStatus::CancelledInto
-                } else if controller
-                    .actions_being_fed
-                    .get(name)
-                    .map(|fed_entry| fed_entry.fed_this_frame)
-                    .unwrap_or(false)
-                {
-                    TnuaActionLifecycleStatus::StillFed
-                } else {
-                    TnuaActionLifecycleStatus::NoLongerFed
+            controller.action_resolution = None;
+            let mut candidates = std::mem::take(&mut controller.simultaneous_contenders);
+            if !candidates.is_empty() {
+                if let Some(last_contender) = controller.contender_action.take() {
+                    candidates.push(last_contender);
+                }
+                let rank = |action_name: &'static str| {
+                    resolution_policy.map_or(0, |policy| policy.rank(action_name))
                 };
+                let (winner_index, _) = candidates
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(index, (action_name, ..))| {
+                        (rank(action_name), std::cmp::Reverse(*index))
+                    })
+                    .expect("candidates is not empty");
+                let candidate_names = candidates
+                    .iter()
+                    .map(|(action_name, ..)| *action_name)
+                    .collect::<Vec<_>>();
+                let winner = candidates.swap_remove(winner_index);
+                for (loser_name, ..) in candidates {
+                    record_event(TnuaControllerEvent::ActionRejected {
+                        action: loser_name,
+                        reason: TnuaActionRejectionReason::OutprioritizedBy(winner.0),
+                    });
+                }
+                controller.action_resolution = Some(TnuaActionResolution {
+                    candidates: candidate_names,
+                    winner: winner.0,
+                });
+                controller.contender_action = Some(winner);
+            }
+            controller.contender_is_fresh = false;
 
-                let directive = current_action.apply(
-                    TnuaActionContext {
+            let external_vel_change = external_force
+                .as_mut()
+                .map(|external_force| external_force.consume(frame_duration));
+            let external_velocity = external_force
+                .as_ref()
+                .map_or(Vector3::ZERO, |external_force| {
+                    external_force.velocity_offset()
+                });
+            let root_motion = root_motion
+                .map(|mut root_motion| root_motion.consume(tracker.rotation, frame_duration));
+
+            if controller.ragdoll_status == TnuaRagdollStatus::Ragdolling {
+                *motor = Default::default();
+                // Ending the action here, after the flow status was cycled, makes it reported
+                // (and released from the resource pool) like any other action that ended.
+                if let Some((action_name, _)) = controller.current_action.take() {
+                    controller.action_flow_status = TnuaActionFlowStatus::ActionEnded(action_name);
+                }
+                if let Some((contender_name, ..)) = controller.contender_action.take() {
+                    record_event(TnuaControllerEvent::ActionRejected {
+                        action: contender_name,
+                        reason: TnuaActionRejectionReason::Ragdolling,
+                    });
+                }
+                // The basis is still applied, so that its state (e.g. the airborne tracking) is
+                // current when it regains control - but its motor is discarded.
+                if let Some((_, basis)) = controller.current_basis.as_mut() {
+                    let up_direction = Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y);
+                    basis.apply(
+                        TnuaBasisContext {
+                            frame_duration,
+                            tracker,
+                            proximity_sensor: sensor.as_ref(),
+                            surface_properties: sensor
+                                .output
+                                .as_ref()
+                                .and_then(|output| surfaces_query.get(output.entity).ok()),
+                            external_velocity,
+                            root_motion,
+                            up_direction,
+                        },
+                        &mut TnuaMotor::default(),
+                    );
+                    sensor.cast_range = basis.proximity_sensor_cast_range();
+                    sensor.cast_direction = -up_direction;
+                }
+            } else if let Some((_, basis)) = controller.current_basis.as_mut() {
+                let surface_properties = sensor
+                    .output
+                    .as_ref()
+                    .and_then(|output| surfaces_query.get(output.entity).ok());
+                let up_direction = Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y);
+                let basis = basis.as_mut();
+                basis.apply(
+                    TnuaBasisContext {
                         frame_duration,
                         tracker,
-                        proximity_sensor,
-                        basis,
+                        proximity_sensor: sensor.as_ref(),
+                        surface_properties,
+                        external_velocity,
+                        root_motion,
                         up_direction,
                     },
-                    lifecycle_status,
                     motor.as_mut(),
                 );
-                if current_action.violates_coyote_time() {
-                    basis.violate_coyote_time();
+                if let (Some((_, previous_basis)), Some(transition)) = (
+                    controller.previous_basis.as_mut(),
+                    controller.basis_transition.as_mut(),
+                ) {
+                    let mut previous_motor = TnuaMotor::default();
+                    previous_basis.apply(
+                        TnuaBasisContext {
+                            frame_duration,
+                            tracker,
+                            proximity_sensor: sensor.as_ref(),
+                            surface_properties,
+                            external_velocity,
+                            root_motion,
+                            up_direction,
+                        },
+                        &mut previous_motor,
+                    );
+                    transition.progress =
+                        (transition.progress + frame_duration / transition.duration).min(1.0);
+                    let blend = transition.progress;
+                    let motor = motor.as_mut();
+                    for (vel_change, previous_vel_change) in [
+                        (&mut motor.lin, previous_motor.lin),
+                        (&mut motor.ang, previous_motor.ang),
+                    ] {
+                        vel_change.acceleration = previous_vel_change
+                            .acceleration
+                            .lerp(vel_change.acceleration, blend);
+                        vel_change.boost = previous_vel_change.boost.lerp(vel_change.boost, blend);
+                    }
+                    if 1.0 <= blend {
+                        controller.previous_basis = None;
+                        controller.basis_transition = None;
+                    }
                 }
-                let reschedule_action =
-                    |actions_being_fed: &mut HashMap<&'static str, FedEntry>,
-                     after_seconds: Float| {
-                        if let Some(fed_entry) = actions_being_fed.get_mut(name) {
-                            fed_entry.rescheduled_in =
-                                Some(Timer::from_seconds(after_seconds.f32(), TimerMode::Once));
+                let sensor_cast_range_for_basis = basis.proximity_sensor_cast_range();
+
+                // To streamline TnuaActionContext creation
+                let proximity_sensor = sensor.as_ref();
+
+                let excluded_by = match (
+                    &controller.contender_action,
+                    &controller.current_action,
+                    resolution_policy,
+                ) {
+                    (Some((contender_name, ..)), Some((current_name, _)), Some(policy))
+                        if policy.are_exclusive(contender_name, current_name) =>
+                    {
+                        Some((*contender_name, *current_name))
+                    }
+                    _ => None,
+                };
+                if let Some((contender_name, current_name)) = excluded_by {
+                    record_event(TnuaControllerEvent::ActionRejected {
+                        action: contender_name,
+                        reason: TnuaActionRejectionReason::MutuallyExclusiveWith(current_name),
+                    });
+                    controller.contender_action = None;
+                }
+
+                let has_valid_contender =
+                    if let Some((contender_name, contender_action, being_fed_for)) =
+                        &mut controller.contender_action
+                    {
+                        let initiation_decision = contender_action.initiation_decision(
+                            TnuaActionContext {
+                                frame_duration,
+                                tracker,
+                                proximity_sensor,
+                                surface_properties,
+                                external_velocity,
+                                root_motion,
+                                basis,
+                                up_direction,
+                            },
+                            being_fed_for,
+                        );
+                        #[allow(clippy::unnecessary_cast)]
+                        being_fed_for.tick(Duration::from_secs_f64(frame_duration as f64));
+                        match initiation_decision {
+                            TnuaActionInitiationDirective::Reject => {
+                                record_event(TnuaControllerEvent::ActionRejected {
+                                    action: contender_name,
+                                    reason: TnuaActionRejectionReason::InitiationDecision,
+                                });
+                                controller.contender_action = None;
+                                false
+                            }
+                            TnuaActionInitiationDirective::Delay => false,
+                            TnuaActionInitiationDirective::Allow => {
+                                if resource_pool
+                                    .as_ref()
+                                    .is_some_and(|pool| !pool.can_afford(contender_name))
+                                {
+                                    record_event(TnuaControllerEvent::ActionRejected {
+                                        action: contender_name,
+                                        reason: TnuaActionRejectionReason::InsufficientResources,
+                                    });
+                                    controller.contender_action = None;
+                                    false
+                                } else {
+                                    true
+                                }
+                            }
                         }
+                    } else {
+                        false
                     };
-                match directive {
-                    TnuaActionLifecycleDirective::StillActive => {
-                        if !lifecycle_status.is_active()
-                            && matches!(
-                                controller.action_flow_status,
-                                TnuaActionFlowStatus::ActionOngoing(_)
-                            )
-                        {
-                            controller.action_flow_status = TnuaActionFlowStatus::ActionEnded(name);
-                        }
+
+                if let Some((name, current_action)) = controller.current_action.as_mut() {
+                    let lifecycle_status = if has_valid_contender {
+                        TnuaActionLifecycleStatus::CancelledInto
+                    } else if controller
+                        .actions_being_fed
+                        .get(name)
+                        .map(|fed_entry| fed_entry.fed_this_frame)
+                        .unwrap_or(false)
+                    {
+                        TnuaActionLifecycleStatus::StillFed
+                    } else {
+                        TnuaActionLifecycleStatus::NoLongerFed
+                    };
+
+                    let directive = current_action.apply(
+                        TnuaActionContext {
+                            frame_duration,
+                            tracker,
+                            proximity_sensor,
+                            surface_properties,
+                            external_velocity,
+                            root_motion,
+                            basis,
+                            up_direction,
+                        },
+                        lifecycle_status,
+                        motor.as_mut(),
+                    );
+                    if current_action.violates_coyote_time() {
+                        basis.violate_coyote_time();
                     }
-                    TnuaActionLifecycleDirective::Finished
-                    | TnuaActionLifecycleDirective::Reschedule { .. } => {
-                        if let TnuaActionLifecycleDirective::Reschedule { after_seconds } =
-                            directive
-                        {
-                            reschedule_action(&mut controller.actions_being_fed, after_seconds);
-                        }
-                        controller.current_action = if has_valid_contender {
-                            let (contender_name, mut contender_action, _) = controller.contender_action.take().expect("has_valid_contender can only be true if contender_action is Some");
-                            if let Some(contender_fed_entry) =
-                                controller.actions_being_fed.get_mut(contender_name)
+                    let reschedule_action =
+                        |actions_being_fed: &mut HashMap<&'static str, FedEntry>,
+                         after_seconds: Float| {
+                            if let Some(fed_entry) = actions_being_fed.get_mut(name) {
+                                fed_entry.rescheduled_in =
+                                    Some(Timer::from_seconds(after_seconds.f32(), TimerMode::Once));
+                            }
+                        };
+                    match directive {
+                        TnuaActionLifecycleDirective::StillActive => {
+                            if !lifecycle_status.is_active()
+                                && matches!(
+                                    controller.action_flow_status,
+                                    TnuaActionFlowStatus::ActionOngoing(_)
+                                )
                             {
-                                contender_fed_entry.rescheduled_in = None;
+                                controller.action_flow_status =
+                                    TnuaActionFlowStatus::ActionEnded(name);
                             }
-                            let contender_directive = contender_action.apply(
-                                TnuaActionContext {
-                                    frame_duration,
-                                    tracker,
-                                    proximity_sensor,
-                                    basis,
-                                    up_direction,
-                                },
-                                TnuaActionLifecycleStatus::CancelledFrom,
-                                motor.as_mut(),
-                            );
-                            if contender_action.violates_coyote_time() {
-                                basis.violate_coyote_time();
+                        }
+                        TnuaActionLifecycleDirective::Finished
+                        | TnuaActionLifecycleDirective::Reschedule { .. } => {
+                            if let TnuaActionLifecycleDirective::Reschedule { after_seconds } =
+                                directive
+                            {
+                                reschedule_action(&mut controller.actions_being_fed, after_seconds);
+                                record_event(TnuaControllerEvent::ActionRescheduled {
+                                    action: name,
+                                    after_seconds,
+                                });
                             }
-                            match contender_directive {
-                                TnuaActionLifecycleDirective::StillActive => {
-                                    if matches!(
-                                        controller.action_flow_status,
-                                        TnuaActionFlowStatus::ActionOngoing(_)
-                                    ) {
-                                        controller.action_flow_status =
-                                            TnuaActionFlowStatus::Cancelled {
-                                                old: name,
-                                                new: contender_name,
-                                            };
-                                    } else {
-                                        controller.action_flow_status =
-                                            TnuaActionFlowStatus::ActionStarted(contender_name);
-                                    }
-                                    Some((contender_name, contender_action))
+                            controller.current_action = if has_valid_contender {
+                                let (contender_name, mut contender_action, _) =
+                                    controller.contender_action.take().expect(
+                                        "has_valid_contender implies that contender_action is Some",
+                                    );
+                                if let Some(contender_fed_entry) =
+                                    controller.actions_being_fed.get_mut(contender_name)
+                                {
+                                    contender_fed_entry.rescheduled_in = None;
                                 }
-                                TnuaActionLifecycleDirective::Finished => {
-                                    if matches!(
-                                        controller.action_flow_status,
-                                        TnuaActionFlowStatus::ActionOngoing(_)
-                                    ) {
-                                        controller.action_flow_status =
-                                            TnuaActionFlowStatus::ActionEnded(name);
-                                    }
-                                    None
+                                let contender_directive = contender_action.apply(
+                                    TnuaActionContext {
+                                        frame_duration,
+                                        tracker,
+                                        proximity_sensor,
+                                        surface_properties,
+                                        external_velocity,
+                                        root_motion,
+                                        basis,
+                                        up_direction,
+                                    },
+                                    TnuaActionLifecycleStatus::CancelledFrom,
+                                    motor.as_mut(),
+                                );
+                                if contender_action.violates_coyote_time() {
+                                    basis.violate_coyote_time();
                                 }
-                                TnuaActionLifecycleDirective::Reschedule { after_seconds } => {
-                                    if matches!(
-                                        controller.action_flow_status,
-                                        TnuaActionFlowStatus::ActionOngoing(_)
-                                    ) {
-                                        controller.action_flow_status =
-                                            TnuaActionFlowStatus::ActionEnded(name);
+                                match contender_directive {
+                                    TnuaActionLifecycleDirective::StillActive => {
+                                        if matches!(
+                                            controller.action_flow_status,
+                                            TnuaActionFlowStatus::ActionOngoing(_)
+                                        ) {
+                                            controller.action_flow_status =
+                                                TnuaActionFlowStatus::Cancelled {
+                                                    old: name,
+                                                    new: contender_name,
+                                                };
+                                        } else {
+                                            controller.action_flow_status =
+                                                TnuaActionFlowStatus::ActionStarted(contender_name);
+                                        }
+                                        Some((contender_name, contender_action))
+                                    }
+                                    TnuaActionLifecycleDirective::Finished => {
+                                        if matches!(
+                                            controller.action_flow_status,
+                                            TnuaActionFlowStatus::ActionOngoing(_)
+                                        ) {
+                                            controller.action_flow_status =
+                                                TnuaActionFlowStatus::ActionEnded(name);
+                                        }
+                                        None
+                                    }
+                                    TnuaActionLifecycleDirective::Reschedule { after_seconds } => {
+                                        if matches!(
+                                            controller.action_flow_status,
+                                            TnuaActionFlowStatus::ActionOngoing(_)
+                                        ) {
+                                            controller.action_flow_status =
+                                                TnuaActionFlowStatus::ActionEnded(name);
+                                        }
+                                        reschedule_action(
+                                            &mut controller.actions_being_fed,
+                                            after_seconds,
+                                        );
+                                        record_event(TnuaControllerEvent::ActionRescheduled {
+                                            action: contender_name,
+                                            after_seconds,
+                                        });
+                                        None
                                     }
-                                    reschedule_action(
-                                        &mut controller.actions_being_fed,
-                                        after_seconds,
-                                    );
-                                    None
                                 }
-                            }
-                        } else {
-                            controller.action_flow_status = TnuaActionFlowStatus::ActionEnded(name);
-                            None
-                        };
+                            } else {
+                                controller.action_flow_status =
+                                    TnuaActionFlowStatus::ActionEnded(name);
+                                None
+                            };
+                        }
                     }
+                } else if has_valid_contender {
+                    let (contender_name, mut contender_action, _) = controller
+                        .contender_action
+                        .take()
+                        .expect("has_valid_contender implies that contender_action is Some");
+                    contender_action.apply(
+                        TnuaActionContext {
+                            frame_duration,
+                            tracker,
+                            proximity_sensor,
+                            surface_properties,
+                            external_velocity,
+                            root_motion,
+                            basis,
+                            up_direction,
+                        },
+                        TnuaActionLifecycleStatus::Initiated,
+                        motor.as_mut(),
+                    );
+                    if contender_action.violates_coyote_time() {
+                        basis.violate_coyote_time();
+                    }
+                    controller.action_flow_status =
+                        TnuaActionFlowStatus::ActionStarted(contender_name);
+                    controller.current_action = Some((contender_name, contender_action));
                 }
-            } else if has_valid_contender {
-                let (contender_name, mut contender_action, _) = controller
-                    .contender_action
-                    .take()
-                    .expect("has_valid_contender can only be true if contender_action is Some");
-                contender_action.apply(
-                    TnuaActionContext {
-                        frame_duration,
-                        tracker,
-                        proximity_sensor,
-                        basis,
-                        up_direction,
-                    },
-                    TnuaActionLifecycleStatus::Initiated,
-                    motor.as_mut(),
-                );
-                if contender_action.violates_coyote_time() {
-                    basis.violate_coyote_time();
+
+                let sensor_case_range_for_action =
+                    if let Some((_, current_action)) = &controller.current_action {
+                        current_action.proximity_sensor_cast_range()
+                    } else {
+                        0.0
+                    };
+
+                sensor.cast_range = sensor_cast_range_for_basis.max(sensor_case_range_for_action);
+                sensor.cast_direction = -up_direction;
+            }
+
+            if let TnuaRagdollStatus::Recovering { progress, duration } =
+                &mut controller.ragdoll_status
+            {
+                *progress += frame_duration / *duration;
+                if 1.0 <= *progress {
+                    controller.ragdoll_status = TnuaRagdollStatus::NotRagdolling;
+                } else {
+                    let blend = *progress;
+                    let motor = motor.as_mut();
+                    for vel_change in [&mut motor.lin, &mut motor.ang] {
+                        vel_change.acceleration *= blend;
+                        vel_change.boost *= blend;
+                    }
                 }
-                controller.action_flow_status = TnuaActionFlowStatus::ActionStarted(contender_name);
-                controller.current_action = Some((contender_name, contender_action));
             }
 
-            let sensor_case_range_for_action =
-                if let Some((_, current_action)) = &controller.current_action {
-                    current_action.proximity_sensor_cast_range()
+            if let Some(external_vel_change) = external_vel_change {
+                motor.lin += external_vel_change;
+            }
+
+            // Cycle actions_being_fed
+            controller.actions_being_fed.retain(|_, fed_entry| {
+                if fed_entry.fed_this_frame {
+                    fed_entry.fed_this_frame = false;
+                    if let Some(rescheduled_in) = &mut fed_entry.rescheduled_in {
+                        #[allow(clippy::unnecessary_cast)]
+                        rescheduled_in.tick(Duration::from_secs_f64(frame_duration as f64));
+                    }
+                    true
                 } else {
-                    0.0
-                };
+                    false
+                }
+            });
 
-            sensor.cast_range = sensor_cast_range_for_basis.max(sensor_case_range_for_action);
-            sensor.cast_direction = -up_direction;
-        }
+            if let Some((contender_name, ..)) = controller.contender_action {
+                if !controller.actions_being_fed.contains_key(contender_name) {
+                    record_event(TnuaControllerEvent::ActionRejected {
+                        action: contender_name,
+                        reason: TnuaActionRejectionReason::NoLongerFed,
+                    });
+                    controller.contender_action = None;
+                }
+            }
 
-        // Cycle actions_being_fed
-        controller.actions_being_fed.retain(|_, fed_entry| {
-            if fed_entry.fed_this_frame {
-                fed_entry.fed_this_frame = false;
-                if let Some(rescheduled_in) = &mut fed_entry.rescheduled_in {
-                    rescheduled_in.tick(time.delta());
+            if let Some(resource_pool) = resource_pool.as_mut() {
+                match controller.action_flow_status {
+                    TnuaActionFlowStatus::NoAction | TnuaActionFlowStatus::ActionOngoing(_) => {}
+                    TnuaActionFlowStatus::ActionStarted(action_name) => {
+                        resource_pool.action_started(action_name);
+                    }
+                    TnuaActionFlowStatus::ActionEnded(_) => {
+                        resource_pool.action_ended();
+                    }
+                    TnuaActionFlowStatus::Cancelled { old, new } => {
+                        resource_pool.action_cancelled(old);
+                        resource_pool.action_started(new);
+                    }
                 }
-                true
-            } else {
-                false
+                if controller.current_action.is_none() {
+                    resource_pool.action_ended();
+                }
+                resource_pool.update(frame_duration);
             }
-        });
 
-        if let Some((contender_name, ..)) = controller.contender_action {
-            if !controller.actions_being_fed.contains_key(contender_name) {
-                controller.contender_action = None;
+            // The flow status is reset at the beginning of each frame, so any of these statuses
+            // means that the event happened in this frame.
+            match controller.action_flow_status {
+                TnuaActionFlowStatus::NoAction | TnuaActionFlowStatus::ActionOngoing(_) => {}
+                TnuaActionFlowStatus::ActionStarted(action_name) => {
+                    record_event(TnuaControllerEvent::ActionStarted(action_name));
+                }
+                TnuaActionFlowStatus::ActionEnded(action_name) => {
+                    record_event(TnuaControllerEvent::ActionEnded(action_name));
+                }
+                TnuaActionFlowStatus::Cancelled { old, new } => {
+                    record_event(TnuaControllerEvent::ActionCancelled { old, new });
+                }
             }
-        }
-    }
+
+            let basis_name = controller.basis_name();
+            if let Some(new) = basis_name {
+                if controller.last_basis_name != basis_name {
+                    record_event(TnuaControllerEvent::BasisChanged {
+                        old: controller.last_basis_name,
+                        new,
+                    });
+                }
+            }
+            controller.last_basis_name = basis_name;
+
+            let is_airborne = controller.dynamic_basis().map(|basis| basis.is_airborne());
+            match (controller.last_is_airborne, is_airborne) {
+                (Some(false), Some(true)) => record_event(TnuaControllerEvent::LeftGround),
+                (Some(true), Some(false)) => record_event(TnuaControllerEvent::Landed),
+                _ => {}
+            }
+            controller.last_is_airborne = is_airborne;
+
+            let up_direction = Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y);
+            let is_rising = is_airborne == Some(true)
+                && 0.0 < tracker.velocity.dot(up_direction.adjust_precision());
+            if controller.last_is_rising && !is_rising && is_airborne == Some(true) {
+                record_event(TnuaControllerEvent::ApexReached);
+            }
+            controller.last_is_rising = is_rising;
+
+            controller.replication_importance.update(
+                tracker.velocity.length(),
+                frame_events.len(),
+                is_airborne == Some(true),
+                frame_duration,
+            );
+
+            if let Some(event_log) = event_log.as_mut() {
+                for event in frame_events.iter() {
+                    event_log.record(time.elapsed_seconds_f64(), event.clone());
+                }
+            }
+            controller.frame_events = frame_events;
+        },
+    );
 }
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
+use crate::{TnuaExternalForce, TnuaRigidBodyTracker, TnuaToggle, TnuaUpdateRate};
+
+/// Gently pushes characters away from each other, so that groups of characters will not get
+/// wedged together or bounce on each other's heads.
+///
+/// Add this component, together with [`TnuaExternalForce`] (which is used for applying the push),
+/// to every character that should take part in the crowd. Characters that have this component
+/// push each other apart horizontally whenever their cylinders (as defined by
+/// [`radius`](Self::radius) and [`height`](Self::height)) overlap. The push gets stronger the
+/// deeper the overlap is, up to [`strength`](Self::strength) when the centers coincide.
+///
+/// Because the push is horizontal, a character that lands on another character's head will slide
+/// off it instead of riding its float spring.
+///
+/// The cost of this is quadratic in the number of characters with this component, so it is meant
+/// for groups of characters (e.g. NPC crowds) rather than for entire levels full of them.
+#[derive(Component, Clone, Debug)]
+pub struct TnuaCrowdSeparation {
+    /// The horizontal radius of the space the character wants for itself.
+    ///
+    /// Two characters are pushed apart when the horizontal distance between them is less than
+    /// the sum of their radii.
+    pub radius: Float,
+
+    /// The vertical extent, above and below the center of the character, where other characters
+    /// are considered too close.
+    ///
+    /// Two characters are only pushed apart when the vertical distance between them is less than
+    /// the sum of their heights.
+    pub height: Float,
+
+    /// The maximum acceleration of the push.
+    pub strength: Float,
+}
+
+impl Default for TnuaCrowdSeparation {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            height: 1.0,
+            strength: 20.0,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_crowd_separation_system(
+    mut query: Query<(
+        Entity,
+        &TnuaCrowdSeparation,
+        &TnuaRigidBodyTracker,
+        &mut TnuaExternalForce,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+) {
+    let members = query
+        .iter()
+        .map(|(entity, separation, tracker, ..)| (entity, separation.clone(), tracker.translation))
+        .collect::<Vec<_>>();
+    if members.len() < 2 {
+        return;
+    }
+    query.par_iter_mut().for_each(
+        |(entity, separation, tracker, mut external_force, tnua_toggle, update_rate)| {
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled | TnuaToggle::SenseOnly => return,
+                TnuaToggle::Enabled => {}
+            }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            let up_direction = Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y);
+            let up = up_direction.adjust_precision();
+            let mut push = Vector3::ZERO;
+            for (other_entity, other_separation, other_translation) in members.iter() {
+                if *other_entity == entity {
+                    continue;
+                }
+                let offset = tracker.translation - *other_translation;
+                if separation.height + other_separation.height <= offset.dot(up).abs() {
+                    continue;
+                }
+                let combined_radius = separation.radius + other_separation.radius;
+                let horizontal_offset = offset.reject_from(up);
+                let distance = horizontal_offset.length();
+                if combined_radius <= distance {
+                    continue;
+                }
+                let direction = if 0.0 < distance {
+                    horizontal_offset / distance
+                } else {
+                    // Exactly on top of each other - pick opposite directions for the two
+                    // characters, based on which entity is bigger.
+                    let (axis, _) = up_direction.any_orthonormal_pair();
+                    let axis = axis.adjust_precision();
+                    if entity < *other_entity {
+                        axis
+                    } else {
+                        -axis
+                    }
+                };
+                push += direction * (1.0 - distance / combined_radius);
+            }
+            if push != Vector3::ZERO {
+                external_force.add_acceleration(separation.strength * push.clamp_length_max(1.0));
+            }
+        },
+    );
+}
@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::math::{AdjustPrecision, Float};
+use crate::{
+    TnuaGhostSensor, TnuaProximitySensor, TnuaRigidBodyTracker, TnuaToggle, TnuaUpdateRate,
+};
+
+/// Makes the character stand on ghost platforms, and drop through them with the common down+jump
+/// gesture.
+///
+/// Add this component to a character entity that has a [`TnuaGhostSensor`], and
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will take care of the ghost
+/// platforms - there is no need to copy the ghost sensor's output to the proximity sensor in the
+/// controls system (and it should not be done together with this component, nor should
+/// [`TnuaSimpleFallThroughPlatformsHelper`](crate::control_helpers::TnuaSimpleFallThroughPlatformsHelper)
+/// be used with it).
+///
+/// In the controls system, call [`down_jump`](Self::down_jump) every frame, and only feed the
+/// jump action if it returns `false`:
+///
+/// ```no_run
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::TnuaDropThroughPlatforms;
+/// # let mut controller = TnuaController::default();
+/// # let mut drop_through = TnuaDropThroughPlatforms::default();
+/// # let (down_pressed, jump_pressed) = (true, true);
+/// let used_for_dropping = drop_through.down_jump(down_pressed, jump_pressed);
+/// if jump_pressed && !used_for_dropping {
+///     controller.action(TnuaBuiltinJump {
+///         height: 4.0,
+///         ..Default::default()
+///     });
+/// }
+/// ```
+///
+/// Once the character starts dropping through a platform, it will not climb back up on it even
+/// if it is still detected, until the ghost sensor stops detecting it.
+///
+/// Jumping up through ghost platforms is handled too. A platform the character is inside of
+/// (closer than [`min_proximity`](Self::min_proximity)) is ignored until the character is fully
+/// above it and stops moving up relative to it - only then does the character land on it. This
+/// prevents the platform from catching the character midway through the jump.
+#[derive(Component)]
+pub struct TnuaDropThroughPlatforms {
+    /// The minimal distance from the origin of the ghost sensor (usually the center of the
+    /// character) to the platform. Closer platforms are ones the character is only halfway
+    /// through (e.g. while jumping through them from below), so it will not stand on them.
+    pub min_proximity: Float,
+
+    /// The time, in seconds, a drop request is remembered.
+    ///
+    /// This allows the drop to happen even if the gesture was done a little before the character
+    /// landed on the platform.
+    pub buffer_time: Float,
+
+    falling_through: HashSet<Entity>,
+    rising_through: HashSet<Entity>,
+    drop_requested_for: Float,
+    platform_below: bool,
+    jump_was_pressed: bool,
+    suppressing_jump: bool,
+}
+
+impl Default for TnuaDropThroughPlatforms {
+    fn default() -> Self {
+        Self {
+            min_proximity: 1.0,
+            buffer_time: 0.15,
+            falling_through: Default::default(),
+            rising_through: Default::default(),
+            drop_requested_for: 0.0,
+            platform_below: false,
+            jump_was_pressed: false,
+            suppressing_jump: false,
+        }
+    }
+}
+
+impl TnuaDropThroughPlatforms {
+    /// Handle the down+jump gesture.
+    ///
+    /// Call this every frame with the current state of the down and jump buttons. When the jump
+    /// button is pressed while the down button is held, a drop is requested. Returns `true` if
+    /// the jump press is used for dropping through a platform - in which case the jump action
+    /// should not be fed. This keeps returning `true` until the jump button is released, so that
+    /// holding it will not start a jump midway through the drop.
+    pub fn down_jump(&mut self, down_pressed: bool, jump_pressed: bool) -> bool {
+        let jump_just_pressed = jump_pressed && !self.jump_was_pressed;
+        self.jump_was_pressed = jump_pressed;
+        if !jump_pressed {
+            self.suppressing_jump = false;
+        }
+        if down_pressed && jump_just_pressed {
+            self.request_drop();
+            if self.platform_below {
+                self.suppressing_jump = true;
+            }
+        }
+        self.suppressing_jump
+    }
+
+    /// Drop through the ghost platform the character stands on, or the one it'll land on within
+    /// [`buffer_time`](Self::buffer_time).
+    pub fn request_drop(&mut self) {
+        self.drop_requested_for = self.buffer_time;
+    }
+
+    /// Whether there is a ghost platform below the character that it can stand on (or drop
+    /// through).
+    pub fn has_platform_below(&self) -> bool {
+        self.platform_below
+    }
+
+    /// Whether the character is currently dropping through a ghost platform.
+    pub fn is_dropping(&self) -> bool {
+        !self.falling_through.is_empty()
+    }
+
+    /// Whether the character is currently passing up through a ghost platform, from below.
+    pub fn is_rising_through(&self) -> bool {
+        !self.rising_through.is_empty()
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_drop_through_platforms_system(
+    time: Res<Time>,
+    mut query: Query<(
+        &mut TnuaDropThroughPlatforms,
+        &TnuaGhostSensor,
+        &mut TnuaProximitySensor,
+        &TnuaRigidBodyTracker,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    query.par_iter_mut().for_each(
+        |(
+            mut drop_through,
+            ghost_sensor,
+            mut proximity_sensor,
+            tracker,
+            tnua_toggle,
+            update_rate,
+        )| {
+            if tnua_toggle.copied().unwrap_or_default() == TnuaToggle::Disabled {
+                return;
+            }
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            let drop_through = drop_through.as_mut();
+            let min_proximity = drop_through.min_proximity;
+            let up = -proximity_sensor.cast_direction.adjust_precision();
+            let mut platforms = ghost_sensor
+                .iter()
+                .filter(|platform| min_proximity <= platform.proximity);
+
+            // Forget the platforms the character has finished falling through.
+            drop_through.falling_through.retain(|entity| {
+                ghost_sensor
+                    .iter()
+                    .any(|platform| platform.entity == *entity)
+            });
+
+            // Platforms the character is jumping through from below are only stood on once the
+            // character is fully above them and no longer moving up relative to them.
+            drop_through.rising_through.retain(|entity| {
+                ghost_sensor.iter().any(|platform| {
+                    platform.entity == *entity
+                        && (platform.proximity < min_proximity
+                            || 0.0 < (tracker.velocity - platform.entity_linvel).dot(up))
+                })
+            });
+            for platform in ghost_sensor.iter() {
+                if platform.proximity < min_proximity
+                    && !drop_through.falling_through.contains(&platform.entity)
+                {
+                    drop_through.rising_through.insert(platform.entity);
+                }
+            }
+
+            let mut platform_to_stand_on = platforms.find(|platform| {
+                !drop_through.falling_through.contains(&platform.entity)
+                    && !drop_through.rising_through.contains(&platform.entity)
+            });
+
+            if 0.0 < drop_through.drop_requested_for {
+                if let Some(platform) = platform_to_stand_on {
+                    drop_through.falling_through.insert(platform.entity);
+                    drop_through.drop_requested_for = 0.0;
+                    platform_to_stand_on = None;
+                } else {
+                    drop_through.drop_requested_for -= frame_duration;
+                }
+            }
+
+            drop_through.platform_below = platform_to_stand_on.is_some();
+            if let Some(platform) = platform_to_stand_on {
+                // Ghost platforms are only detected before solid ones, so this will not hide a
+                // solid platform.
+                proximity_sensor.output = Some(platform.clone());
+            }
+        },
+    );
+}
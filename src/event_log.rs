@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::math::Float;
+
+/// Keeps a log of the recent decisions of the controller, for debugging.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) and
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will record into it the basis
+/// changes, the actions that started, ended or were cancelled, the actions that were rejected
+/// (with the reason), and the landings and takeoffs. Only the last [`capacity`](Self::capacity)
+/// entries are kept, so the log can stay on the character at all times and be
+/// [dumped](Self::dump) when something goes wrong.
+#[derive(Component)]
+pub struct TnuaEventLog {
+    /// The maximum number of entries to keep. Older entries are discarded.
+    pub capacity: usize,
+
+    entries: VecDeque<TnuaEventLogEntry>,
+}
+
+impl Default for TnuaEventLog {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+impl TnuaEventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn record(&mut self, time: f64, event: TnuaControllerEvent) {
+        while self.capacity <= self.entries.len() {
+            if self.entries.pop_front().is_none() {
+                // Capacity is zero
+                return;
+            }
+        }
+        self.entries.push_back(TnuaEventLogEntry { time, event });
+    }
+
+    /// The recorded entries, from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &TnuaEventLogEntry> {
+        self.entries.iter()
+    }
+
+    /// Discard all the recorded entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Format all the recorded entries, one per line, from the oldest to the newest.
+    pub fn dump(&self) -> String {
+        let mut result = String::new();
+        for TnuaEventLogEntry { time, event } in self.entries.iter() {
+            writeln!(&mut result, "{time:.3}: {event:?}").expect("writing to a string cannot fail");
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TnuaEventLogEntry {
+    /// The [elapsed time](Time::elapsed_seconds_f64) when the event happened.
+    pub time: f64,
+    pub event: TnuaControllerEvent,
+}
+
+/// A decision made by the controller.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TnuaControllerEvent {
+    /// A basis with a different name started running.
+    BasisChanged {
+        old: Option<&'static str>,
+        new: &'static str,
+    },
+
+    /// An action has started.
+    ActionStarted(&'static str),
+
+    /// An action has stopped being fed (or finished)
+    ActionEnded(&'static str),
+
+    /// An action has been cancelled into another action.
+    ActionCancelled {
+        old: &'static str,
+        new: &'static str,
+    },
+
+    /// An action was fed, but did not start.
+    ActionRejected {
+        action: &'static str,
+        reason: TnuaActionRejectionReason,
+    },
+
+    /// An action has finished, and will not start again for some time even if it is still fed.
+    ActionRescheduled {
+        action: &'static str,
+        after_seconds: Float,
+    },
+
+    /// The basis started considering the character [airborne](crate::TnuaBasis::is_airborne).
+    ///
+    /// With [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) this only happens after the
+    /// coyote time, unless an action (like a jump) violated it.
+    LeftGround,
+
+    /// The basis stopped considering the character [airborne](crate::TnuaBasis::is_airborne).
+    Landed,
+
+    /// The character was airborne and moving up, and has stopped moving up - e.g. the peak of a
+    /// jump.
+    ApexReached,
+
+    /// The controller was reset by
+    /// [`teleport_reset`](crate::prelude::TnuaController::teleport_reset).
+    TeleportReset,
+}
+
+/// Sent by [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) for every decision and
+/// transition of the controller, so that systems like audio and VFX can react to them without
+/// polling the controllers.
+///
+/// See also [`TnuaController::events`](crate::prelude::TnuaController::events).
+#[derive(Event, Debug, Clone)]
+pub struct TnuaEvent {
+    /// The entity of the character.
+    pub entity: Entity,
+    pub event: TnuaControllerEvent,
+}
+
+/// Sent by [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) when an action that
+/// was fed did not start.
+///
+/// See also [`TnuaController::rejected_actions`](crate::prelude::TnuaController::rejected_actions).
+#[derive(Event, Debug, Clone)]
+pub struct TnuaActionRejected {
+    /// The entity of the character.
+    pub entity: Entity,
+    /// The name the action was fed with.
+    pub action: &'static str,
+    pub reason: TnuaActionRejectionReason,
+}
+
+/// Why an action that was fed did not start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TnuaActionRejectionReason {
+    /// The [`initiation_decision`](crate::TnuaAction::initiation_decision) of the action rejected
+    /// it - e.g. a jump that was fed while the character was in the air and its input buffer time
+    /// has passed.
+    InitiationDecision,
+
+    /// The action was delayed by its
+    /// [`initiation_decision`](crate::TnuaAction::initiation_decision), and stopped being fed
+    /// before it could start.
+    NoLongerFed,
+
+    /// The action was fed while the character was
+    /// [ragdolling](crate::prelude::TnuaController::enter_ragdoll).
+    Ragdolling,
+
+    /// The action was fed for the first time in the same frame as another action, which was
+    /// chosen over it (see [`TnuaActionResolutionPolicy`](crate::TnuaActionResolutionPolicy)).
+    OutprioritizedBy(&'static str),
+
+    /// The action shares an [exclusive
+    /// group](crate::TnuaActionResolutionPolicy::exclusive_groups) with the action that is
+    /// currently running.
+    MutuallyExclusiveWith(&'static str),
+
+    /// The [`TnuaResourcePool`](crate::TnuaResourcePool) of the character does not have enough
+    /// for the [cost](crate::TnuaActionCost) of the action.
+    InsufficientResources,
+}
@@ -0,0 +1,93 @@
+//! The API for crates that extend Tnua with their own [bases](TnuaBasis) and
+//! [actions](TnuaAction).
+//!
+//! Everything a basis or an action needs in order to be implemented is re-exported here, and
+//! crates that only implement bases and actions (e.g. a wall running action or a swimming basis)
+//! are encouraged to import from this module only. The items in this module follow these semver
+//! rules:
+//!
+//! * Removing or renaming an item, changing the signature of a trait method, adding a trait method
+//!   without a default implementation, or changing the meaning of a field is a breaking change
+//!   and will only happen in a version that bumps the minor version (while Tnua is `0.x`) and will
+//!   be marked as **BREAKING** in the changelog.
+//! * Adding fields to [`TnuaBasisContext`] and [`TnuaActionContext`], and adding variants to
+//!   [`TnuaActionLifecycleStatus`], [`TnuaActionLifecycleDirective`] and
+//!   [`TnuaActionInitiationDirective`], is **not** a breaking change. These types are marked as
+//!   `#[non_exhaustive]`, so extension crates cannot construct the contexts or match the enums
+//!   exhaustively.
+//! * Adding trait methods with default implementations is not a breaking change. The default
+//!   implementation will behave the same as the trait did before the method was added.
+//! * The sensor and motor data ([`TnuaRigidBodyTracker`], [`TnuaProximitySensor`], [`TnuaMotor`]
+//!   and [`TnuaVelChange`]) come from
+//!   [bevy-tnua-physics-integration-layer](https://crates.io/crates/bevy-tnua-physics-integration-layer),
+//!   which changes less often than the main crate.
+//!
+//! Items that are not re-exported here - the builtin bases and actions, the control helpers, the
+//! [`motion_math`](crate::motion_math) functions and the controller's internals - may change in
+//! any release.
+//!
+//! A minimal basis, that floats the character at a fixed height regardless of the ground:
+//!
+//! ```
+//! use bevy_tnua::extension::*;
+//! use bevy_tnua::math::{AdjustPrecision, Float, Vector3};
+//!
+//! struct Hover {
+//!     height: Float,
+//! }
+//!
+//! #[derive(Default)]
+//! struct HoverState {
+//!     velocity: Vector3,
+//! }
+//!
+//! impl TnuaBasis for Hover {
+//!     const NAME: &'static str = "Hover";
+//!     type State = HoverState;
+//!
+//!     fn apply(&self, state: &mut Self::State, ctx: TnuaBasisContext, motor: &mut TnuaMotor) {
+//!         state.velocity = ctx.tracker.velocity;
+//!         let up = ctx.up_direction.adjust_precision();
+//!         let offset = self.height - ctx.tracker.translation.dot(up);
+//!         let desired_velocity = (offset / ctx.frame_duration) * up;
+//!         motor.lin = TnuaVelChange::boost(desired_velocity - ctx.tracker.velocity)
+//!             + TnuaVelChange::acceleration(-ctx.tracker.gravity);
+//!     }
+//!
+//!     fn proximity_sensor_cast_range(&self, _state: &Self::State) -> Float {
+//!         0.0
+//!     }
+//!
+//!     fn displacement(&self, _state: &Self::State) -> Option<Vector3> {
+//!         None
+//!     }
+//!
+//!     fn effective_velocity(&self, state: &Self::State) -> Vector3 {
+//!         state.velocity
+//!     }
+//!
+//!     fn vertical_velocity(&self, _state: &Self::State) -> Float {
+//!         0.0
+//!     }
+//!
+//!     fn neutralize(&mut self) {}
+//!
+//!     fn is_airborne(&self, _state: &Self::State) -> bool {
+//!         true
+//!     }
+//!
+//!     fn violate_coyote_time(&self, _state: &mut Self::State) {}
+//! }
+//! ```
+
+pub use bevy::time::Stopwatch;
+
+pub use crate::basis_action_traits::{
+    DynamicBasis, TnuaAction, TnuaActionContext, TnuaActionInitiationDirective,
+    TnuaActionLifecycleDirective, TnuaActionLifecycleStatus, TnuaBasis, TnuaBasisContext,
+};
+pub use crate::root_motion::TnuaRootMotionVelocity;
+pub use crate::surface_properties::TnuaSurfaceProperties;
+pub use bevy_tnua_physics_integration_layer::data_for_backends::{
+    TnuaMotor, TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker, TnuaVelChange,
+};
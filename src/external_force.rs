@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::math::{Float, Vector3};
+use crate::TnuaVelChange;
+
+/// Pushes a character with forces that come from the game rather than from its controls - e.g.
+/// wind, conveyors or explosions.
+///
+/// Applying forces or impulses directly to the rigid body (e.g. with the `ExternalImpulse` of the
+/// physics backend) does not work well with Tnua, because the basis treats the resulting velocity
+/// as a deviation from the desired velocity and cancels it within a frame or two. Instead, add
+/// this component to the character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) and write to it.
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will:
+///
+/// * Apply the [`acceleration`](Self::acceleration) and the [`impulse`](Self::impulse) to the
+///   character through the motor, and then clear them. This means that continuous forces (like
+///   wind) must be written every frame they should be in effect.
+/// * Accumulate them into a [velocity offset](Self::velocity_offset), which is passed to the
+///   basis as [`external_velocity`](crate::TnuaBasisContext::external_velocity).
+///   [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) adds it to its desired velocity, so
+///   that it will not fight the push.
+///
+/// The velocity offset decays over time (at a rate determined by [`decay`](Self::decay)), at which
+/// point the basis takes back control of the character's velocity.
+#[derive(Component)]
+pub struct TnuaExternalForce {
+    /// An acceleration to apply to the character in the current frame.
+    ///
+    /// Cleared once it is applied.
+    pub acceleration: Vector3,
+
+    /// A velocity change to apply to the character in the current frame.
+    ///
+    /// Cleared once it is applied.
+    pub impulse: Vector3,
+
+    /// The rate, per second, of the exponential decay of the velocity offset.
+    ///
+    /// A constant acceleration will make the velocity offset converge to the acceleration
+    /// divided by this number.
+    pub decay: Float,
+
+    velocity_offset: Vector3,
+}
+
+impl Default for TnuaExternalForce {
+    fn default() -> Self {
+        Self {
+            acceleration: Vector3::ZERO,
+            impulse: Vector3::ZERO,
+            decay: 2.0,
+            velocity_offset: Vector3::ZERO,
+        }
+    }
+}
+
+impl TnuaExternalForce {
+    /// The velocity the external forces accumulated to, which the basis should not cancel.
+    pub fn velocity_offset(&self) -> Vector3 {
+        self.velocity_offset
+    }
+
+    /// Add a velocity change to apply in the current frame (e.g. from an explosion).
+    pub fn add_impulse(&mut self, impulse: Vector3) {
+        self.impulse += impulse;
+    }
+
+    /// Add an acceleration to apply in the current frame (e.g. from wind).
+    pub fn add_acceleration(&mut self, acceleration: Vector3) {
+        self.acceleration += acceleration;
+    }
+
+    /// Update the velocity offset, and return the velocity change the motor should apply.
+    pub(crate) fn consume(&mut self, frame_duration: Float) -> TnuaVelChange {
+        let vel_change = TnuaVelChange {
+            acceleration: std::mem::take(&mut self.acceleration),
+            boost: std::mem::take(&mut self.impulse),
+        };
+        self.velocity_offset *= (-self.decay * frame_duration).exp();
+        self.velocity_offset += vel_change.calc_boost(frame_duration);
+        vel_change
+    }
+}
@@ -22,6 +22,21 @@
 //! defined by the physics backend - Bevy itself will still use single precision, and this is the
 //! precision the position and rotation will use.
 //!
+//! With the `f64` flag, all of Tnua's math - the configuration of the bases and actions, the
+//! sensors' output, the motors and the helpers in [`math`] and [`motion_math`] - uses
+//! [`Float`](math::Float), which becomes `f64`, and [`Vector3`](math::Vector3), which becomes
+//! `DVec3`. Rapier's Bevy integration only supports single precision, so the Rapier integration
+//! crates do not have that flag.
+//!
+//! The `gizmos` flag enables debug helpers that draw with Bevy's gizmos, like
+//! `TnuaTrajectoryGizmos` for visualizing the tuning of the movement.
+//!
+//! The `bevy_animation` flag enables `TnuaAnimationDriver`, which plays animations according to
+//! the controller's state for games that do not need custom animation logic.
+//!
+//! The `serialize` flag implements serde's traits for the data that games may want to save or
+//! send, like `TnuaControlsRecording` and `TnuaCharacterSnapshot`.
+//!
 //! In addition to the physics integration plugin, the
 //! [`TnuaControllerPlugin`](prelude::TnuaControllerPlugin) should also be added.
 //!
@@ -126,17 +141,53 @@
 //!
 //! [`TnuaController`](crate::prelude::TnuaController) can also be used to retreive data that can
 //! be used to decide which animation to play. A useful helper for that is [`TnuaAnimatingState`].
+mod action_resolution;
 mod animating_helper;
+mod arena_bounds;
+mod assists;
 mod basis_action_traits;
 pub mod builtins;
+mod carrying;
 pub mod control_helpers;
 pub mod controller;
+mod crowd_separation;
+mod drop_through;
+mod event_log;
+pub mod extension;
+mod external_force;
+pub mod motion_math;
+mod replication;
+mod resource_pool;
+mod root_motion;
+mod surface_properties;
+mod swept_sensing;
+pub mod testing;
 pub mod util;
-pub use animating_helper::{TnuaAnimatingState, TnuaAnimatingStateDirective};
+pub use action_resolution::{TnuaActionResolution, TnuaActionResolutionPolicy};
+pub use animating_helper::{
+    TnuaAnimatingHierarchy, TnuaAnimatingState, TnuaAnimatingStateDirective,
+};
+pub use arena_bounds::{TnuaArenaBounds, TnuaArenaBoundsMode};
+pub use assists::TnuaAssists;
 pub use basis_action_traits::{
     DynamicAction, DynamicBasis, TnuaAction, TnuaActionContext, TnuaActionInitiationDirective,
     TnuaActionLifecycleDirective, TnuaActionLifecycleStatus, TnuaBasis, TnuaBasisContext,
 };
+pub use carrying::{TnuaCarrier, TnuaNotCarried};
+pub use crowd_separation::TnuaCrowdSeparation;
+pub use drop_through::TnuaDropThroughPlatforms;
+pub use event_log::{
+    TnuaActionRejected, TnuaActionRejectionReason, TnuaControllerEvent, TnuaEvent, TnuaEventLog,
+    TnuaEventLogEntry,
+};
+pub use external_force::TnuaExternalForce;
+pub use replication::{
+    TnuaCharacterSnapshot, TnuaReplicationImportance, TnuaSnapshotCodec, TnuaSnapshotDecodeError,
+};
+pub use resource_pool::{TnuaActionCost, TnuaResourcePool};
+pub use root_motion::{TnuaRootMotion, TnuaRootMotionVelocity};
+pub use surface_properties::{TnuaConveyorSurface, TnuaSurfaceProperties};
+pub use swept_sensing::TnuaSweptSensing;
 
 pub mod prelude {
     pub use crate::builtins::{TnuaBuiltinJump, TnuaBuiltinWalk};
@@ -0,0 +1,296 @@
+//! The core math of Tnua's builtins, as pure functions.
+//!
+//! These functions do not depend on the ECS or on any physics backend, so they can be used for
+//! unit-testing tuning values, for building editors and visualizers, and for reusing Tnua's
+//! movement math in custom [bases](crate::TnuaBasis) and [actions](crate::TnuaAction).
+//!
+//! All the scalar values that represent a direction are measured along the up direction, and all
+//! the gravities are given as positive magnitudes (e.g. `9.81`, not `-9.81`).
+//!
+//! ```
+//! # use bevy_tnua::builtins::TnuaBuiltinWalk;
+//! # use bevy_tnua::motion_math::simulate_float_spring;
+//! let walk = TnuaBuiltinWalk::default();
+//! // Start 0.5 below the float height, and check that the character settles in a second.
+//! let (height, velocity) = simulate_float_spring(
+//!     walk.spring_strengh,
+//!     walk.spring_dampening,
+//!     -0.5,
+//!     0.0,
+//!     1.0 / 60.0,
+//! )
+//! .nth(60)
+//! .unwrap();
+//! assert!(height.abs() < 0.01);
+//! assert!(velocity.abs() < 0.1);
+//! ```
+use crate::builtins::TnuaBuiltinJump;
+use crate::math::{Float, Vector3};
+use crate::util::SegmentedJumpInitialVelocityCalculator;
+
+/// The acceleration the floating spring applies, including the compensation for gravity.
+///
+/// * `spring_offset` - how far below the float height the character is (negative when it is above
+///   the float height).
+pub fn float_spring_acceleration(
+    spring_offset: Float,
+    spring_strength: Float,
+    gravity: Float,
+) -> Float {
+    spring_offset * spring_strength + gravity
+}
+
+/// The (instant) boost the floating spring applies to dampen the vertical motion.
+///
+/// * `relative_velocity` - the vertical velocity of the character relative to the ground it floats
+///   above.
+pub fn float_spring_dampening_boost(relative_velocity: Float, spring_dampening: Float) -> Float {
+    -relative_velocity * spring_dampening
+}
+
+/// Simulate the floating spring frame by frame, yielding the height above the float height and the
+/// vertical velocity after each frame.
+///
+/// The simulation assumes the ground is static and that the spring is in effect during the entire
+/// simulation (that is - the character never goes above the cling distance)
+pub fn simulate_float_spring(
+    spring_strength: Float,
+    spring_dampening: Float,
+    initial_height: Float,
+    initial_velocity: Float,
+    frame_duration: Float,
+) -> impl Iterator<Item = (Float, Float)> {
+    let mut height = initial_height;
+    let mut velocity = initial_velocity;
+    std::iter::from_fn(move || {
+        // The gravity compensation cancels the gravity, so neither is needed here.
+        let acceleration = float_spring_acceleration(-height, spring_strength, 0.0);
+        velocity += acceleration * frame_duration
+            + float_spring_dampening_boost(velocity, spring_dampening);
+        height += velocity * frame_duration;
+        Some((height, velocity))
+    })
+}
+
+/// The kinetic energy (for a mass of 1.0) that [`TnuaBuiltinJump`] needs to reach its
+/// [effective height](TnuaBuiltinJump::effective_height), with its segmented gravity.
+pub fn jump_kinetic_energy(jump: &TnuaBuiltinJump, gravity: Float) -> Float {
+    SegmentedJumpInitialVelocityCalculator::new(jump.effective_height())
+        .add_segment(
+            gravity + jump.peak_prevention_extra_gravity,
+            jump.peak_prevention_at_upward_velocity,
+        )
+        .add_segment(gravity, jump.takeoff_above_velocity)
+        .add_final_segment(gravity + jump.takeoff_extra_gravity)
+        .kinetic_energy()
+        .expect("`add_final_segment` should have covered remaining height")
+}
+
+/// The initial upward velocity [`TnuaBuiltinJump`] takes off with, when jumping from the float
+/// height on flat ground.
+pub fn jump_takeoff_velocity(jump: &TnuaBuiltinJump, gravity: Float) -> Float {
+    SegmentedJumpInitialVelocityCalculator::kinetic_energy_to_velocity(jump_kinetic_energy(
+        jump, gravity,
+    ))
+}
+
+/// The time, in seconds, it takes [`TnuaBuiltinJump`] to reach its peak when the jump button is
+/// held for the entire jump.
+///
+/// ```
+/// # use bevy_tnua::builtins::TnuaBuiltinJump;
+/// # use bevy_tnua::math::Float;
+/// # use bevy_tnua::motion_math::{jump_takeoff_velocity, jump_time_to_peak};
+/// let jump = TnuaBuiltinJump {
+///     height: 4.0,
+///     ..Default::default()
+/// };
+/// // The extra gravity makes the jump snappier than a ballistic jump with the same height.
+/// let ballistic_time_to_peak = (2.0 * 4.0 / 9.81 as Float).sqrt();
+/// assert!(jump_time_to_peak(&jump, 9.81) < ballistic_time_to_peak);
+/// // ...which requires a higher takeoff velocity.
+/// assert!((2.0 * 4.0 * 9.81 as Float).sqrt() < jump_takeoff_velocity(&jump, 9.81));
+/// ```
+pub fn jump_time_to_peak(jump: &TnuaBuiltinJump, gravity: Float) -> Float {
+    let takeoff_velocity = jump_takeoff_velocity(jump, gravity);
+    // Same segments as in `jump_kinetic_energy`, from the peak down.
+    let segments = [
+        (
+            gravity + jump.peak_prevention_extra_gravity,
+            jump.peak_prevention_at_upward_velocity,
+        ),
+        (gravity, jump.takeoff_above_velocity),
+        (gravity + jump.takeoff_extra_gravity, Float::INFINITY),
+    ];
+    let mut time = 0.0;
+    let mut segment_top_velocity: Float = 0.0;
+    for (segment_gravity, velocity_threshold) in segments {
+        let segment_bottom_velocity = velocity_threshold.min(takeoff_velocity);
+        if segment_top_velocity < segment_bottom_velocity {
+            time += (segment_bottom_velocity - segment_top_velocity) / segment_gravity;
+            segment_top_velocity = segment_bottom_velocity;
+        }
+    }
+    time
+}
+
+/// The extra gravity (added to the regular gravity) that [`TnuaBuiltinJump`] applies while the
+/// jump button is held, according to the upward velocity of the character.
+pub fn jump_extra_gravity(jump: &TnuaBuiltinJump, upward_velocity: Float) -> Float {
+    if upward_velocity <= 0.0 {
+        jump.fall_extra_gravity
+    } else if upward_velocity < jump.peak_prevention_at_upward_velocity {
+        jump.peak_prevention_extra_gravity
+    } else if jump.takeoff_above_velocity <= upward_velocity {
+        jump.takeoff_extra_gravity
+    } else {
+        0.0
+    }
+}
+
+/// Simulate a [`TnuaBuiltinJump`] frame by frame, yielding the height above the takeoff point and
+/// the vertical velocity after each frame.
+///
+/// The simulation assumes the jump button is held for the entire jump and that nothing blocks the
+/// character. It ends with the first frame where the character falls below the takeoff height.
+///
+/// ```
+/// # use bevy_tnua::builtins::TnuaBuiltinJump;
+/// # use bevy_tnua::math::Float;
+/// # use bevy_tnua::motion_math::simulate_jump;
+/// let jump = TnuaBuiltinJump {
+///     height: 4.0,
+///     ..Default::default()
+/// };
+/// let peak = simulate_jump(&jump, 9.81, 1.0 / 60.0)
+///     .map(|(height, _)| height)
+///     .fold(0.0, Float::max);
+/// // Integrating frame by frame (like the physics backends do) loses a little height.
+/// assert!(3.7 < peak && peak <= 4.0);
+/// ```
+pub fn simulate_jump(
+    jump: &TnuaBuiltinJump,
+    gravity: Float,
+    frame_duration: Float,
+) -> impl Iterator<Item = (Float, Float)> {
+    let jump = jump.clone();
+    let mut height = 0.0;
+    let mut velocity = jump_takeoff_velocity(&jump, gravity);
+    std::iter::from_fn(move || {
+        if height < 0.0 {
+            return None;
+        }
+        velocity -= (gravity + jump_extra_gravity(&jump, velocity)) * frame_duration;
+        height += velocity * frame_duration;
+        Some((height, velocity))
+    })
+}
+
+/// The time, in seconds, it takes [`TnuaBuiltinJump`] to come down to a height that is
+/// `height_offset` above the takeoff height (when the jump button is held for the entire jump)
+///
+/// Returns `None` if the jump cannot reach that height.
+pub fn jump_time_to_land_at(
+    jump: &TnuaBuiltinJump,
+    gravity: Float,
+    height_offset: Float,
+) -> Option<Float> {
+    let jump_height = jump.effective_height();
+    if jump_height < height_offset {
+        return None;
+    }
+    let fall_time =
+        (2.0 * (jump_height - height_offset) / (gravity + jump.fall_extra_gravity)).sqrt();
+    Some(jump_time_to_peak(jump, gravity) + fall_time)
+}
+
+/// The maximum acceleration used for reaching a desired velocity.
+///
+/// When the character needs to brake or change direction, the acceleration is greater - up to 2
+/// times `acceleration` when doing a 180 turn.
+pub fn velocity_matching_max_acceleration(
+    current_velocity: Vector3,
+    desired_velocity: Vector3,
+    acceleration: Float,
+) -> Float {
+    let safe_direction_coefficient = desired_velocity
+        .normalize_or_zero()
+        .dot(current_velocity.normalize_or_zero());
+    let direction_change_factor = 1.5 - 0.5 * safe_direction_coefficient;
+    direction_change_factor * acceleration
+}
+
+/// The acceleration to apply during a frame for changing the velocity to the desired velocity.
+///
+/// The result is limited by [`velocity_matching_max_acceleration`], and will not overshoot the
+/// desired velocity.
+pub fn velocity_matching_acceleration(
+    current_velocity: Vector3,
+    desired_velocity: Vector3,
+    acceleration: Float,
+    frame_duration: Float,
+) -> Vector3 {
+    let max_acceleration =
+        velocity_matching_max_acceleration(current_velocity, desired_velocity, acceleration);
+    ((desired_velocity - current_velocity) / frame_duration).clamp_length_max(max_acceleration)
+}
+
+/// The distance a character moving at `speed` needs for coming to a full stop, when it brakes with
+/// the acceleration [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) uses for stopping.
+pub fn stopping_distance(speed: Float, acceleration: Float) -> Float {
+    let deceleration =
+        velocity_matching_max_acceleration(speed * Vector3::X, Vector3::ZERO, acceleration);
+    speed.powi(2) / (2.0 * deceleration)
+}
+
+/// The upward boost needed to keep a falling body from exceeding `max_fall_speed` by the end of
+/// the frame.
+///
+/// `upward_acceleration` is the total acceleration along the up direction that the body is going
+/// to get during the frame - including the gravity, so it is negative while falling.
+pub fn fall_speed_limit_boost(
+    upward_velocity: Float,
+    upward_acceleration: Float,
+    max_fall_speed: Float,
+    frame_duration: Float,
+) -> Float {
+    let upward_velocity_at_frame_end = upward_velocity + upward_acceleration * frame_duration;
+    (-max_fall_speed - upward_velocity_at_frame_end).max(0.0)
+}
+
+/// The time it takes a body moving ballistically to reach, on its way down, a height that is
+/// `height_offset` above its current height.
+///
+/// Returns `None` if the body never reaches that height (e.g. because it is too high above the
+/// apex of its trajectory). `gravity` must be positive.
+pub fn ballistic_descent_time(
+    height_offset: Float,
+    upward_velocity: Float,
+    gravity: Float,
+) -> Option<Float> {
+    let discriminant = upward_velocity.powi(2) - 2.0 * gravity * height_offset;
+    if discriminant < 0.0 {
+        return None;
+    }
+    Some((upward_velocity + discriminant.sqrt()) / gravity)
+}
+
+/// The velocity a body moving ballistically needs in order to be at `offset` from its current
+/// position after `time` seconds.
+///
+/// ```
+/// # use bevy_tnua::math::{Float, Vector3};
+/// # use bevy_tnua::motion_math::{ballistic_descent_time, ballistic_velocity_to_reach};
+/// let offset = Vector3::new(3.0, 1.0, 0.0);
+/// let velocity = ballistic_velocity_to_reach(offset, 1.0, 9.81, Vector3::Y);
+/// let time = ballistic_descent_time(offset.y, velocity.y, 9.81).unwrap();
+/// assert!((time - 1.0).abs() < 0.0001);
+/// ```
+pub fn ballistic_velocity_to_reach(
+    offset: Vector3,
+    time: Float,
+    gravity: Float,
+    up: Vector3,
+) -> Vector3 {
+    offset / time + 0.5 * gravity * time * up
+}
@@ -0,0 +1,550 @@
+use crate::math::{Float, Quaternion, Vector3};
+use crate::prelude::*;
+use crate::TnuaRigidBodyTracker;
+
+/// The state of a character that needs to be sent over the network, for encoding with
+/// [`TnuaSnapshotCodec`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TnuaCharacterSnapshot {
+    pub translation: Vector3,
+    pub rotation: Quaternion,
+    pub velocity: Vector3,
+    pub is_airborne: bool,
+
+    /// An identifier of the running action, for playing its animation on the remote side. The
+    /// numbering is up to the game.
+    pub action_id: Option<u8>,
+}
+
+impl Default for TnuaCharacterSnapshot {
+    fn default() -> Self {
+        Self {
+            translation: Vector3::ZERO,
+            rotation: Quaternion::IDENTITY,
+            velocity: Vector3::ZERO,
+            is_airborne: false,
+            action_id: None,
+        }
+    }
+}
+
+impl TnuaCharacterSnapshot {
+    /// Take a snapshot of a character. The [`action_id`](Self::action_id) is left as `None`.
+    pub fn capture(tracker: &TnuaRigidBodyTracker, controller: &TnuaController) -> Self {
+        Self {
+            translation: tracker.translation,
+            rotation: tracker.rotation,
+            velocity: tracker.velocity,
+            is_airborne: controller.is_airborne().unwrap_or(false),
+            action_id: None,
+        }
+    }
+}
+
+/// How fast a character's state is changing, for prioritizing the replication of characters over
+/// the network.
+///
+/// Get it from [`TnuaController::replication_importance`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TnuaReplicationImportance {
+    /// The speed of the character.
+    pub speed: Float,
+
+    /// The number of [controller events](TnuaController::events) - actions starting and ending,
+    /// leaving the ground, landing etc. - in the recent frames. Each event decays exponentially,
+    /// to about a third of its weight after one second.
+    pub recent_state_changes: Float,
+
+    /// Whether the character is airborne (where players notice replication errors more).
+    pub is_airborne: bool,
+}
+
+impl TnuaReplicationImportance {
+    pub(crate) fn update(
+        &mut self,
+        speed: Float,
+        new_state_changes: usize,
+        is_airborne: bool,
+        frame_duration: Float,
+    ) {
+        self.speed = speed;
+        self.recent_state_changes =
+            self.recent_state_changes * (-frame_duration).exp() + new_state_changes as Float;
+        self.is_airborne = is_airborne;
+    }
+
+    /// A single number combining the metrics, for sorting the characters by their replication
+    /// priority. Games with specific needs should combine the metrics themselves.
+    pub fn score(&self) -> Float {
+        self.speed + 5.0 * self.recent_state_changes + if self.is_airborne { 5.0 } else { 0.0 }
+    }
+}
+
+/// Why [`TnuaSnapshotCodec::decode`] could not decode a snapshot.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum TnuaSnapshotDecodeError {
+    #[error("The encoded snapshot ended unexpectedly")]
+    UnexpectedEnd,
+    #[error("The encoded snapshot contains an invalid number")]
+    InvalidNumber,
+    #[error("The encoded snapshot is relative to a baseline, but no baseline was given")]
+    MissingBaseline,
+}
+
+const TRANSLATION_CHANGED: u8 = 1 << 0;
+const VELOCITY_CHANGED: u8 = 1 << 1;
+const ROTATION_CHANGED: u8 = 1 << 2;
+const ACTION_CHANGED: u8 = 1 << 3;
+const IS_AIRBORNE: u8 = 1 << 4;
+const HAS_ACTION: u8 = 1 << 5;
+const HAS_BASELINE: u8 = 1 << 6;
+
+/// Encodes [`TnuaCharacterSnapshot`]s into a compact binary form, for replicating many characters
+/// over the network.
+///
+/// The positions, velocities and rotations are quantized to the configured precisions, and
+/// encoded as the difference from a baseline snapshot - usually the last one the other side has
+/// acknowledged. Fields that did not change are not encoded at all, and the boolean flags are
+/// packed into a single header byte, so a character that stands still costs a single byte.
+///
+/// Both sides must use the same baseline - the one that was decoded, not the original one:
+///
+/// ```
+/// # use bevy_tnua::{TnuaCharacterSnapshot, TnuaSnapshotCodec};
+/// # use bevy_tnua::math::Vector3;
+/// let codec = TnuaSnapshotCodec::default();
+/// let snapshot = TnuaCharacterSnapshot {
+///     translation: Vector3::new(12.34, 1.5, -7.0),
+///     velocity: Vector3::new(3.0, 0.0, -0.25),
+///     is_airborne: true,
+///     action_id: Some(2),
+///     ..Default::default()
+/// };
+///
+/// let mut full = Vec::new();
+/// codec.encode(&snapshot, None, &mut full);
+/// let baseline = codec.decode(&full, None).unwrap();
+/// assert!(baseline.translation.distance(snapshot.translation) <= codec.position_precision);
+/// assert!(baseline.velocity.distance(snapshot.velocity) <= codec.velocity_precision);
+/// assert_eq!(baseline.is_airborne, snapshot.is_airborne);
+/// assert_eq!(baseline.action_id, snapshot.action_id);
+///
+/// let next = TnuaCharacterSnapshot {
+///     translation: snapshot.translation + Vector3::new(0.05, 0.0, 0.0),
+///     ..snapshot.clone()
+/// };
+/// let mut delta = Vec::new();
+/// codec.encode(&next, Some(&baseline), &mut delta);
+/// assert!(delta.len() < full.len());
+/// let decoded = codec.decode(&delta, Some(&baseline)).unwrap();
+/// assert!(decoded.translation.distance(next.translation) <= codec.position_precision);
+///
+/// // Nothing changed - only the header is sent.
+/// let mut unchanged = Vec::new();
+/// codec.encode(&decoded, Some(&decoded), &mut unchanged);
+/// assert_eq!(unchanged.len(), 1);
+/// assert_eq!(codec.decode(&unchanged, Some(&decoded)).unwrap(), decoded);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TnuaSnapshotCodec {
+    /// The precision, in length units, of the encoded translation.
+    pub position_precision: Float,
+
+    /// The precision, in length units per second, of the encoded velocity.
+    pub velocity_precision: Float,
+
+    /// The precision of each component of the encoded rotation quaternion.
+    pub rotation_precision: Float,
+}
+
+impl Default for TnuaSnapshotCodec {
+    fn default() -> Self {
+        Self {
+            position_precision: 0.001,
+            velocity_precision: 0.01,
+            rotation_precision: 0.0001,
+        }
+    }
+}
+
+impl TnuaSnapshotCodec {
+    /// Append the encoded snapshot to `out`.
+    ///
+    /// If `baseline` is `None`, the snapshot is encoded in full.
+    pub fn encode(
+        &self,
+        snapshot: &TnuaCharacterSnapshot,
+        baseline: Option<&TnuaCharacterSnapshot>,
+        out: &mut Vec<u8>,
+    ) {
+        let default_baseline = TnuaCharacterSnapshot::default();
+        let has_baseline = baseline.is_some();
+        let baseline = baseline.unwrap_or(&default_baseline);
+
+        let translation = quantize_vector(snapshot.translation, self.position_precision);
+        let translation_delta = delta(
+            translation,
+            quantize_vector(baseline.translation, self.position_precision),
+        );
+        let velocity = quantize_vector(snapshot.velocity, self.velocity_precision);
+        let velocity_delta = delta(
+            velocity,
+            quantize_vector(baseline.velocity, self.velocity_precision),
+        );
+        let rotation = quantize_rotation(snapshot.rotation, self.rotation_precision);
+        let rotation_delta = delta(
+            rotation,
+            quantize_rotation(baseline.rotation, self.rotation_precision),
+        );
+
+        let mut header = 0;
+        if has_baseline {
+            header |= HAS_BASELINE;
+        }
+        if translation_delta.iter().any(|d| *d != 0) {
+            header |= TRANSLATION_CHANGED;
+        }
+        if velocity_delta.iter().any(|d| *d != 0) {
+            header |= VELOCITY_CHANGED;
+        }
+        if rotation_delta.iter().any(|d| *d != 0) {
+            header |= ROTATION_CHANGED;
+        }
+        if snapshot.action_id != baseline.action_id {
+            header |= ACTION_CHANGED;
+        }
+        if snapshot.is_airborne {
+            header |= IS_AIRBORNE;
+        }
+        if snapshot.action_id.is_some() {
+            header |= HAS_ACTION;
+        }
+        out.push(header);
+
+        if header & TRANSLATION_CHANGED != 0 {
+            translation_delta[..3]
+                .iter()
+                .for_each(|d| write_varint(*d, out));
+        }
+        if header & VELOCITY_CHANGED != 0 {
+            velocity_delta[..3]
+                .iter()
+                .for_each(|d| write_varint(*d, out));
+        }
+        if header & ROTATION_CHANGED != 0 {
+            rotation_delta.iter().for_each(|d| write_varint(*d, out));
+        }
+        if let (true, Some(action_id)) = (header & ACTION_CHANGED != 0, snapshot.action_id) {
+            out.push(action_id);
+        }
+    }
+
+    /// Decode a snapshot encoded by [`encode`](Self::encode) with the same baseline.
+    pub fn decode(
+        &self,
+        bytes: &[u8],
+        baseline: Option<&TnuaCharacterSnapshot>,
+    ) -> Result<TnuaCharacterSnapshot, TnuaSnapshotDecodeError> {
+        let mut reader = bytes.iter().copied();
+        let header = reader
+            .next()
+            .ok_or(TnuaSnapshotDecodeError::UnexpectedEnd)?;
+        let default_baseline = TnuaCharacterSnapshot::default();
+        let baseline = match (header & HAS_BASELINE != 0, baseline) {
+            (true, Some(baseline)) => baseline,
+            (true, None) => return Err(TnuaSnapshotDecodeError::MissingBaseline),
+            (false, _) => &default_baseline,
+        };
+
+        let mut read_deltas = |changed: bool, base: [i64; 4], components: usize| {
+            let mut result = base;
+            if changed {
+                for component in result[..components].iter_mut() {
+                    // The encoder uses wrapping subtraction for the deltas.
+                    *component = component.wrapping_add(read_varint(&mut reader)?);
+                }
+            }
+            Ok(result)
+        };
+        let translation = read_deltas(
+            header & TRANSLATION_CHANGED != 0,
+            quantize_vector(baseline.translation, self.position_precision),
+            3,
+        )?;
+        let velocity = read_deltas(
+            header & VELOCITY_CHANGED != 0,
+            quantize_vector(baseline.velocity, self.velocity_precision),
+            3,
+        )?;
+        let rotation = read_deltas(
+            header & ROTATION_CHANGED != 0,
+            quantize_rotation(baseline.rotation, self.rotation_precision),
+            4,
+        )?;
+        let action_id = if header & ACTION_CHANGED == 0 {
+            baseline.action_id
+        } else if header & HAS_ACTION != 0 {
+            Some(
+                reader
+                    .next()
+                    .ok_or(TnuaSnapshotDecodeError::UnexpectedEnd)?,
+            )
+        } else {
+            None
+        };
+
+        Ok(TnuaCharacterSnapshot {
+            translation: dequantize_vector(translation, self.position_precision),
+            rotation: dequantize_rotation(rotation, self.rotation_precision),
+            velocity: dequantize_vector(velocity, self.velocity_precision),
+            is_airborne: header & IS_AIRBORNE != 0,
+            action_id,
+        })
+    }
+}
+
+// Vectors are padded to four components so that they can share the code with the rotations.
+fn quantize_vector(vector: Vector3, precision: Float) -> [i64; 4] {
+    [
+        quantize(vector.x, precision),
+        quantize(vector.y, precision),
+        quantize(vector.z, precision),
+        0,
+    ]
+}
+
+fn dequantize_vector(quantized: [i64; 4], precision: Float) -> Vector3 {
+    Vector3::new(
+        quantized[0] as Float * precision,
+        quantized[1] as Float * precision,
+        quantized[2] as Float * precision,
+    )
+}
+
+fn quantize_rotation(rotation: Quaternion, precision: Float) -> [i64; 4] {
+    // `q` and `-q` are the same rotation - pick the one that makes for smaller deltas.
+    let rotation = if rotation.w < 0.0 {
+        -rotation
+    } else {
+        rotation
+    };
+    [
+        quantize(rotation.x, precision),
+        quantize(rotation.y, precision),
+        quantize(rotation.z, precision),
+        quantize(rotation.w, precision),
+    ]
+}
+
+fn dequantize_rotation(quantized: [i64; 4], precision: Float) -> Quaternion {
+    Quaternion::from_xyzw(
+        quantized[0] as Float * precision,
+        quantized[1] as Float * precision,
+        quantized[2] as Float * precision,
+        quantized[3] as Float * precision,
+    )
+    .normalize()
+}
+
+fn quantize(value: Float, precision: Float) -> i64 {
+    (value / precision).round() as i64
+}
+
+fn delta(value: [i64; 4], baseline: [i64; 4]) -> [i64; 4] {
+    std::array::from_fn(|i| value[i].wrapping_sub(baseline[i]))
+}
+
+fn write_varint(value: i64, out: &mut Vec<u8>) {
+    // Zigzag encoding, so that small negative numbers are also encoded in few bytes.
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while 0x80 <= value {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(reader: &mut impl Iterator<Item = u8>) -> Result<i64, TnuaSnapshotDecodeError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = reader
+            .next()
+            .ok_or(TnuaSnapshotDecodeError::UnexpectedEnd)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(((value >> 1) as i64) ^ -((value & 1) as i64));
+        }
+    }
+    Err(TnuaSnapshotDecodeError::InvalidNumber)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(
+        codec: &TnuaSnapshotCodec,
+        snapshot: &TnuaCharacterSnapshot,
+        baseline: Option<&TnuaCharacterSnapshot>,
+    ) -> TnuaCharacterSnapshot {
+        let mut encoded = Vec::new();
+        codec.encode(snapshot, baseline, &mut encoded);
+        codec.decode(&encoded, baseline).unwrap()
+    }
+
+    #[test]
+    fn rotation_round_trip() {
+        let codec = TnuaSnapshotCodec::default();
+        let baseline = round_trip(&codec, &TnuaCharacterSnapshot::default(), None);
+        for rotation in [
+            Quaternion::from_xyzw(0.1, 0.2, 0.3, 0.9).normalize(),
+            Quaternion::from_xyzw(0.1, 0.2, 0.3, -0.9).normalize(),
+            Quaternion::from_xyzw(0.0, 1.0, 0.0, -0.0),
+        ] {
+            let snapshot = TnuaCharacterSnapshot {
+                rotation,
+                ..Default::default()
+            };
+            for baseline in [None, Some(&baseline)] {
+                let decoded = round_trip(&codec, &snapshot, baseline);
+                // `q` and `-q` are the same rotation.
+                assert!(
+                    0.999 < decoded.rotation.dot(rotation).abs(),
+                    "{rotation:?} was decoded as {:?}",
+                    decoded.rotation,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn negative_and_large_varints() {
+        for value in [0, -1, 1, -64, 64, -1 << 40, 1 << 40, i64::MIN, i64::MAX] {
+            let mut encoded = Vec::new();
+            write_varint(value, &mut encoded);
+            let mut reader = encoded.iter().copied();
+            assert_eq!(read_varint(&mut reader), Ok(value));
+            assert_eq!(reader.next(), None);
+        }
+    }
+
+    #[test]
+    fn negative_and_large_deltas() {
+        let codec = TnuaSnapshotCodec::default();
+        let baseline = round_trip(
+            &codec,
+            &TnuaCharacterSnapshot {
+                translation: Vector3::new(1000.0, -20.0, 5.0),
+                velocity: Vector3::new(30.0, 0.0, -30.0),
+                ..Default::default()
+            },
+            None,
+        );
+        let snapshot = TnuaCharacterSnapshot {
+            translation: Vector3::new(-1000.0, 20.0, 4.5),
+            velocity: Vector3::new(-30.0, 0.0, 30.0),
+            ..Default::default()
+        };
+        let decoded = round_trip(&codec, &snapshot, Some(&baseline));
+        assert!(decoded.translation.distance(snapshot.translation) <= codec.position_precision);
+        assert!(decoded.velocity.distance(snapshot.velocity) <= codec.velocity_precision);
+    }
+
+    #[test]
+    fn deltas_that_overflow_wrap_around() {
+        // With such precision the quantized values saturate to the edges of `i64`, so the delta
+        // between them overflows.
+        let codec = TnuaSnapshotCodec {
+            position_precision: 1e-30,
+            ..Default::default()
+        };
+        let baseline = round_trip(
+            &codec,
+            &TnuaCharacterSnapshot {
+                translation: Vector3::new(-1e10, 0.0, 0.0),
+                ..Default::default()
+            },
+            None,
+        );
+        let snapshot = TnuaCharacterSnapshot {
+            translation: Vector3::new(1e10, 0.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            round_trip(&codec, &snapshot, Some(&baseline)),
+            round_trip(&codec, &snapshot, None),
+        );
+    }
+
+    #[test]
+    fn action_id_removed() {
+        let codec = TnuaSnapshotCodec::default();
+        let baseline = round_trip(
+            &codec,
+            &TnuaCharacterSnapshot {
+                action_id: Some(3),
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(baseline.action_id, Some(3));
+        let snapshot = TnuaCharacterSnapshot {
+            action_id: None,
+            ..baseline.clone()
+        };
+        let mut encoded = Vec::new();
+        codec.encode(&snapshot, Some(&baseline), &mut encoded);
+        assert_eq!(encoded.len(), 1);
+        assert_eq!(
+            codec.decode(&encoded, Some(&baseline)).unwrap().action_id,
+            None
+        );
+    }
+
+    #[test]
+    fn missing_baseline() {
+        let codec = TnuaSnapshotCodec::default();
+        let baseline = TnuaCharacterSnapshot::default();
+        let mut encoded = Vec::new();
+        codec.encode(&baseline, Some(&baseline), &mut encoded);
+        assert_eq!(
+            codec.decode(&encoded, None),
+            Err(TnuaSnapshotDecodeError::MissingBaseline)
+        );
+    }
+
+    #[test]
+    fn truncated_input() {
+        let codec = TnuaSnapshotCodec::default();
+        let mut encoded = Vec::new();
+        codec.encode(
+            &TnuaCharacterSnapshot {
+                translation: Vector3::new(1.0, 2.0, 3.0),
+                action_id: Some(1),
+                ..Default::default()
+            },
+            None,
+            &mut encoded,
+        );
+        for length in 0..encoded.len() {
+            assert_eq!(
+                codec.decode(&encoded[..length], None),
+                Err(TnuaSnapshotDecodeError::UnexpectedEnd),
+                "decoding the first {length} bytes",
+            );
+        }
+    }
+
+    #[test]
+    fn over_long_varint() {
+        let codec = TnuaSnapshotCodec::default();
+        let mut encoded = vec![TRANSLATION_CHANGED];
+        encoded.extend([0xff; 10]);
+        encoded.push(0x01);
+        assert_eq!(
+            codec.decode(&encoded, None),
+            Err(TnuaSnapshotDecodeError::InvalidNumber)
+        );
+    }
+}
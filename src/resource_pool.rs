@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::math::Float;
+
+/// The cost of starting an action, paid from a [`TnuaResourcePool`].
+#[derive(Clone, Debug)]
+pub struct TnuaActionCost {
+    /// The amount taken from the pool when the action starts. The action will not start if the
+    /// pool does not have this amount.
+    pub amount: Float,
+
+    /// The fraction (between `0.0` and `1.0`) of the amount to give back if the action is
+    /// cancelled into another action within [`refund_window`](Self::refund_window).
+    pub refund_on_cancel: Float,
+
+    /// The time, in seconds, since the action started during which cancelling it gives a refund.
+    pub refund_window: Float,
+}
+
+impl TnuaActionCost {
+    /// A cost that is never refunded.
+    pub fn new(amount: Float) -> Self {
+        Self {
+            amount,
+            refund_on_cancel: 0.0,
+            refund_window: 0.0,
+        }
+    }
+
+    /// Give back `fraction` of the amount if the action is cancelled within `window` seconds.
+    pub fn with_refund(self, fraction: Float, window: Float) -> Self {
+        Self {
+            refund_on_cancel: fraction,
+            refund_window: window,
+            ..self
+        }
+    }
+}
+
+/// A resource - like stamina - that actions need to pay for starting.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) and
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will consult it before
+/// starting actions that have a [cost](Self::with_cost). Actions that cannot be paid for are
+/// [rejected](crate::TnuaActionRejectionReason::InsufficientResources), so the controls system can
+/// feed them normally and let the controller decide.
+///
+/// ```
+/// # use bevy_tnua::{TnuaActionCost, TnuaResourcePool};
+/// # use bevy_tnua::builtins::TnuaBuiltinDash;
+/// # use bevy_tnua::prelude::*;
+/// let stamina = TnuaResourcePool::new(100.0)
+///     .with_regeneration(20.0, 1.0)
+///     .with_cost(TnuaBuiltinDash::NAME, TnuaActionCost::new(30.0).with_refund(1.0, 0.1));
+/// ```
+///
+/// Continuous costs (like sprinting) are not related to the actions' lifecycle, so the controls
+/// system should pay them directly with [`try_consume`](Self::try_consume).
+#[derive(Component, Clone, Debug)]
+pub struct TnuaResourcePool {
+    /// The amount currently in the pool.
+    pub amount: Float,
+
+    /// The maximal amount the pool can hold.
+    pub max: Float,
+
+    /// The amount, per second, the pool regenerates.
+    pub regeneration: Float,
+
+    /// The time, in seconds, after paying (or while a paid action is running) before the pool
+    /// starts regenerating.
+    pub regeneration_delay: Float,
+
+    costs: HashMap<&'static str, TnuaActionCost>,
+    paid_action: Option<(&'static str, Float)>,
+    since_last_payment: Float,
+}
+
+impl TnuaResourcePool {
+    /// A full pool with no regeneration and no costs.
+    pub fn new(max: Float) -> Self {
+        Self {
+            amount: max,
+            max,
+            regeneration: 0.0,
+            regeneration_delay: 0.0,
+            costs: Default::default(),
+            paid_action: None,
+            since_last_payment: 0.0,
+        }
+    }
+
+    /// Regenerate `per_second` after `delay` seconds without payments.
+    pub fn with_regeneration(self, per_second: Float, delay: Float) -> Self {
+        Self {
+            regeneration: per_second,
+            regeneration_delay: delay,
+            ..self
+        }
+    }
+
+    /// Set the cost of an action. `action` is the name the action is fed with.
+    pub fn with_cost(mut self, action: &'static str, cost: TnuaActionCost) -> Self {
+        self.set_cost(action, cost);
+        self
+    }
+
+    /// Set the cost of an action. `action` is the name the action is fed with.
+    pub fn set_cost(&mut self, action: &'static str, cost: TnuaActionCost) {
+        self.costs.insert(action, cost);
+    }
+
+    /// Remove the cost of an action, making it free.
+    pub fn remove_cost(&mut self, action: &'static str) {
+        self.costs.remove(action);
+    }
+
+    /// The cost of starting an action, if it has one.
+    pub fn cost(&self, action: &'static str) -> Option<&TnuaActionCost> {
+        self.costs.get(action)
+    }
+
+    /// Check if the pool has enough for starting the action.
+    pub fn can_afford(&self, action: &'static str) -> bool {
+        self.cost(action)
+            .map_or(true, |cost| cost.amount <= self.amount)
+    }
+
+    /// Take `amount` from the pool, if it has it. Returns `false` (and takes nothing) if it does
+    /// not.
+    pub fn try_consume(&mut self, amount: Float) -> bool {
+        if amount <= self.amount {
+            self.amount -= amount;
+            self.since_last_payment = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn action_started(&mut self, action: &'static str) {
+        if let Some(cost) = self.costs.get(action) {
+            self.amount = (self.amount - cost.amount).max(0.0);
+            self.since_last_payment = 0.0;
+            self.paid_action = Some((action, 0.0));
+        } else {
+            self.paid_action = None;
+        }
+    }
+
+    pub(crate) fn action_cancelled(&mut self, action: &'static str) {
+        let Some((paid_action, elapsed)) = self.paid_action.take() else {
+            return;
+        };
+        if paid_action != action {
+            return;
+        }
+        if let Some(cost) = self.costs.get(action) {
+            if elapsed <= cost.refund_window {
+                self.amount = (self.amount + cost.refund_on_cancel * cost.amount).min(self.max);
+            }
+        }
+    }
+
+    pub(crate) fn action_ended(&mut self) {
+        self.paid_action = None;
+    }
+
+    pub(crate) fn update(&mut self, frame_duration: Float) {
+        if let Some((_, elapsed)) = self.paid_action.as_mut() {
+            *elapsed += frame_duration;
+            return;
+        }
+        self.since_last_payment += frame_duration;
+        if self.regeneration_delay <= self.since_last_payment {
+            self.amount = (self.amount + self.regeneration * frame_duration).min(self.max);
+        }
+    }
+}
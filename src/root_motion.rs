@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+
+use crate::math::{Float, Quaternion, Vector3};
+
+/// Drives the character with motion extracted from its animations, instead of with the desired
+/// velocity of the basis.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) and have the animation system
+/// [`add`](Self::add) the per-frame root motion deltas to it.
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) will consume the accumulated
+/// deltas every frame, convert them to velocities and pass them to the basis as
+/// [`root_motion`](crate::TnuaBasisContext::root_motion).
+///
+/// [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) supports root motion - while grounded it
+/// follows it exactly, ignoring its
+/// [`desired_velocity`](crate::builtins::TnuaBuiltinWalk::desired_velocity), its acceleration
+/// limits and its turning speed limit. Everything else - the ground sensing,
+/// the floating, the slopes and the collisions with obstacles - works as usual. Only the
+/// horizontal part of the root motion is used, since the vertical position is determined by the
+/// float height.
+#[derive(Component, Clone, Debug)]
+pub struct TnuaRootMotion {
+    /// The translation accumulated since the last time the controller consumed it, in the
+    /// character's coordinate system.
+    pub translation: Vector3,
+
+    /// The rotation accumulated since the last time the controller consumed it.
+    pub rotation: Quaternion,
+}
+
+impl Default for TnuaRootMotion {
+    fn default() -> Self {
+        Self {
+            translation: Vector3::ZERO,
+            rotation: Quaternion::IDENTITY,
+        }
+    }
+}
+
+impl TnuaRootMotion {
+    /// Accumulate a root motion delta, in the character's coordinate system.
+    pub fn add(&mut self, translation: Vector3, rotation: Quaternion) {
+        // Later deltas happen after the earlier rotations.
+        self.translation += self.rotation * translation;
+        self.rotation = (self.rotation * rotation).normalize();
+    }
+
+    /// Convert the accumulated deltas to velocities in world coordinates, and clear them.
+    pub(crate) fn consume(
+        &mut self,
+        character_rotation: Quaternion,
+        frame_duration: Float,
+    ) -> TnuaRootMotionVelocity {
+        let Self {
+            translation,
+            rotation,
+        } = std::mem::take(self);
+        if frame_duration <= 0.0 {
+            return TnuaRootMotionVelocity::default();
+        }
+        // `q` and `-q` are the same rotation, but only one of them is the short way around.
+        let rotation = if rotation.w < 0.0 {
+            -rotation
+        } else {
+            rotation
+        };
+        let (axis, angle) = rotation.to_axis_angle();
+        TnuaRootMotionVelocity {
+            velocity: character_rotation * translation / frame_duration,
+            angvel: character_rotation * (angle / frame_duration * axis),
+        }
+    }
+}
+
+/// The velocities of the root motion of the current frame. See [`TnuaRootMotion`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TnuaRootMotionVelocity {
+    /// The linear velocity, in world coordinates.
+    pub velocity: Vector3,
+
+    /// The angular velocity, as the rotation axis multiplied by the rotation speed in radians per
+    /// second.
+    pub angvel: Vector3,
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::math::{Float, Vector3};
+use crate::stage_tracing::TnuaStageTrace;
+use crate::{TnuaGhostSensor, TnuaPipelineStages, TnuaProximitySensor, TnuaToggle, TnuaUpdateRate};
+
+/// Properties of a surface that affect characters that stand on it.
+///
+/// Add this component to the entities of colliders the characters can stand on (e.g. the entity
+/// of an ice floor, or of a crumbling ledge). The properties of the surface the proximity sensor
+/// detects are passed to the bases and actions in [`TnuaBasisContext`](crate::TnuaBasisContext)
+/// and [`TnuaActionContext`](crate::TnuaActionContext).
+#[derive(Component, Clone, Debug)]
+pub struct TnuaSurfaceProperties {
+    /// Multiply the coyote time (e.g. [`TnuaBuiltinWalk::coyote_time`]) by this number when the
+    /// character leaves this surface.
+    ///
+    /// Values higher than `1.0` give the player more time to jump after walking off the surface,
+    /// lower values give it less time, and `0.0` cancels the coyote time entirely.
+    ///
+    /// [`TnuaBuiltinWalk::coyote_time`]: crate::builtins::TnuaBuiltinWalk::coyote_time
+    pub coyote_time_multiplier: Float,
+}
+
+impl Default for TnuaSurfaceProperties {
+    fn default() -> Self {
+        Self {
+            coyote_time_multiplier: 1.0,
+        }
+    }
+}
+
+/// Makes a surface move the characters that stand on it, like a conveyor belt or a treadmill,
+/// without the entity itself moving.
+///
+/// Add this component to the entities of colliders the characters can stand on.
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) adds the velocity to the
+/// [`entity_linvel`](crate::TnuaProximitySensorOutput::entity_linvel) of the sensor outputs that
+/// detect the surface, so the bases treat it exactly like a moving platform.
+#[derive(Component, Clone, Debug, Default)]
+pub struct TnuaConveyorSurface {
+    /// The velocity, in world coordinates, the surface moves the characters at.
+    pub velocity: Vector3,
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_conveyor_surfaces_system(
+    mut query: Query<(
+        Entity,
+        &mut TnuaProximitySensor,
+        Option<&mut TnuaGhostSensor>,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+    conveyors_query: Query<&TnuaConveyorSurface>,
+) {
+    if conveyors_query.is_empty() {
+        return;
+    }
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::SubservientSensors);
+    query.par_iter_mut().for_each(
+        |(entity, mut sensor, ghost_sensor, tnua_toggle, update_rate)| {
+            let _span = stage_trace.character(entity);
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            // If the sensor was not cast this frame, the velocity was already added to its output.
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            if let Some(output) = sensor.output.as_mut() {
+                if let Ok(conveyor) = conveyors_query.get(output.entity) {
+                    output.entity_linvel += conveyor.velocity;
+                }
+            }
+            if let Some(mut ghost_sensor) = ghost_sensor {
+                for output in ghost_sensor.0.iter_mut() {
+                    if let Ok(conveyor) = conveyors_query.get(output.entity) {
+                        output.entity_linvel += conveyor.velocity;
+                    }
+                }
+            }
+        },
+    );
+}
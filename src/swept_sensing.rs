@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
+use crate::stage_tracing::TnuaStageTrace;
+use crate::{
+    TnuaGhostSensor, TnuaPipelineStages, TnuaProximitySensor, TnuaRigidBodyTracker, TnuaToggle,
+    TnuaUpdateRate,
+};
+
+/// Makes the ground sensor of a character sweep the distance it fell in the last frame, so that
+/// fast falls do not pass through thin platforms without detecting them.
+///
+/// Add this component to a character entity (next to the
+/// [`TnuaController`](crate::prelude::TnuaController)) to enable it. When the character falls,
+/// [`TnuaControllerPlugin`](crate::prelude::TnuaControllerPlugin) moves the
+/// [`cast_origin`](TnuaProximitySensor::cast_origin) of the sensor up by the distance the
+/// character will fall until the next cast (and extends the
+/// [`cast_range`](TnuaProximitySensor::cast_range) by that distance) - so that the cast starts
+/// from where the character was in the previous frame. Once the sensor is cast, the
+/// [`proximity`](crate::TnuaProximitySensorOutput::proximity) of the outputs (of both the
+/// [`TnuaProximitySensor`] and the [`TnuaGhostSensor`]) is corrected to be relative to the
+/// original `cast_origin` again.
+///
+/// This means that the proximity may be negative - if the character fell past the ground it
+/// detected. [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) handles that like any other
+/// case of being below the float height, by pushing the character back up.
+#[derive(Component, Default)]
+pub struct TnuaSweptSensing {
+    /// Only sweep when the character falls faster than this.
+    pub min_fall_speed: Float,
+
+    /// The offset, in the character's coord system, that was added to the cast origin.
+    applied_offset: Vector3,
+    /// The distance that was added to the cast range.
+    applied_sweep: Float,
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn restore_swept_sensors_system(
+    mut query: Query<(
+        Entity,
+        &mut TnuaSweptSensing,
+        &mut TnuaProximitySensor,
+        Option<&mut TnuaGhostSensor>,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+) {
+    let stage_trace = TnuaStageTrace::new(TnuaPipelineStages::SubservientSensors);
+    query.par_iter_mut().for_each(
+        |(entity, mut swept_sensing, mut sensor, ghost_sensor, tnua_toggle, update_rate)| {
+            if swept_sensing.applied_sweep == 0.0 {
+                return;
+            }
+            let _span = stage_trace.character(entity);
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            // If the sensor was not cast this frame, its output was already corrected.
+            if update_rate.is_some_and(|update_rate| !update_rate.should_update()) {
+                return;
+            }
+            let sweep = swept_sensing.applied_sweep;
+            sensor.cast_origin -= swept_sensing.applied_offset;
+            sensor.cast_range -= sweep;
+            if let Some(output) = sensor.output.as_mut() {
+                output.proximity -= sweep;
+            }
+            if let Some(mut ghost_sensor) = ghost_sensor {
+                for output in ghost_sensor.0.iter_mut() {
+                    output.proximity -= sweep;
+                }
+            }
+            swept_sensing.applied_offset = Vector3::ZERO;
+            swept_sensing.applied_sweep = 0.0;
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_swept_sensors_system(
+    time: Res<Time>,
+    mut query: Query<(
+        &mut TnuaSweptSensing,
+        &mut TnuaProximitySensor,
+        &TnuaRigidBodyTracker,
+        Option<&TnuaToggle>,
+        Option<&TnuaUpdateRate>,
+    )>,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    query.par_iter_mut().for_each(
+        |(mut swept_sensing, mut sensor, tracker, tnua_toggle, update_rate)| {
+            match tnua_toggle.copied().unwrap_or_default() {
+                TnuaToggle::Disabled => return,
+                TnuaToggle::SenseOnly => {}
+                TnuaToggle::Enabled => {}
+            }
+            let frame_duration = match update_rate {
+                Some(update_rate) if !update_rate.should_update() => return,
+                // Estimate the time until the next cast by the time since the previous one.
+                Some(update_rate) => update_rate.time_since_update(),
+                None => frame_duration,
+            };
+            let Ok(up_direction) = Dir3::new(-tracker.gravity.f32()) else {
+                return;
+            };
+            let up = up_direction.adjust_precision();
+            let fall_speed = -tracker.velocity.dot(up);
+            if fall_speed <= swept_sensing.min_fall_speed.max(0.0) {
+                return;
+            }
+            let sweep = fall_speed * frame_duration;
+            // The cast origin is in the character's coord system, but the sweep is along the
+            // world's up direction.
+            let offset = tracker.rotation.inverse() * (sweep * up);
+            sensor.cast_origin += offset;
+            sensor.cast_range += sweep;
+            swept_sensing.applied_offset = offset;
+            swept_sensing.applied_sweep = sweep;
+        },
+    );
+}
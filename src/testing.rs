@@ -0,0 +1,256 @@
+//! Utilities for writing regression tests for the movement of Tnua controlled characters.
+//!
+//! [`TnuaTestApp`] builds a minimal headless app, with the physics backend of the user's choice,
+//! that advances by a fixed frame duration on every step - so the same test always produces the
+//! same simulation. Tests can then feed the controller every frame and check the position, the
+//! velocity and the controller's state of the character:
+//!
+//! ```
+//! use avian3d::prelude::*;
+//! use bevy::prelude::*;
+//! use bevy_tnua::prelude::*;
+//! use bevy_tnua::testing::{assert_near, TnuaTestApp};
+//! use bevy_tnua_avian3d::TnuaAvian3dPlugin;
+//!
+//! let mut test_app = TnuaTestApp::new((
+//!     // Avian needs these when Bevy's rendering and scenes are enabled, even if the
+//!     // colliders are not created from meshes:
+//!     AssetPlugin::default(),
+//!     bevy::scene::ScenePlugin,
+//!     PhysicsPlugins::default(),
+//!     TnuaAvian3dPlugin::default(),
+//! ));
+//! test_app.app_mut().init_asset::<Mesh>();
+//! test_app
+//!     .world_mut()
+//!     .spawn((RigidBody::Static, Collider::half_space(Vec3::Y)));
+//! let character = test_app
+//!     .world_mut()
+//!     .spawn((
+//!         TransformBundle::from_transform(Transform::from_xyz(0.0, 2.0, 0.0)),
+//!         RigidBody::Dynamic,
+//!         Collider::capsule(0.5, 1.0),
+//!         LockedAxes::ROTATION_LOCKED,
+//!         TnuaControllerBundle::default(),
+//!     ))
+//!     .id();
+//!
+//! let walk = |desired_velocity: Vec3| TnuaBuiltinWalk {
+//!     desired_velocity,
+//!     float_height: 1.5,
+//!     ..Default::default()
+//! };
+//!
+//! // Let the character settle at its float height:
+//! test_app.step_controlling(character, 120, |controller| {
+//!     controller.basis(walk(Vec3::ZERO));
+//! });
+//! assert!(!test_app.controller(character).is_airborne().unwrap());
+//! assert_near(test_app.position(character).y, 1.5, 0.05);
+//!
+//! // The walk basis reaches the desired speed within a second:
+//! test_app.step_controlling(character, 60, |controller| {
+//!     controller.basis(walk(Vec3::X * 5.0));
+//! });
+//! assert_near(test_app.velocity(character).x, 5.0, 0.05);
+//! ```
+//!
+//! The physics backend is only passed as plugins, so the test app works with any of them. Tnua's
+//! own plugins are registered in `Update`, matching the `Default` of the backend integration
+//! plugins.
+use std::time::Duration;
+
+use bevy::app::Plugins;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_tnua_physics_integration_layer::data_for_backends::TnuaRigidBodyTracker;
+
+use crate::controller::{TnuaController, TnuaControllerPlugin};
+use crate::math::{AdjustPrecision, Float, Vector3};
+
+/// A headless app for testing Tnua controlled characters. See the [module docs](self).
+pub struct TnuaTestApp {
+    app: App,
+    frame_duration: Duration,
+}
+
+impl TnuaTestApp {
+    /// Create a test app with the given physics backend plugins, running at 60 frames per second.
+    ///
+    /// `backend_plugins` should contain both the physics engine plugins and the Tnua integration
+    /// plugin for that engine, and any plugin the engine depends on that is not part of Bevy's
+    /// `MinimalPlugins` (e.g. `AssetPlugin`, if colliders are created from meshes).
+    /// [`TnuaControllerPlugin`] is added automatically.
+    pub fn new<M>(backend_plugins: impl Plugins<M>) -> Self {
+        Self::with_frame_duration(backend_plugins, Duration::from_secs(1) / 60)
+    }
+
+    /// Create a test app with the given physics backend plugins, advancing by `frame_duration` on
+    /// every step.
+    pub fn with_frame_duration<M>(
+        backend_plugins: impl Plugins<M>,
+        frame_duration: Duration,
+    ) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_duration));
+        app.add_plugins(backend_plugins);
+        app.add_plugins(TnuaControllerPlugin::default());
+        app.finish();
+        app.cleanup();
+        Self {
+            app,
+            frame_duration,
+        }
+    }
+
+    /// The underlying Bevy app, for adding more plugins and systems.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// The world of the underlying Bevy app.
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    /// The world of the underlying Bevy app, for spawning the level and the characters.
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// The duration of a single step.
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+
+    /// Run the app for `frames` frames.
+    pub fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Run the app for `frames` frames, calling `control` with the controller of `entity` before
+    /// each one.
+    ///
+    /// Like the controls system in a game, `control` must feed the basis and the action every
+    /// frame.
+    pub fn step_controlling(
+        &mut self,
+        entity: Entity,
+        frames: usize,
+        mut control: impl FnMut(&mut TnuaController),
+    ) {
+        for _ in 0..frames {
+            control(&mut self.controller_mut(entity));
+            self.app.update();
+        }
+    }
+
+    /// Run the app until `predicate` returns `true`, but for no more than `max_frames` frames.
+    ///
+    /// Returns the number of frames it took, or `None` if `predicate` did not return `true` within
+    /// `max_frames` frames. `predicate` is checked before the first frame as well, so a predicate
+    /// that is already `true` returns `Some(0)`.
+    pub fn step_until(
+        &mut self,
+        max_frames: usize,
+        mut predicate: impl FnMut(&World) -> bool,
+    ) -> Option<usize> {
+        for frame in 0..max_frames {
+            if predicate(self.app.world()) {
+                return Some(frame);
+            }
+            self.app.update();
+        }
+        predicate(self.app.world()).then_some(max_frames)
+    }
+
+    /// The controller of the character.
+    ///
+    /// # Panics
+    ///
+    /// If the entity does not have a [`TnuaController`].
+    pub fn controller(&self, entity: Entity) -> &TnuaController {
+        self.app
+            .world()
+            .get::<TnuaController>(entity)
+            .expect("entity does not have a TnuaController")
+    }
+
+    /// The controller of the character, for feeding it between steps.
+    ///
+    /// # Panics
+    ///
+    /// If the entity does not have a [`TnuaController`].
+    pub fn controller_mut(&mut self, entity: Entity) -> Mut<TnuaController> {
+        self.app
+            .world_mut()
+            .get_mut::<TnuaController>(entity)
+            .expect("entity does not have a TnuaController")
+    }
+
+    /// The position of the entity, as written by the physics backend at the end of the last step.
+    ///
+    /// # Panics
+    ///
+    /// If the entity does not have a `Transform`.
+    pub fn position(&self, entity: Entity) -> Vector3 {
+        self.app
+            .world()
+            .get::<Transform>(entity)
+            .expect("entity does not have a Transform")
+            .translation
+            .adjust_precision()
+    }
+
+    /// The velocity of the character, as sensed by the physics backend at the beginning of the
+    /// last step.
+    ///
+    /// This is the velocity that the controller saw, which means it does not include the effect of
+    /// the last step's motor.
+    ///
+    /// # Panics
+    ///
+    /// If the entity does not have a [`TnuaRigidBodyTracker`] (which is part of the
+    /// [`TnuaControllerBundle`](crate::prelude::TnuaControllerBundle)).
+    pub fn velocity(&self, entity: Entity) -> Vector3 {
+        self.app
+            .world()
+            .get::<TnuaRigidBodyTracker>(entity)
+            .expect("entity does not have a TnuaRigidBodyTracker")
+            .velocity
+    }
+}
+
+/// Assert that `actual` is within `tolerance` of `expected`.
+///
+/// Works for both scalars and vectors - for vectors, the tolerance is for the distance between
+/// them.
+#[track_caller]
+pub fn assert_near<T: TnuaTestApproximate>(actual: T, expected: T, tolerance: Float) {
+    let distance = actual.distance_to(expected);
+    assert!(
+        distance <= tolerance,
+        "expected {expected:?} (with tolerance {tolerance}), got {actual:?} (off by {distance})",
+    );
+}
+
+/// Values that can be compared with [`assert_near`].
+pub trait TnuaTestApproximate: Copy + std::fmt::Debug {
+    /// The distance between two values.
+    fn distance_to(self, other: Self) -> Float;
+}
+
+impl TnuaTestApproximate for Float {
+    fn distance_to(self, other: Self) -> Float {
+        (self - other).abs()
+    }
+}
+
+impl TnuaTestApproximate for Vector3 {
+    fn distance_to(self, other: Self) -> Float {
+        self.distance(other)
+    }
+}